@@ -0,0 +1,289 @@
+use meerai_core::{ToolDefinition, ToolError, ToolMetadata, ToolOutput, Toolset, async_trait};
+use serde::Deserialize;
+use tokio::sync::Mutex;
+
+use crate::multi_turn_agent::MultiTurnAgent;
+
+#[derive(Debug, Deserialize)]
+struct DelegateArgs {
+    task: String,
+}
+
+/// Exposes an agent as a toolset with a single `{name}-delegate` function.
+///
+/// Calling the function runs the agent on the given task until it answers and returns the
+/// answer, so a coordinating agent can hand tasks off to specialized agents. The agent runs one
+/// task at a time, concurrent calls wait for the running one to finish. The function is
+/// side-effecting if any tool of the agent is, so delegating needs the caller's approval while
+/// the agent's own approver still decides on each of its calls.
+#[derive(Debug)]
+pub struct AgentToolset {
+    name: String,
+    description: String,
+    side_effecting: bool,
+    agent: Mutex<MultiTurnAgent>,
+}
+
+impl AgentToolset {
+    /// # Arguments
+    ///
+    /// * `name` - Name of the toolset, the function is called `{name}-delegate`
+    /// * `description` - Tells the calling model what kind of tasks the agent handles
+    /// * `agent` - The agent running the delegated tasks
+    pub fn new(
+        name: impl Into<String>,
+        description: impl Into<String>,
+        agent: MultiTurnAgent,
+    ) -> Self {
+        let side_effecting = agent
+            .tool_definitions()
+            .iter()
+            .any(|definition| definition.metadata.side_effecting);
+        Self {
+            name: name.into(),
+            description: description.into(),
+            side_effecting,
+            agent: Mutex::new(agent),
+        }
+    }
+
+    fn function_name(&self) -> String {
+        format!("{}-delegate", self.name)
+    }
+}
+
+#[async_trait]
+impl Toolset for AgentToolset {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn definition(&self) -> Vec<ToolDefinition> {
+        vec![ToolDefinition {
+            r#type: "function".to_string(),
+            name: self.function_name(),
+            description: self.description.clone(),
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "task": {
+                        "type": "string",
+                        "description": "Complete description of the task, the agent does not see this conversation"
+                    }
+                },
+                "required": ["task"]
+            }),
+            // Running the task again repeats everything the agent did
+            metadata: ToolMetadata {
+                side_effecting: self.side_effecting,
+                idempotent: Some(false),
+                ..Default::default()
+            },
+        }]
+    }
+
+    fn contain(&self, fn_name: &str) -> bool {
+        fn_name == self.function_name()
+    }
+
+    async fn invoke(&self, fn_name: &str, args: &str) -> Result<ToolOutput, ToolError> {
+        if !self.contain(fn_name) {
+            return Err(ToolError::InvalidFunctionName(fn_name.to_string()));
+        }
+        let args: DelegateArgs = serde_json::from_str(args)?;
+
+        let mut agent = self.agent.lock().await;
+        match agent.prompt(&args.task).await {
+            Ok(answer) => Ok(ToolOutput::Text(answer)),
+            // The calling model decides whether to rephrase the task or carry on without it
+            Err(err) => Ok(ToolOutput::Fail(format!(
+                "agent '{}' failed: {:#}",
+                self.name, err
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::VecDeque,
+        pin::Pin,
+        sync::{Arc, Mutex},
+    };
+
+    use meerai_core::{
+        ToolCall, ToolRegistry,
+        chat_completion::{
+            ChatCompletion, ChatCompletionError, ChatCompletionRequest, ChatCompletionResponse,
+            ChatMessage,
+        },
+    };
+
+    use super::*;
+
+    /// Answers with scripted responses and records the requests it received.
+    #[derive(Default)]
+    struct ScriptedChatCompletion {
+        responses: Mutex<VecDeque<ChatCompletionResponse>>,
+        requests: Arc<Mutex<Vec<ChatCompletionRequest>>>,
+    }
+
+    impl ScriptedChatCompletion {
+        fn new(responses: Vec<ChatCompletionResponse>) -> Self {
+            Self {
+                responses: Mutex::new(responses.into()),
+                ..Default::default()
+            }
+        }
+    }
+
+    #[async_trait]
+    impl ChatCompletion for ScriptedChatCompletion {
+        async fn send(
+            &self,
+            request: &ChatCompletionRequest,
+        ) -> Result<ChatCompletionResponse, ChatCompletionError> {
+            self.requests.lock().unwrap().push(request.clone());
+            self.responses
+                .lock()
+                .unwrap()
+                .pop_front()
+                .ok_or_else(|| ChatCompletionError::Literal("no response left".to_string()))
+        }
+    }
+
+    fn answer(text: &str) -> ChatCompletionResponse {
+        ChatCompletionResponse {
+            messages: vec![ChatMessage::Assistant(text.to_string())],
+            tool_calls: vec![],
+            usage: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_delegate() {
+        let researcher = ScriptedChatCompletion::new(vec![answer("The tweet is about BTC")]);
+        let researcher_requests = researcher.requests.clone();
+        let researcher = AgentToolset::new(
+            "researcher",
+            "Researches tweets",
+            MultiTurnAgent::new_without_tools(researcher, "You research".to_string()),
+        );
+
+        let coordinator = ScriptedChatCompletion::new(vec![
+            ChatCompletionResponse {
+                messages: vec![],
+                tool_calls: vec![ToolCall {
+                    id: "call_1".to_string(),
+                    name: "researcher-delegate".to_string(),
+                    args: r#"{"task": "Research the tweet"}"#.to_string(),
+                }],
+                usage: None,
+            },
+            answer("Posted about BTC"),
+        ]);
+        let coordinator_requests = coordinator.requests.clone();
+        let mut coordinator = MultiTurnAgent::new(
+            coordinator,
+            ToolRegistry::try_from(vec![Box::pin(researcher) as Pin<Box<dyn Toolset>>]).unwrap(),
+            "You coordinate".to_string(),
+        );
+
+        let output = coordinator.prompt("Post about the tweet").await.unwrap();
+        assert_eq!(output, "Posted about BTC");
+
+        let researcher_requests = researcher_requests.lock().unwrap();
+        assert!(matches!(
+            &researcher_requests[0].messages[1],
+            ChatMessage::User(task) if task == "Research the tweet"
+        ));
+        let coordinator_requests = coordinator_requests.lock().unwrap();
+        assert!(matches!(
+            coordinator_requests[1].messages.last().unwrap(),
+            ChatMessage::ToolResult(_, result) if result == "Success: The tweet is about BTC"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_delegate_failure() {
+        let toolset = AgentToolset::new(
+            "writer",
+            "Writes posts",
+            MultiTurnAgent::new_without_tools(
+                ScriptedChatCompletion::default(),
+                "You write".to_string(),
+            ),
+        );
+
+        let output = toolset
+            .invoke("writer-delegate", r#"{"task": "Write a post"}"#)
+            .await
+            .unwrap();
+        assert_eq!(
+            output,
+            ToolOutput::Fail(
+                "agent 'writer' failed: unexpected error: no response left".to_string()
+            )
+        );
+
+        let err = toolset.invoke("writer-write", "{}").await.unwrap_err();
+        assert!(matches!(err, ToolError::InvalidFunctionName(_)));
+    }
+
+    struct PostToolset;
+
+    #[async_trait]
+    impl Toolset for PostToolset {
+        fn name(&self) -> String {
+            "bsky".to_string()
+        }
+
+        fn definition(&self) -> Vec<ToolDefinition> {
+            vec![ToolDefinition {
+                r#type: "function".to_string(),
+                name: "bsky-post".to_string(),
+                description: "Posts a text".to_string(),
+                parameters: serde_json::json!({ "type": "string" }),
+                metadata: ToolMetadata {
+                    side_effecting: true,
+                    ..Default::default()
+                },
+            }]
+        }
+
+        fn contain(&self, fn_name: &str) -> bool {
+            fn_name == "bsky-post"
+        }
+
+        async fn invoke(&self, _fn_name: &str, _args: &str) -> Result<ToolOutput, ToolError> {
+            Ok(ToolOutput::Text("posted".to_string()))
+        }
+    }
+
+    #[test]
+    fn test_delegate_side_effecting() {
+        let researcher = AgentToolset::new(
+            "researcher",
+            "Researches tweets",
+            MultiTurnAgent::new_without_tools(
+                ScriptedChatCompletion::default(),
+                "You research".to_string(),
+            ),
+        );
+        assert!(!researcher.definition()[0].metadata.side_effecting);
+
+        // Delegating to an agent that posts has the effects of posting
+        let writer = AgentToolset::new(
+            "writer",
+            "Writes posts",
+            MultiTurnAgent::new(
+                ScriptedChatCompletion::default(),
+                ToolRegistry::try_from(vec![Box::pin(PostToolset) as Pin<Box<dyn Toolset>>])
+                    .unwrap(),
+                "You write".to_string(),
+            ),
+        );
+        assert!(writer.definition()[0].metadata.side_effecting);
+    }
+}
@@ -0,0 +1,102 @@
+use meerai_core::{ToolCall, ToolDefinition, async_trait};
+use tokio::sync::{mpsc, oneshot};
+
+/// Decision of an approver about a side-effecting tool call.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Approval {
+    /// Run the call as requested
+    Approve,
+
+    /// Run the call with these arguments instead
+    Edit(String),
+
+    /// Do not run the call, the reason is sent to the model
+    Reject(String),
+}
+
+/// Decides whether a side-effecting tool call may run.
+#[async_trait]
+pub trait Approver: Send + Sync {
+    async fn approve(&self, tool_call: &ToolCall, definition: &ToolDefinition) -> Approval;
+}
+
+impl std::fmt::Debug for dyn Approver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Approver")
+    }
+}
+
+#[async_trait]
+impl<F> Approver for F
+where
+    F: Fn(&ToolCall, &ToolDefinition) -> Approval + Send + Sync,
+{
+    async fn approve(&self, tool_call: &ToolCall, definition: &ToolDefinition) -> Approval {
+        self(tool_call, definition)
+    }
+}
+
+/// Approves every call.
+///
+/// Side-effecting tools are rejected while no approver is set, this approver opts out of the
+/// check for agents trusted to run them unattended.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AutoApprover;
+
+#[async_trait]
+impl Approver for AutoApprover {
+    async fn approve(&self, _tool_call: &ToolCall, _definition: &ToolDefinition) -> Approval {
+        Approval::Approve
+    }
+}
+
+/// A pending tool call waiting for a decision, e.g. from a human reviewer.
+#[derive(Debug)]
+pub struct ApprovalRequest {
+    pub tool_call: ToolCall,
+    pub definition: ToolDefinition,
+    respond_to: oneshot::Sender<Approval>,
+}
+
+impl ApprovalRequest {
+    pub fn respond(self, approval: Approval) {
+        // The agent may have stopped waiting, in which case there is nobody to tell
+        let _ = self.respond_to.send(approval);
+    }
+}
+
+/// Forwards approval requests to a channel and waits for the answer.
+///
+/// Calls are rejected if the receiving side is dropped or drops a request unanswered.
+#[derive(Clone, Debug)]
+pub struct ChannelApprover {
+    sender: mpsc::Sender<ApprovalRequest>,
+}
+
+impl ChannelApprover {
+    /// Creates an approver together with the receiver of its requests.
+    pub fn new(buffer: usize) -> (Self, mpsc::Receiver<ApprovalRequest>) {
+        let (sender, receiver) = mpsc::channel(buffer);
+        (Self { sender }, receiver)
+    }
+}
+
+#[async_trait]
+impl Approver for ChannelApprover {
+    async fn approve(&self, tool_call: &ToolCall, definition: &ToolDefinition) -> Approval {
+        let (respond_to, response) = oneshot::channel();
+        let request = ApprovalRequest {
+            tool_call: tool_call.clone(),
+            definition: definition.clone(),
+            respond_to,
+        };
+
+        if self.sender.send(request).await.is_err() {
+            return Approval::Reject("no approver is available".to_string());
+        }
+
+        response
+            .await
+            .unwrap_or_else(|_| Approval::Reject("the approver did not answer".to_string()))
+    }
+}
@@ -1,5 +1,21 @@
+mod agent_toolset;
+mod approval;
 mod multi_turn_agent;
+mod output_policy;
+mod telemetry;
+mod tool_executor;
 
 pub mod agents {
-    pub use crate::multi_turn_agent::MultiTurnAgent;
+    pub use crate::{
+        agent_toolset::AgentToolset,
+        multi_turn_agent::{MultiTurnAgent, MultiTurnAgentConfig},
+        output_policy::{OutputPolicy, READ_OUTPUT_FUNCTION, ReadOutputToolset},
+    };
+}
+
+pub mod tools {
+    pub use crate::{
+        approval::{Approval, ApprovalRequest, Approver, AutoApprover, ChannelApprover},
+        tool_executor::{ToolExecutor, ToolExecutorConfig},
+    };
 }
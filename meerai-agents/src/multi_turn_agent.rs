@@ -1,10 +1,18 @@
-use std::time::Duration;
 use std::{pin::Pin, vec};
 
 use anyhow::{Result, anyhow};
 use meerai_core::{
-    ToolCall, ToolDefinition, ToolOutput, Toolset,
-    chat_completion::{ChatCompletion, ChatCompletionRequest, ChatMessage},
+    Artifact, ToolDefinition, ToolOutput, ToolRegistry, ToolRegistryError, Toolset,
+    chat_completion::{CacheBreakpoint, ChatCompletion, ChatCompletionRequest, ChatMessage},
+};
+
+use tracing::{Instrument, Span, field::Empty, info_span};
+
+use crate::{
+    approval::Approver,
+    output_policy::{OutputLimiter, OutputPolicy},
+    telemetry::send_traced,
+    tool_executor::{ToolExecutor, ToolExecutorConfig},
 };
 
 /// Configuration for the MultiTurnAgent.
@@ -15,6 +23,27 @@ pub struct MultiTurnAgentConfig {
 
     /// Maximum number of times to retry a failed tool invocation
     pub max_retries: usize,
+
+    /// Maximum number of tool calls of a single response executed at the same time
+    pub max_concurrent_tool_calls: usize,
+
+    /// Whether to mark the system prompt and tool definitions as cacheable. Off by default, as
+    /// caching is not free everywhere: Gemini bills every created cache and OpenRouter sends
+    /// the marked messages as content parts
+    pub prompt_caching: bool,
+
+    /// How tool outputs too large for the chat history are shortened. Policies that shorten
+    /// outputs add a tool reading the full output
+    pub output_policy: OutputPolicy,
+}
+
+impl MultiTurnAgentConfig {
+    fn tool_executor_config(&self) -> ToolExecutorConfig {
+        ToolExecutorConfig {
+            max_retries: self.max_retries,
+            max_concurrent_calls: self.max_concurrent_tool_calls,
+        }
+    }
 }
 
 impl Default for MultiTurnAgentConfig {
@@ -22,6 +51,9 @@ impl Default for MultiTurnAgentConfig {
         Self {
             max_cycles: 10,
             max_retries: 3,
+            max_concurrent_tool_calls: 4,
+            prompt_caching: false,
+            output_policy: OutputPolicy::Unlimited,
         }
     }
 }
@@ -40,12 +72,18 @@ pub struct MultiTurnAgent {
     /// History of the conversation
     chat_history: Vec<ChatMessage>,
 
-    /// Available tools for the agent to use
-    tools: Vec<Pin<Box<dyn Toolset>>>,
+    /// Executes the tool calls of the agent
+    tool_executor: ToolExecutor,
 
     /// System prompt that guides the agent's behavior
     system_prompt: String,
 
+    /// Artifacts produced by tools during the last prompt
+    artifacts: Vec<Artifact>,
+
+    /// Shortens large tool outputs and keeps them for the last prompt
+    output_limiter: OutputLimiter,
+
     /// Configuration for the agent
     config: MultiTurnAgentConfig,
 }
@@ -56,36 +94,47 @@ impl MultiTurnAgent {
     /// # Arguments
     ///
     /// * `chat_completion` - The chat completion provider to use
-    /// * `tools` - A vector of tools available to the agent
+    /// * `tools` - The registry of tools available to the agent
     /// * `system_prompt` - The system prompt that guides the agent's behavior
     pub fn new(
         chat_completion: impl ChatCompletion + 'static,
-        tools: Vec<Pin<Box<dyn Toolset>>>,
+        tools: ToolRegistry,
         system_prompt: String,
     ) -> Self {
-        Self {
-            chat_completion: Box::pin(chat_completion),
-            chat_history: vec![],
+        Self::new_with_config(
+            chat_completion,
             tools,
             system_prompt,
-            config: MultiTurnAgentConfig::default(),
-        }
+            MultiTurnAgentConfig::default(),
+        )
+        .expect("the default output policy adds no tools")
     }
 
     /// Creates a new MultiTurnAgent with custom configuration
+    ///
+    /// Fails if the output policy shortens outputs and `tools` already has a function named
+    /// [`READ_OUTPUT_FUNCTION`](crate::output_policy::READ_OUTPUT_FUNCTION).
     pub fn new_with_config(
         chat_completion: impl ChatCompletion + 'static,
-        tools: Vec<Pin<Box<dyn Toolset>>>,
+        tools: ToolRegistry,
         system_prompt: String,
         config: MultiTurnAgentConfig,
-    ) -> Self {
-        Self {
+    ) -> Result<Self, ToolRegistryError> {
+        let mut tool_executor = ToolExecutor::new_with_config(tools, config.tool_executor_config());
+        let output_limiter = OutputLimiter::new(config.output_policy.clone());
+        if let Some(toolset) = output_limiter.toolset() {
+            tool_executor.add_tool(toolset)?;
+        }
+
+        Ok(Self {
             chat_completion: Box::pin(chat_completion),
             chat_history: vec![],
-            tools,
+            tool_executor,
             system_prompt,
+            artifacts: vec![],
+            output_limiter,
             config,
-        }
+        })
     }
 
     /// Creates a new MultiTurnAgent without any tools.
@@ -98,7 +147,7 @@ impl MultiTurnAgent {
         chat_completion: impl ChatCompletion + 'static,
         system_prompt: String,
     ) -> Self {
-        Self::new(chat_completion, vec![], system_prompt)
+        Self::new(chat_completion, ToolRegistry::new(), system_prompt)
     }
 
     /// Adds a single tool to the agent.
@@ -106,8 +155,10 @@ impl MultiTurnAgent {
     /// # Arguments
     ///
     /// * `tool` - The tool to add
-    pub fn add_tool(&mut self, tool: impl Toolset + 'static) {
-        self.tools.push(Box::pin(tool));
+    ///
+    /// Fails if one of the tool's function names is already registered.
+    pub fn add_tool(&mut self, tool: impl Toolset + 'static) -> Result<(), ToolRegistryError> {
+        self.tool_executor.add_tool(tool)
     }
 
     /// Adds multiple tools to the agent.
@@ -115,8 +166,37 @@ impl MultiTurnAgent {
     /// # Arguments
     ///
     /// * `tools` - A vector of tools to add
-    pub fn add_tools(&mut self, tools: Vec<Pin<Box<dyn Toolset>>>) {
-        self.tools.extend(tools);
+    ///
+    /// Fails on the first tool whose function names are already registered.
+    pub fn add_tools(
+        &mut self,
+        tools: Vec<Pin<Box<dyn Toolset>>>,
+    ) -> Result<(), ToolRegistryError> {
+        self.tool_executor.add_tools(tools)
+    }
+
+    /// Requires approval from `approver` before the agent runs side-effecting tools.
+    ///
+    /// # Arguments
+    ///
+    /// * `approver` - Approves, edits or rejects side-effecting tool calls
+    pub fn set_approver(&mut self, approver: impl Approver + 'static) {
+        self.tool_executor.set_approver(approver);
+    }
+
+    /// Returns the definitions of the tools available to the agent.
+    pub fn tool_definitions(&self) -> Vec<ToolDefinition> {
+        self.tool_executor.definitions()
+    }
+
+    /// Returns the artifacts produced by tools during the last prompt.
+    pub fn artifacts(&self) -> &[Artifact] {
+        &self.artifacts
+    }
+
+    /// Returns the full tool output that was shortened during the last prompt under `reference`.
+    pub fn full_output(&self, reference: &str) -> Option<String> {
+        self.output_limiter.full_output(reference)
     }
 
     /// Sends a prompt to the agent and processes the response.
@@ -125,7 +205,8 @@ impl MultiTurnAgent {
     /// 1. Collects tool definitions
     /// 2. Adds the system and user prompts to the chat history
     /// 3. Sends the request to the chat completion provider
-    /// 4. Processes any tool calls
+    /// 4. Executes every tool call of the response and adds the results to the chat history,
+    ///    followed by the media that tools returned
     /// 5. Returns the final response
     ///
     /// # Arguments
@@ -134,20 +215,27 @@ impl MultiTurnAgent {
     ///
     /// # Returns
     ///
-    /// A Result containing the agent's response as a String
+    /// A Result containing the last assistant message, or the reason given to a stop tool
+    #[tracing::instrument(name = "agent_run", skip_all, fields(cycles = Empty))]
     pub async fn prompt(&mut self, prompt: &str) -> Result<String> {
-        let mut tool_definitions = Vec::<ToolDefinition>::new();
-        for tool in &self.tools {
-            tool_definitions.extend(tool.definition());
-        }
+        let tool_definitions = self.tool_executor.definitions();
 
-        println!("[Agent] Current Prompt: {:?}", prompt);
+        tracing::debug!(prompt, "running prompt");
         self.chat_history.clear();
+        self.artifacts.clear();
+        self.output_limiter.clear();
         self.chat_history
             .push(ChatMessage::System(self.system_prompt.clone()));
         self.chat_history
             .push(ChatMessage::User(prompt.to_string()));
 
+        // The system prompt and tools are the same on every cycle
+        let cache_breakpoints = if self.config.prompt_caching {
+            vec![CacheBreakpoint::Tools, CacheBreakpoint::Message(0)]
+        } else {
+            vec![]
+        };
+
         let mut cycle_count = 0;
 
         loop {
@@ -160,86 +248,137 @@ impl MultiTurnAgent {
             }
 
             cycle_count += 1;
+            Span::current().record("cycles", cycle_count);
+            let cycle_span = info_span!("agent_cycle", cycle = cycle_count, tool_calls = Empty);
+
             let chat_completion_request = ChatCompletionRequest {
                 model: None,
                 messages: self.chat_history.clone(),
                 tool_definitions: tool_definitions.clone(),
+                cache_breakpoints: cache_breakpoints.clone(),
             };
 
-            let chat_completion_response =
-                self.chat_completion.send(&chat_completion_request).await?;
+            let chat_completion_response = send_traced(
+                self.chat_completion.as_ref().get_ref(),
+                &chat_completion_request,
+            )
+            .instrument(cycle_span.clone())
+            .await?;
             self.chat_history
                 .append(chat_completion_response.messages.clone().as_mut());
 
             // If no tool calls, return the final response
             if chat_completion_response.tool_calls.is_empty() {
-                println!("[Agent] Chat Completion: {:?}", chat_completion_response);
-
-                let last_message = match chat_completion_response.messages.last() {
-                    Some(msg) => format!("{:?}", msg),
-                    None => "No response".to_string(),
-                };
-
-                return Ok(format!("Chat Completion: {}", last_message));
+                cycle_span.in_scope(|| {
+                    tracing::debug!(response = ?chat_completion_response, "final response");
+                });
+
+                let answer = chat_completion_response
+                    .messages
+                    .iter()
+                    .rev()
+                    .find_map(|message| match message {
+                        ChatMessage::Assistant(text) => Some(text.clone()),
+                        _ => None,
+                    })
+                    .unwrap_or_else(|| "No response".to_string());
+
+                return Ok(answer);
             }
 
-            let ToolCall { name, args, .. } = chat_completion_response.tool_calls[0].clone();
-            println!("[Tool] Invoking: '{}' with args: {:?}", name, args);
-
-            let tool = self
-                .tools
-                .iter()
-                .find(|tool| tool.contain(&name))
-                .ok_or_else(|| anyhow!("Tool not found: {}", name))?;
+            let tool_calls = chat_completion_response.tool_calls;
+            self.chat_history
+                .push(ChatMessage::ToolCalls(tool_calls.clone()));
+
+            cycle_span.record("tool_calls", tool_calls.len());
+            let tool_outputs = self
+                .tool_executor
+                .execute_all(&tool_calls)
+                .instrument(cycle_span)
+                .await;
+
+            let mut stop_reason = None;
+            // Media follows all tool results, as providers expect the results right after the calls
+            let mut attachments = vec![];
+            for (tool_call, tool_output) in tool_calls.into_iter().zip(tool_outputs) {
+                let tool_output = tool_output?;
+                if let ToolOutput::Stop(reason) = &tool_output {
+                    stop_reason = Some(reason.clone());
+                }
 
-            // Handle tool invocation with retry logic
-            let tool_output = self.invoke_tool_with_retry(tool, &name, &args).await?;
-            println!("[Tool] Result: {:?}", tool_output);
+                let content = self
+                    .output_limiter
+                    .limit(&tool_call.name, tool_output.to_string())
+                    .await;
+                self.chat_history
+                    .push(ChatMessage::ToolResult(tool_call, content));
+                match tool_output {
+                    ToolOutput::Artifact(artifact) => self.artifacts.push(artifact),
+                    ToolOutput::Attachment(attachment) if attachment.as_text().is_none() => {
+                        attachments.push(ChatMessage::Attachment(attachment))
+                    }
+                    _ => {}
+                }
+            }
+            self.chat_history.extend(attachments);
 
-            if let ToolOutput::Stop(_) = tool_output {
+            if let Some(reason) = stop_reason {
                 self.chat_history.clear();
-                return Ok("Finished".to_string());
+                return Ok(reason);
             }
-
-            self.chat_history.push(ChatMessage::Assistant(format!(
-                "Receive result: {} from tool: {}",
-                tool_output, name,
-            )));
         }
     }
+}
 
-    /// Invokes a tool with retry logic
-    async fn invoke_tool_with_retry(
-        &self,
-        tool: &Pin<Box<dyn Toolset>>,
-        name: &str,
-        args: &str,
-    ) -> Result<ToolOutput> {
-        let mut retry_count = 0;
-
-        loop {
-            match tool.invoke(name, args).await {
-                Ok(output) => return Ok(output),
-                Err(err) => {
-                    retry_count += 1;
-
-                    if retry_count >= self.config.max_retries {
-                        let error_message = format!(
-                            "[Tool Error] Failed to invoke tool '{}' after {} retries: {}",
-                            name, self.config.max_retries, err
-                        );
-                        println!("{}", error_message);
-                        return Err(anyhow!(error_message));
-                    }
-
-                    // Wait briefly before retrying
-                    std::thread::sleep(Duration::from_millis(500));
-                    println!(
-                        "[Tool] Retry {}/{} for '{}': {}",
-                        retry_count, self.config.max_retries, name, err
-                    );
-                }
-            }
+#[cfg(test)]
+mod tests {
+    use meerai_core::{
+        async_trait,
+        chat_completion::{ChatCompletionError, ChatCompletionResponse},
+    };
+
+    use super::*;
+    use crate::output_policy::READ_OUTPUT_FUNCTION;
+
+    struct Silent;
+
+    #[async_trait]
+    impl ChatCompletion for Silent {
+        async fn send(
+            &self,
+            _request: &ChatCompletionRequest,
+        ) -> Result<ChatCompletionResponse, ChatCompletionError> {
+            Err(ChatCompletionError::Literal("not expected".to_string()))
         }
     }
+
+    #[test]
+    fn test_output_reader_registration() {
+        let agent = MultiTurnAgent::new_without_tools(Silent, "You help".to_string());
+        assert!(agent.tool_executor.definitions().is_empty());
+
+        let config = MultiTurnAgentConfig {
+            output_policy: OutputPolicy::Truncate { max_chars: 100 },
+            ..Default::default()
+        };
+        let agent = MultiTurnAgent::new_with_config(
+            Silent,
+            ToolRegistry::new(),
+            "You help".to_string(),
+            config.clone(),
+        )
+        .unwrap();
+        let definitions = agent.tool_executor.definitions();
+        assert_eq!(definitions.len(), 1);
+        assert_eq!(definitions[0].name, READ_OUTPUT_FUNCTION);
+
+        // A registry bringing its own reader clashes with the one of the policy
+        let reader = OutputLimiter::new(config.output_policy.clone())
+            .toolset()
+            .unwrap();
+        let tools =
+            ToolRegistry::try_from(vec![Box::pin(reader) as Pin<Box<dyn Toolset>>]).unwrap();
+        let result = MultiTurnAgent::new_with_config(Silent, tools, "You help".to_string(), config);
+        assert!(result.is_err());
+    }
 }
@@ -0,0 +1,422 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Result, anyhow};
+use meerai_core::{
+    ToolDefinition, ToolError, ToolOutput, Toolset, async_trait,
+    chat_completion::{ChatCompletion, ChatCompletionRequest, ChatMessage},
+};
+use serde::Deserialize;
+
+/// Name of the function reading outputs that were shortened by an [`OutputPolicy`].
+pub const READ_OUTPUT_FUNCTION: &str = "tool_output-read";
+
+/// How tool outputs that are too large for the chat history are shortened.
+///
+/// Sizes are counted in characters. The full output of a shortened call is kept and can be
+/// read in windows with [`READ_OUTPUT_FUNCTION`].
+#[derive(Debug, Clone, Default)]
+pub enum OutputPolicy {
+    /// Outputs are added to the chat history as they are
+    #[default]
+    Unlimited,
+
+    /// Keeps the start of the output
+    Truncate { max_chars: usize },
+
+    /// Keeps the start and the end of the output, e.g. to see both a request and its result
+    HeadTail {
+        head_chars: usize,
+        tail_chars: usize,
+    },
+
+    /// Replaces the output by a summary written by a secondary model
+    Summarize {
+        max_chars: usize,
+
+        /// Only the start of longer outputs is summarized
+        max_input_chars: usize,
+
+        chat_completion: Arc<dyn ChatCompletion>,
+    },
+}
+
+impl OutputPolicy {
+    /// Size above which outputs are shortened, if any.
+    fn max_chars(&self) -> Option<usize> {
+        match self {
+            OutputPolicy::Unlimited => None,
+            OutputPolicy::Truncate { max_chars } | OutputPolicy::Summarize { max_chars, .. } => {
+                Some(*max_chars)
+            }
+            OutputPolicy::HeadTail {
+                head_chars,
+                tail_chars,
+            } => Some(head_chars + tail_chars),
+        }
+    }
+}
+
+/// Applies an [`OutputPolicy`] to tool outputs and keeps the full outputs it shortened.
+///
+/// Full outputs are referenced as `output-1`, `output-2`, ... until [`OutputLimiter::clear`].
+#[derive(Debug, Clone, Default)]
+pub struct OutputLimiter {
+    policy: OutputPolicy,
+    outputs: Arc<Mutex<Vec<String>>>,
+}
+
+impl OutputLimiter {
+    pub fn new(policy: OutputPolicy) -> Self {
+        Self {
+            policy,
+            outputs: Arc::default(),
+        }
+    }
+
+    /// Returns the toolset reading full outputs, `None` if outputs are never shortened.
+    pub fn toolset(&self) -> Option<ReadOutputToolset> {
+        Some(ReadOutputToolset {
+            outputs: self.outputs.clone(),
+            max_chars: self.policy.max_chars()?,
+        })
+    }
+
+    /// Returns the full output stored under `reference`.
+    pub fn full_output(&self, reference: &str) -> Option<String> {
+        let outputs = self.outputs.lock().expect("outputs lock");
+        output_index(reference).and_then(|index| outputs.get(index).cloned())
+    }
+
+    /// Drops the stored full outputs, their references become invalid.
+    pub fn clear(&self) {
+        self.outputs.lock().expect("outputs lock").clear();
+    }
+
+    /// Shortens the output of a call to `tool_name` if it exceeds the policy.
+    pub async fn limit(&self, tool_name: &str, output: String) -> String {
+        let Some(max_chars) = self.policy.max_chars() else {
+            return output;
+        };
+        let length = output.chars().count();
+        // Reads are already windowed and would otherwise be stored again
+        if length <= max_chars || tool_name == READ_OUTPUT_FUNCTION {
+            return output;
+        }
+
+        let reference = self.store(output.clone());
+        let hint = format!(
+            "read it with {} and reference `{}`",
+            READ_OUTPUT_FUNCTION, reference
+        );
+
+        match &self.policy {
+            OutputPolicy::Unlimited => output,
+            OutputPolicy::Truncate { max_chars } => truncated(&output, *max_chars, &hint),
+            OutputPolicy::HeadTail {
+                head_chars,
+                tail_chars,
+            } => format!(
+                "{}\n[... {} characters omitted, {}]\n{}",
+                head(&output, *head_chars),
+                length - head_chars - tail_chars,
+                hint,
+                tail(&output, *tail_chars)
+            ),
+            OutputPolicy::Summarize {
+                max_chars,
+                max_input_chars,
+                chat_completion,
+            } => {
+                let input = head(&output, *max_input_chars);
+                match summarize(chat_completion.as_ref(), tool_name, input, *max_chars).await {
+                    Ok(summary) => format!(
+                        "[Summary of a {} character output, {}]\n{}",
+                        length,
+                        hint,
+                        head(&summary, *max_chars)
+                    ),
+                    Err(err) => {
+                        tracing::warn!(tool = tool_name, error = %err, "failed to summarize output");
+                        truncated(&output, *max_chars, &hint)
+                    }
+                }
+            }
+        }
+    }
+
+    fn store(&self, output: String) -> String {
+        let mut outputs = self.outputs.lock().expect("outputs lock");
+        outputs.push(output);
+        format!("output-{}", outputs.len())
+    }
+}
+
+fn output_index(reference: &str) -> Option<usize> {
+    reference
+        .strip_prefix("output-")?
+        .parse::<usize>()
+        .ok()?
+        .checked_sub(1)
+}
+
+fn head(text: &str, chars: usize) -> &str {
+    match text.char_indices().nth(chars) {
+        Some((index, _)) => &text[..index],
+        None => text,
+    }
+}
+
+fn tail(text: &str, chars: usize) -> &str {
+    match chars.checked_sub(1) {
+        Some(skip) => match text.char_indices().rev().nth(skip) {
+            Some((index, _)) => &text[index..],
+            None => text,
+        },
+        None => "",
+    }
+}
+
+fn truncated(output: &str, max_chars: usize, hint: &str) -> String {
+    format!(
+        "{}\n[... {} characters truncated, {}]",
+        head(output, max_chars),
+        output.chars().count() - max_chars,
+        hint
+    )
+}
+
+async fn summarize(
+    chat_completion: &dyn ChatCompletion,
+    tool_name: &str,
+    output: &str,
+    max_chars: usize,
+) -> Result<String> {
+    let request = ChatCompletionRequest {
+        messages: vec![
+            ChatMessage::System(format!(
+                "Summarize the output of the tool `{}` in at most {} characters. Keep the facts, \
+                 names, numbers, URLs and IDs needed to continue the task.",
+                tool_name, max_chars
+            )),
+            ChatMessage::User(output.to_string()),
+        ],
+        ..Default::default()
+    };
+
+    let response = chat_completion.send(&request).await?;
+    response
+        .messages
+        .into_iter()
+        .rev()
+        .find_map(|message| match message {
+            ChatMessage::Assistant(text) => Some(text),
+            _ => None,
+        })
+        .ok_or_else(|| anyhow!("the summary is empty"))
+}
+
+#[derive(Debug, Deserialize)]
+struct ReadOutputArgs {
+    reference: String,
+
+    #[serde(default)]
+    offset: usize,
+
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+/// Reads windows of the full outputs kept by an [`OutputLimiter`].
+#[derive(Debug)]
+pub struct ReadOutputToolset {
+    outputs: Arc<Mutex<Vec<String>>>,
+
+    /// Largest window returned by a single read
+    max_chars: usize,
+}
+
+#[async_trait]
+impl Toolset for ReadOutputToolset {
+    fn name(&self) -> String {
+        "tool_output".to_string()
+    }
+
+    fn definition(&self) -> Vec<ToolDefinition> {
+        vec![ToolDefinition {
+            r#type: "function".to_string(),
+            name: READ_OUTPUT_FUNCTION.to_string(),
+            description: "Read part of a tool output that was shortened, by its reference."
+                .to_string(),
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "reference": {
+                        "type": "string",
+                        "description": "Reference of the output, e.g. `output-1`"
+                    },
+                    "offset": {
+                        "type": "integer",
+                        "minimum": 0,
+                        "description": "Character to start reading at"
+                    },
+                    "limit": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "description": format!("Number of characters to read, at most {}", self.max_chars)
+                    }
+                },
+                "required": ["reference"]
+            }),
+            metadata: Default::default(),
+        }]
+    }
+
+    fn contain(&self, fn_name: &str) -> bool {
+        fn_name == READ_OUTPUT_FUNCTION
+    }
+
+    async fn invoke(&self, fn_name: &str, args: &str) -> Result<ToolOutput, ToolError> {
+        if !self.contain(fn_name) {
+            return Err(ToolError::InvalidFunctionName(fn_name.to_string()));
+        }
+        let args: ReadOutputArgs = serde_json::from_str(args)?;
+
+        let output = output_index(&args.reference)
+            .and_then(|index| {
+                self.outputs
+                    .lock()
+                    .expect("outputs lock")
+                    .get(index)
+                    .cloned()
+            })
+            .ok_or_else(|| {
+                ToolError::invalid_argument(
+                    "$.reference",
+                    format!("no output is stored as `{}`", args.reference),
+                )
+            })?;
+
+        let limit = args.limit.unwrap_or(self.max_chars).min(self.max_chars);
+        let rest = output.chars().skip(args.offset).collect::<String>();
+        let window = head(&rest, limit);
+        let remaining = rest.chars().count() - window.chars().count();
+
+        Ok(ToolOutput::Text(if remaining > 0 {
+            format!(
+                "{}\n[... {} more characters, continue at offset {}]",
+                window,
+                remaining,
+                args.offset + limit
+            )
+        } else {
+            window.to_string()
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use meerai_core::chat_completion::{ChatCompletionError, ChatCompletionResponse};
+
+    use super::*;
+
+    struct Summarizer;
+
+    #[async_trait]
+    impl ChatCompletion for Summarizer {
+        async fn send(
+            &self,
+            request: &ChatCompletionRequest,
+        ) -> Result<ChatCompletionResponse, ChatCompletionError> {
+            let ChatMessage::User(input) = &request.messages[1] else {
+                panic!("expected the output as user message");
+            };
+            Ok(ChatCompletionResponse {
+                messages: vec![ChatMessage::Assistant(format!(
+                    "{} characters of digits",
+                    input.len()
+                ))],
+                tool_calls: vec![],
+                usage: None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_policies() {
+        let output = "0123456789".repeat(3);
+
+        let limiter = OutputLimiter::new(OutputPolicy::Truncate { max_chars: 5 });
+        assert_eq!(
+            limiter.limit("web-fetch_url", "short".to_string()).await,
+            "short"
+        );
+        assert_eq!(
+            limiter.limit("web-fetch_url", output.clone()).await,
+            "01234\n[... 25 characters truncated, read it with tool_output-read and reference `output-1`]"
+        );
+        assert_eq!(limiter.full_output("output-1").unwrap(), output);
+
+        let limiter = OutputLimiter::new(OutputPolicy::HeadTail {
+            head_chars: 3,
+            tail_chars: 2,
+        });
+        assert_eq!(
+            limiter.limit("web-fetch_url", output.clone()).await,
+            "012\n[... 25 characters omitted, read it with tool_output-read and reference `output-1`]\n89"
+        );
+
+        let limiter = OutputLimiter::new(OutputPolicy::Summarize {
+            max_chars: 50,
+            max_input_chars: 20,
+            chat_completion: Arc::new(Summarizer),
+        });
+        assert_eq!(
+            limiter.limit("web-fetch_url", output.repeat(2)).await,
+            "[Summary of a 60 character output, read it with tool_output-read and reference `output-1`]\n20 characters of digits"
+        );
+
+        let limiter = OutputLimiter::new(OutputPolicy::Unlimited);
+        assert_eq!(limiter.limit("web-fetch_url", output.clone()).await, output);
+        assert!(limiter.toolset().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_read_output() {
+        let limiter = OutputLimiter::new(OutputPolicy::Truncate { max_chars: 4 });
+        limiter
+            .limit("x-read_tweet", "héllo wörld".to_string())
+            .await;
+        let toolset = limiter.toolset().unwrap();
+
+        let output = toolset
+            .invoke(
+                READ_OUTPUT_FUNCTION,
+                r#"{"reference": "output-1", "offset": 4}"#,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            output,
+            ToolOutput::Text("o wö\n[... 3 more characters, continue at offset 8]".to_string())
+        );
+
+        let output = toolset
+            .invoke(
+                READ_OUTPUT_FUNCTION,
+                r#"{"reference": "output-1", "offset": 8, "limit": 100}"#,
+            )
+            .await
+            .unwrap();
+        assert_eq!(output, ToolOutput::Text("rld".to_string()));
+
+        let err = toolset
+            .invoke(READ_OUTPUT_FUNCTION, r#"{"reference": "output-2"}"#)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ToolError::InvalidArguments(_)));
+
+        limiter.clear();
+        assert_eq!(limiter.full_output("output-1"), None);
+    }
+}
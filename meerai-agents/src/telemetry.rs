@@ -0,0 +1,53 @@
+use meerai_core::chat_completion::{
+    ChatCompletion, ChatCompletionError, ChatCompletionRequest, ChatCompletionResponse,
+};
+use tokio::time::Instant;
+use tracing::{Instrument, field::Empty, info_span};
+
+/// Sends a chat completion request inside an `llm_call` span.
+///
+/// The span records the duration, whether the call succeeded, the number of tool calls and the
+/// token usage reported by the provider.
+pub async fn send_traced(
+    chat_completion: &dyn ChatCompletion,
+    request: &ChatCompletionRequest,
+) -> Result<ChatCompletionResponse, ChatCompletionError> {
+    let span = info_span!(
+        "llm_call",
+        model = request.model.as_deref().unwrap_or("default"),
+        messages = request.messages.len(),
+        duration_ms = Empty,
+        success = Empty,
+        tool_calls = Empty,
+        prompt_tokens = Empty,
+        completion_tokens = Empty,
+        total_tokens = Empty,
+        cache_read_tokens = Empty,
+        cache_write_tokens = Empty,
+        cost = Empty,
+    );
+
+    let started = Instant::now();
+    let result = chat_completion.send(request).instrument(span.clone()).await;
+
+    span.record("duration_ms", started.elapsed().as_millis() as u64);
+    span.record("success", result.is_ok());
+    match &result {
+        Ok(response) => {
+            span.record("tool_calls", response.tool_calls.len());
+            if let Some(usage) = &response.usage {
+                span.record("prompt_tokens", usage.prompt_tokens);
+                span.record("completion_tokens", usage.completion_tokens);
+                span.record("total_tokens", usage.total_tokens);
+                span.record("cache_read_tokens", usage.cache_read_tokens);
+                span.record("cache_write_tokens", usage.cache_write_tokens);
+                if let Some(cost) = usage.cost {
+                    span.record("cost", cost);
+                }
+            }
+        }
+        Err(err) => span.in_scope(|| tracing::warn!(error = %err, "chat completion failed")),
+    }
+
+    result
+}
@@ -0,0 +1,725 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    pin::Pin,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use anyhow::{Result, anyhow};
+use futures::{StreamExt, stream};
+use meerai_core::{
+    RateLimit, ToolCall, ToolDefinition, ToolError, ToolErrorKind, ToolMetadata, ToolOutput,
+    ToolRegistry, ToolRegistryError, Toolset,
+};
+use tokio::{
+    sync::{OwnedSemaphorePermit, Semaphore},
+    time::Instant,
+};
+use tracing::{Instrument, Span, field::Empty, info_span};
+
+use crate::approval::{Approval, Approver};
+
+/// Configuration for the ToolExecutor.
+#[derive(Debug, Clone)]
+pub struct ToolExecutorConfig {
+    /// Maximum number of times to retry a failed tool invocation
+    pub max_retries: usize,
+
+    /// Maximum number of tool calls of a single response executed at the same time
+    pub max_concurrent_calls: usize,
+}
+
+impl Default for ToolExecutorConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            max_concurrent_calls: 4,
+        }
+    }
+}
+
+/// Executes the tool calls requested by a chat completion against a set of tools.
+#[derive(Debug)]
+pub struct ToolExecutor {
+    /// Available tools
+    tools: ToolRegistry,
+
+    /// Decides whether side-effecting tools may run, they are rejected when unset
+    approver: Option<Arc<dyn Approver>>,
+
+    /// Concurrency and rate limit state per tool name, created on first use
+    limiters: Mutex<HashMap<String, Arc<ToolLimiter>>>,
+
+    /// Configuration for the executor
+    config: ToolExecutorConfig,
+}
+
+impl ToolExecutor {
+    /// Creates a new ToolExecutor with the default configuration.
+    pub fn new(tools: ToolRegistry) -> Self {
+        Self::new_with_config(tools, ToolExecutorConfig::default())
+    }
+
+    /// Creates a new ToolExecutor with custom configuration
+    pub fn new_with_config(tools: ToolRegistry, config: ToolExecutorConfig) -> Self {
+        Self {
+            tools,
+            approver: None,
+            limiters: Mutex::default(),
+            config,
+        }
+    }
+
+    /// Requires approval from `approver` before running side-effecting tools.
+    ///
+    /// Without an approver side-effecting tools are rejected, use
+    /// [`AutoApprover`](crate::approval::AutoApprover) to run them unattended.
+    pub fn set_approver(&mut self, approver: impl Approver + 'static) {
+        self.approver = Some(Arc::new(approver));
+    }
+
+    /// Adds a single tool to the executor.
+    pub fn add_tool(&mut self, tool: impl Toolset + 'static) -> Result<(), ToolRegistryError> {
+        self.tools.register(Box::pin(tool))
+    }
+
+    /// Adds multiple tools to the executor, stopping at the first name conflict.
+    pub fn add_tools(
+        &mut self,
+        tools: Vec<Pin<Box<dyn Toolset>>>,
+    ) -> Result<(), ToolRegistryError> {
+        tools
+            .into_iter()
+            .try_for_each(|tool| self.tools.register(tool))
+    }
+
+    /// Collects the definitions of every available tool.
+    pub fn definitions(&self) -> Vec<ToolDefinition> {
+        self.tools.definitions().to_vec()
+    }
+
+    /// Executes every tool call, at most `max_concurrent_calls` at a time.
+    ///
+    /// Results are returned in the order of `tool_calls`, regardless of completion order.
+    pub async fn execute_all(&self, tool_calls: &[ToolCall]) -> Vec<Result<ToolOutput>> {
+        // Futures are created upfront so the stream does not hold a closure, which keeps
+        // the returned future `Send` for callers such as actors
+        let executions = tool_calls
+            .iter()
+            .map(|tool_call| self.execute(tool_call))
+            .collect::<Vec<_>>();
+
+        stream::iter(executions)
+            .buffered(self.config.max_concurrent_calls.max(1))
+            .collect()
+            .await
+    }
+
+    /// Executes a single tool call with retry logic.
+    ///
+    /// Errors the model can correct, such as an unknown tool name or invalid arguments, are
+    /// returned as [`ToolOutput::Fail`] so they can be sent back to the model. Retryable errors
+    /// are retried up to `max_retries` times and only fatal errors are returned as `Err`.
+    pub async fn execute(&self, tool_call: &ToolCall) -> Result<ToolOutput> {
+        let span = info_span!(
+            "tool_call",
+            tool = %tool_call.name,
+            call_id = %tool_call.id,
+            duration_ms = Empty,
+            success = Empty,
+            retries = Empty,
+        );
+
+        let started = Instant::now();
+        let result = self.execute_call(tool_call).instrument(span.clone()).await;
+
+        span.record("duration_ms", started.elapsed().as_millis() as u64);
+        span.record(
+            "success",
+            matches!(&result, Ok(output) if !matches!(output, ToolOutput::Fail(_))),
+        );
+        result
+    }
+
+    async fn execute_call(&self, tool_call: &ToolCall) -> Result<ToolOutput> {
+        let ToolCall { name, args, .. } = tool_call;
+        tracing::debug!(args = %args, "invoking tool");
+
+        let (Some(tool), Some(definition)) = (self.tools.get(name), self.tools.definition(name))
+        else {
+            return Ok(Self::report(
+                name,
+                ToolError::InvalidFunctionName(name.clone()),
+            ));
+        };
+
+        // Invalid arguments are reported back to the model instead of being retried as is
+        if let Err(err) = definition.validate_args(args) {
+            return Ok(Self::report(name, err));
+        }
+
+        let mut args = args.clone();
+        if definition.metadata.side_effecting {
+            let Some(approver) = &self.approver else {
+                tracing::warn!("side-effecting call rejected, no approver is configured");
+                return Ok(ToolOutput::Fail(
+                    "the tool has side effects and requires approval, but no approver is \
+                     configured"
+                        .to_string(),
+                ));
+            };
+            match approver.approve(tool_call, definition).await {
+                Approval::Approve => {}
+                Approval::Edit(edited_args) => {
+                    tracing::info!(args = %edited_args, "arguments edited by the approver");
+                    if let Err(err) = definition.validate_args(&edited_args) {
+                        return Ok(Self::report(name, err));
+                    }
+                    args = edited_args;
+                }
+                Approval::Reject(reason) => {
+                    tracing::info!(reason = %reason, "call rejected by the approver");
+                    return Ok(ToolOutput::Fail(format!(
+                        "the call was rejected by the approver: {}",
+                        reason
+                    )));
+                }
+            }
+        }
+
+        let tool_output = self.invoke_tool_with_retry(tool, definition, &args).await?;
+        tracing::debug!(output = %tool_output, "tool returned");
+
+        Ok(tool_output)
+    }
+
+    /// Invokes a tool with retry logic, enforcing the limits of its metadata
+    async fn invoke_tool_with_retry(
+        &self,
+        tool: &Pin<Box<dyn Toolset>>,
+        definition: &ToolDefinition,
+        args: &str,
+    ) -> Result<ToolOutput> {
+        let name = definition.name.as_str();
+        let metadata = &definition.metadata;
+        let limiter = self.limiter(definition);
+        let mut retry_count = 0;
+
+        loop {
+            let result = {
+                let _permit = limiter.acquire().await;
+                match metadata.timeout {
+                    Some(timeout) => tokio::time::timeout(timeout, tool.invoke(name, args))
+                        .await
+                        .unwrap_or(Err(ToolError::Timeout(timeout))),
+                    None => tool.invoke(name, args).await,
+                }
+            };
+
+            let err = match result {
+                Ok(output) => return Ok(output),
+                Err(err) => err,
+            };
+
+            match err.kind() {
+                ToolErrorKind::ModelCorrectable => return Ok(Self::report(name, err)),
+                ToolErrorKind::Fatal => {
+                    tracing::error!(error = %err, "tool failed");
+                    return Err(anyhow!("Failed to invoke tool '{}': {}", name, err));
+                }
+                // A failed call of a non-idempotent tool may still have taken effect
+                ToolErrorKind::Retryable if !metadata.is_idempotent() => {
+                    tracing::warn!(error = %err, "not retrying non-idempotent tool");
+                    return Ok(ToolOutput::Fail(format!(
+                        "Failed to invoke tool '{}': {}. The call was not retried because the tool \
+                         is not idempotent, it may or may not have taken effect",
+                        name, err
+                    )));
+                }
+                ToolErrorKind::Retryable => {
+                    retry_count += 1;
+                    Span::current().record("retries", retry_count);
+
+                    if retry_count >= self.config.max_retries {
+                        return Ok(ToolOutput::Fail(format!(
+                            "Failed to invoke tool '{}' after {} retries: {}",
+                            name, self.config.max_retries, err
+                        )));
+                    }
+
+                    // Wait briefly before retrying
+                    tokio::time::sleep(Duration::from_millis(500)).await;
+                    tracing::warn!(
+                        error = %err,
+                        "retry {}/{}",
+                        retry_count,
+                        self.config.max_retries
+                    );
+                }
+            }
+        }
+    }
+
+    /// Returns the limiter of a tool, creating it from the tool's metadata on first use.
+    fn limiter(&self, definition: &ToolDefinition) -> Arc<ToolLimiter> {
+        self.limiters
+            .lock()
+            .unwrap()
+            .entry(definition.name.clone())
+            .or_insert_with(|| Arc::new(ToolLimiter::new(&definition.metadata)))
+            .clone()
+    }
+
+    /// Turns an error into a tool output that tells the model what to fix.
+    fn report(name: &str, err: ToolError) -> ToolOutput {
+        tracing::info!(tool = name, error = %err, "reporting error to the model");
+        ToolOutput::Fail(err.to_string())
+    }
+}
+
+/// Enforces the concurrency and rate limits of a single tool.
+#[derive(Debug)]
+struct ToolLimiter {
+    semaphore: Option<Arc<Semaphore>>,
+    rate_limit: Option<RateLimit>,
+
+    /// Start times of the calls within the current rate limit window
+    calls: Mutex<VecDeque<Instant>>,
+}
+
+impl ToolLimiter {
+    fn new(metadata: &ToolMetadata) -> Self {
+        Self {
+            semaphore: metadata
+                .max_concurrency
+                .map(|max| Arc::new(Semaphore::new(max.max(1)))),
+            rate_limit: metadata.rate_limit,
+            calls: Mutex::default(),
+        }
+    }
+
+    /// Waits until a call is allowed, the returned permit must be held for the call.
+    async fn acquire(&self) -> Option<OwnedSemaphorePermit> {
+        let permit = match &self.semaphore {
+            Some(semaphore) => Some(
+                semaphore
+                    .clone()
+                    .acquire_owned()
+                    .await
+                    .expect("tool semaphore is never closed"),
+            ),
+            None => None,
+        };
+
+        let Some(RateLimit { max_calls, per }) = self.rate_limit else {
+            return permit;
+        };
+
+        loop {
+            let wait = {
+                let mut calls = self.calls.lock().unwrap();
+                let now = Instant::now();
+                while calls
+                    .front()
+                    .is_some_and(|start| now.duration_since(*start) >= per)
+                {
+                    calls.pop_front();
+                }
+
+                if calls.len() < max_calls.max(1) as usize {
+                    calls.push_back(now);
+                    None
+                } else {
+                    calls.front().map(|oldest| *oldest + per - now)
+                }
+            };
+
+            match wait {
+                Some(wait) => tokio::time::sleep(wait).await,
+                None => return permit,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    use meerai_core::async_trait;
+
+    use super::*;
+    use crate::approval::{AutoApprover, ChannelApprover};
+
+    #[derive(Default)]
+    struct EchoToolset {
+        in_flight: Arc<AtomicUsize>,
+        max_in_flight: Arc<AtomicUsize>,
+        metadata: ToolMetadata,
+    }
+
+    #[async_trait]
+    impl Toolset for EchoToolset {
+        fn name(&self) -> String {
+            "echo".to_string()
+        }
+
+        fn definition(&self) -> Vec<ToolDefinition> {
+            vec![ToolDefinition {
+                r#type: "function".to_string(),
+                name: "echo-echo".to_string(),
+                description: "Echoes a number".to_string(),
+                parameters: serde_json::json!({ "type": "integer" }),
+                metadata: self.metadata.clone(),
+            }]
+        }
+
+        fn contain(&self, fn_name: &str) -> bool {
+            fn_name == "echo-echo"
+        }
+
+        async fn invoke(&self, _fn_name: &str, args: &str) -> Result<ToolOutput, ToolError> {
+            let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+
+            // Later calls finish first
+            let delay = 30 - 10 * args.parse::<u64>().unwrap();
+            tokio::time::sleep(Duration::from_millis(delay)).await;
+
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            Ok(ToolOutput::Text(args.to_string()))
+        }
+    }
+
+    fn registry(tool: impl Toolset + 'static) -> ToolRegistry {
+        ToolRegistry::try_from(vec![Box::pin(tool) as Pin<Box<dyn Toolset>>]).unwrap()
+    }
+
+    fn tool_call(id: usize) -> ToolCall {
+        ToolCall {
+            id: format!("call_{}", id),
+            name: "echo-echo".to_string(),
+            args: id.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_all_concurrently_in_order() {
+        let max_in_flight = Arc::new(AtomicUsize::new(0));
+        let executor = ToolExecutor::new_with_config(
+            registry(EchoToolset {
+                max_in_flight: max_in_flight.clone(),
+                ..Default::default()
+            }),
+            ToolExecutorConfig {
+                max_concurrent_calls: 2,
+                ..Default::default()
+            },
+        );
+
+        let outputs = executor
+            .execute_all(&[tool_call(0), tool_call(1), tool_call(2)])
+            .await
+            .into_iter()
+            .map(Result::unwrap)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            outputs,
+            vec![
+                ToolOutput::Text("0".to_string()),
+                ToolOutput::Text("1".to_string()),
+                ToolOutput::Text("2".to_string()),
+            ]
+        );
+        assert_eq!(max_in_flight.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_execute_invalid_args() {
+        let invocations = Arc::new(AtomicUsize::new(0));
+        let executor = ToolExecutor::new(registry(EchoToolset {
+            in_flight: invocations.clone(),
+            ..Default::default()
+        }));
+
+        let output = executor
+            .execute(&ToolCall {
+                id: "call_0".to_string(),
+                name: "echo-echo".to_string(),
+                args: "\"zero\"".to_string(),
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(
+            output,
+            ToolOutput::Fail(
+                "arguments for tool do not match its schema: $: expected integer, found string"
+                    .to_string()
+            )
+        );
+        assert_eq!(invocations.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_execute_unknown_tool() {
+        let executor = ToolExecutor::new(ToolRegistry::new());
+
+        let output = executor.execute(&tool_call(0)).await.unwrap();
+        assert_eq!(
+            output,
+            ToolOutput::Fail("invalid function name: echo-echo".to_string())
+        );
+    }
+
+    struct FlakyToolset {
+        invocations: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl Toolset for FlakyToolset {
+        fn name(&self) -> String {
+            "flaky".to_string()
+        }
+
+        fn definition(&self) -> Vec<ToolDefinition> {
+            [
+                "flaky-transient",
+                "flaky-fatal",
+                "flaky-renamed",
+                "flaky-slow",
+            ]
+            .into_iter()
+            .map(|name| ToolDefinition {
+                r#type: "function".to_string(),
+                name: name.to_string(),
+                description: String::new(),
+                parameters: serde_json::json!({ "type": "object", "properties": {} }),
+                metadata: match name {
+                    "flaky-slow" => ToolMetadata {
+                        timeout: Some(Duration::from_millis(10)),
+                        idempotent: Some(false),
+                        ..Default::default()
+                    },
+                    _ => Default::default(),
+                },
+            })
+            .collect()
+        }
+
+        fn contain(&self, fn_name: &str) -> bool {
+            fn_name.starts_with("flaky-")
+        }
+
+        async fn invoke(&self, fn_name: &str, _args: &str) -> Result<ToolOutput, ToolError> {
+            let invocations = self.invocations.fetch_add(1, Ordering::SeqCst) + 1;
+            match fn_name {
+                "flaky-transient" if invocations < 2 => {
+                    Err(ToolError::Transient(anyhow!("connection reset")))
+                }
+                "flaky-transient" => Ok(ToolOutput::Text("recovered".to_string())),
+                "flaky-fatal" => Err(ToolError::Unknown(anyhow!("invalid credentials"))),
+                "flaky-slow" => {
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                    Ok(ToolOutput::Text("too late".to_string()))
+                }
+                _ => Err(ToolError::InvalidFunctionName(fn_name.to_string())),
+            }
+        }
+    }
+
+    async fn execute_flaky(name: &str) -> (Result<ToolOutput>, usize) {
+        let invocations = Arc::new(AtomicUsize::new(0));
+        let executor = ToolExecutor::new(registry(FlakyToolset {
+            invocations: invocations.clone(),
+        }));
+
+        let output = executor
+            .execute(&ToolCall {
+                id: "call_0".to_string(),
+                name: name.to_string(),
+                args: "{}".to_string(),
+            })
+            .await;
+        (output, invocations.load(Ordering::SeqCst))
+    }
+
+    #[tokio::test]
+    async fn test_execute_error_kinds() {
+        let (output, invocations) = execute_flaky("flaky-transient").await;
+        assert_eq!(output.unwrap(), ToolOutput::Text("recovered".to_string()));
+        assert_eq!(invocations, 2);
+
+        let (output, invocations) = execute_flaky("flaky-renamed").await;
+        assert_eq!(
+            output.unwrap(),
+            ToolOutput::Fail("invalid function name: flaky-renamed".to_string())
+        );
+        assert_eq!(invocations, 1);
+
+        let (output, invocations) = execute_flaky("flaky-typo").await;
+        assert_eq!(
+            output.unwrap(),
+            ToolOutput::Fail("invalid function name: flaky-typo".to_string())
+        );
+        assert_eq!(invocations, 0);
+
+        let (output, invocations) = execute_flaky("flaky-fatal").await;
+        assert!(output.is_err());
+        assert_eq!(invocations, 1);
+    }
+
+    #[tokio::test]
+    async fn test_execute_timeout_without_retry() {
+        let (output, invocations) = execute_flaky("flaky-slow").await;
+        assert!(matches!(
+            output.unwrap(),
+            ToolOutput::Fail(message) if message.contains("timed out after 10ms")
+        ));
+        // Timed out calls of non-idempotent tools are not retried
+        assert_eq!(invocations, 1);
+    }
+
+    #[tokio::test]
+    async fn test_execute_all_with_tool_limits() {
+        let max_in_flight = Arc::new(AtomicUsize::new(0));
+        let executor = ToolExecutor::new(registry(EchoToolset {
+            max_in_flight: max_in_flight.clone(),
+            metadata: ToolMetadata {
+                max_concurrency: Some(1),
+                rate_limit: Some(RateLimit {
+                    max_calls: 2,
+                    per: Duration::from_millis(100),
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        }));
+
+        let start = Instant::now();
+        let outputs = executor
+            .execute_all(&[tool_call(0), tool_call(1), tool_call(2)])
+            .await;
+
+        assert!(outputs.iter().all(Result::is_ok));
+        assert_eq!(max_in_flight.load(Ordering::SeqCst), 1);
+        // The third call waits for the first one to leave the rate limit window
+        assert!(start.elapsed() >= Duration::from_millis(100));
+    }
+
+    struct PostToolset {
+        posts: Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl Toolset for PostToolset {
+        fn name(&self) -> String {
+            "bsky".to_string()
+        }
+
+        fn definition(&self) -> Vec<ToolDefinition> {
+            vec![ToolDefinition {
+                r#type: "function".to_string(),
+                name: "bsky-post".to_string(),
+                description: "Posts a text".to_string(),
+                parameters: serde_json::json!({ "type": "string" }),
+                metadata: ToolMetadata {
+                    side_effecting: true,
+                    ..Default::default()
+                },
+            }]
+        }
+
+        fn contain(&self, fn_name: &str) -> bool {
+            fn_name == "bsky-post"
+        }
+
+        async fn invoke(&self, _fn_name: &str, args: &str) -> Result<ToolOutput, ToolError> {
+            self.posts.lock().unwrap().push(args.to_string());
+            Ok(ToolOutput::Text("posted".to_string()))
+        }
+    }
+
+    fn post_call(text: &str) -> ToolCall {
+        ToolCall {
+            id: "call_0".to_string(),
+            name: "bsky-post".to_string(),
+            args: serde_json::to_string(text).unwrap(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_approver() {
+        let posts = Arc::new(std::sync::Mutex::new(vec![]));
+        let mut executor = ToolExecutor::new(registry(PostToolset {
+            posts: posts.clone(),
+        }));
+        executor.set_approver(|tool_call: &ToolCall, _: &ToolDefinition| {
+            match tool_call.args.as_str() {
+                "\"hello\"" => Approval::Approve,
+                "\"helo\"" => Approval::Edit("\"hello again\"".to_string()),
+                _ => Approval::Reject("off brand".to_string()),
+            }
+        });
+
+        let approved = executor.execute(&post_call("hello")).await.unwrap();
+        assert_eq!(approved, ToolOutput::Text("posted".to_string()));
+
+        let edited = executor.execute(&post_call("helo")).await.unwrap();
+        assert_eq!(edited, ToolOutput::Text("posted".to_string()));
+
+        let rejected = executor.execute(&post_call("buy now")).await.unwrap();
+        assert_eq!(
+            rejected,
+            ToolOutput::Fail("the call was rejected by the approver: off brand".to_string())
+        );
+
+        assert_eq!(
+            *posts.lock().unwrap(),
+            vec!["\"hello\"".to_string(), "\"hello again\"".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_channel_approver() {
+        let posts = Arc::new(std::sync::Mutex::new(vec![]));
+        let mut executor = ToolExecutor::new(registry(PostToolset {
+            posts: posts.clone(),
+        }));
+        let (approver, mut requests) = ChannelApprover::new(1);
+        executor.set_approver(approver);
+
+        let reviewer = tokio::spawn(async move {
+            let request = requests.recv().await.unwrap();
+            assert_eq!(request.tool_call.name, "bsky-post");
+            request.respond(Approval::Approve);
+        });
+
+        let output = executor.execute(&post_call("hello")).await.unwrap();
+        reviewer.await.unwrap();
+
+        assert_eq!(output, ToolOutput::Text("posted".to_string()));
+        assert_eq!(posts.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_execute_without_approver() {
+        let posts = Arc::new(std::sync::Mutex::new(vec![]));
+        let mut executor = ToolExecutor::new(registry(PostToolset {
+            posts: posts.clone(),
+        }));
+
+        let rejected = executor.execute(&post_call("hello")).await.unwrap();
+        assert!(matches!(rejected, ToolOutput::Fail(_)));
+        assert!(posts.lock().unwrap().is_empty());
+
+        executor.set_approver(AutoApprover);
+        let approved = executor.execute(&post_call("hello")).await.unwrap();
+        assert_eq!(approved, ToolOutput::Text("posted".to_string()));
+        assert_eq!(posts.lock().unwrap().len(), 1);
+    }
+}
@@ -1,2 +1,4 @@
 pub mod config;
-mod tools;
+pub mod mcp;
+pub mod memory;
+pub mod tools;
@@ -0,0 +1,455 @@
+use std::{
+    collections::{HashMap, hash_map::Entry},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use base64::{Engine, prelude::BASE64_STANDARD};
+use meerai_core::{
+    Attachment, ToolDefinition, ToolError, ToolMetadata, ToolOutput, Toolset, async_trait,
+};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use super::{
+    McpError,
+    protocol::{
+        CallToolResult, Content, INVALID_PARAMS, JsonRpcRequest, ListToolsResult, PROTOCOL_VERSION,
+        Tool,
+    },
+    transport::{HttpTransport, StdioTransport, Transport},
+};
+
+/// How to reach an MCP server.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "transport", rename_all = "snake_case")]
+pub enum McpServerConfig {
+    /// Launches the server and talks to it over its stdin and stdout
+    Stdio {
+        command: String,
+
+        #[serde(default)]
+        args: Vec<String>,
+
+        #[serde(default)]
+        env: HashMap<String, String>,
+    },
+
+    /// Connects to a running server over streamable HTTP
+    Http {
+        url: String,
+
+        #[serde(default)]
+        headers: HashMap<String, String>,
+    },
+}
+
+/// Exposes the tools of an MCP server as a Toolset.
+///
+/// The tools are listed once on connection and named `{name}-{tool}`, connecting fails when two
+/// tools end up with the same name. Calls are proxied to the server as they are, so argument
+/// validation and execution happen on its side.
+pub struct McpToolset {
+    name: String,
+    transport: Box<dyn Transport>,
+    definitions: Vec<ToolDefinition>,
+
+    /// Function name to the name of the tool on the server
+    tools: HashMap<String, String>,
+
+    next_id: AtomicU64,
+}
+
+impl McpToolset {
+    pub async fn connect(
+        name: impl Into<String>,
+        config: &McpServerConfig,
+    ) -> Result<Self, McpError> {
+        let transport: Box<dyn Transport> = match config {
+            McpServerConfig::Stdio { command, args, env } => {
+                Box::new(StdioTransport::spawn(command, args, env)?)
+            }
+            McpServerConfig::Http { url, headers } => Box::new(HttpTransport::new(url, headers)?),
+        };
+
+        Self::initialize(name.into(), transport).await
+    }
+
+    pub(super) async fn initialize(
+        name: String,
+        transport: Box<dyn Transport>,
+    ) -> Result<Self, McpError> {
+        let mut toolset = Self {
+            name,
+            transport,
+            definitions: vec![],
+            tools: HashMap::new(),
+            next_id: AtomicU64::new(0),
+        };
+
+        toolset
+            .call(
+                "initialize",
+                json!({
+                    "protocolVersion": PROTOCOL_VERSION,
+                    "capabilities": {},
+                    "clientInfo": {
+                        "name": "meerai",
+                        "version": env!("CARGO_PKG_VERSION"),
+                    },
+                }),
+            )
+            .await?;
+        toolset
+            .transport
+            .notify(JsonRpcRequest::notification("notifications/initialized"))
+            .await?;
+
+        let mut cursor = None;
+        loop {
+            let params = match cursor {
+                Some(cursor) => json!({ "cursor": cursor }),
+                None => json!({}),
+            };
+            let result: ListToolsResult =
+                serde_json::from_value(toolset.call("tools/list", params).await?)?;
+
+            for tool in result.tools {
+                toolset.add_tool(tool)?;
+            }
+
+            cursor = result.next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+
+        Ok(toolset)
+    }
+
+    async fn call(&self, method: &str, params: Value) -> Result<Value, McpError> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let response = self
+            .transport
+            .request(JsonRpcRequest::new(id, method, params))
+            .await?;
+
+        if let Some(error) = response.error {
+            return Err(McpError::Rpc {
+                code: error.code,
+                message: error.message,
+            });
+        }
+        response
+            .result
+            .ok_or_else(|| McpError::Protocol(format!("response to `{}` has no result", method)))
+    }
+
+    /// Registers the tool, failing when its function name is taken by another tool, as
+    /// sanitizing maps e.g. `a.b` and `a_b` to the same name.
+    fn add_tool(&mut self, tool: Tool) -> Result<(), McpError> {
+        let definition = self.tool_definition(&tool);
+        match self.tools.entry(definition.name.clone()) {
+            Entry::Occupied(entry) => Err(McpError::DuplicateTool {
+                first: entry.get().clone(),
+                second: tool.name,
+                function: definition.name,
+            }),
+            Entry::Vacant(entry) => {
+                entry.insert(tool.name);
+                self.definitions.push(definition);
+                Ok(())
+            }
+        }
+    }
+
+    fn tool_definition(&self, tool: &Tool) -> ToolDefinition {
+        // Function names of the model APIs only allow a small set of characters
+        let tool_name = tool
+            .name
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect::<String>();
+
+        // The specification assumes the worst when a server gives no hints
+        let annotations = tool.annotations.clone().unwrap_or_default();
+        let read_only = annotations.read_only_hint.unwrap_or(false);
+
+        ToolDefinition {
+            r#type: "function".to_string(),
+            name: format!("{}-{}", self.name, tool_name),
+            description: tool.description.clone().unwrap_or_default(),
+            parameters: tool.input_schema.clone(),
+            metadata: ToolMetadata {
+                side_effecting: !read_only,
+                idempotent: Some(read_only || annotations.idempotent_hint.unwrap_or(false)),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl Toolset for McpToolset {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn definition(&self) -> Vec<ToolDefinition> {
+        self.definitions.clone()
+    }
+
+    fn contain(&self, fn_name: &str) -> bool {
+        self.tools.contains_key(fn_name)
+    }
+
+    async fn invoke(&self, fn_name: &str, args: &str) -> Result<ToolOutput, ToolError> {
+        let tool = self
+            .tools
+            .get(fn_name)
+            .ok_or_else(|| ToolError::InvalidFunctionName(fn_name.to_string()))?;
+        let arguments: Value = if args.trim().is_empty() {
+            json!({})
+        } else {
+            serde_json::from_str(args)?
+        };
+
+        let result = self
+            .call(
+                "tools/call",
+                json!({ "name": tool, "arguments": arguments }),
+            )
+            .await
+            .map_err(|err| match err {
+                McpError::Rpc {
+                    code: INVALID_PARAMS,
+                    message,
+                } => ToolError::invalid_argument("$", message),
+                // Kept unwrapped so timeouts and connection errors are retried
+                McpError::Http(err) => ToolError::Unknown(err.into()),
+                McpError::Io(err) => ToolError::Unknown(err.into()),
+                err => ToolError::Unknown(err.into()),
+            })?;
+        let result: CallToolResult =
+            serde_json::from_value(result).map_err(|err| ToolError::Unknown(err.into()))?;
+
+        Ok(tool_output(result))
+    }
+}
+
+fn tool_output(result: CallToolResult) -> ToolOutput {
+    if result.is_error {
+        let text = content_text(&result.content);
+        return ToolOutput::Fail(if text.is_empty() {
+            "the tool failed without a message".to_string()
+        } else {
+            text
+        });
+    }
+
+    if let Some(structured_content) = result.structured_content {
+        return ToolOutput::Json(structured_content);
+    }
+
+    if let [Content::Image { data, mime_type } | Content::Audio { data, mime_type }] =
+        result.content.as_slice()
+        && let Ok(data) = BASE64_STANDARD.decode(data)
+    {
+        return ToolOutput::Attachment(Attachment::new(mime_type, data));
+    }
+
+    ToolOutput::Text(content_text(&result.content))
+}
+
+fn content_text(content: &[Content]) -> String {
+    content
+        .iter()
+        .filter_map(|content| match content {
+            Content::Text { text } => Some(text.clone()),
+            Content::Image { mime_type, .. } => Some(format!("[image {}]", mime_type)),
+            Content::Audio { mime_type, .. } => Some(format!("[audio {}]", mime_type)),
+            Content::Resource { resource } => Some(
+                resource["text"]
+                    .as_str()
+                    .map(ToString::to_string)
+                    .unwrap_or_else(|| format!("[resource {}]", resource["uri"])),
+            ),
+            Content::Unknown => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    use super::*;
+    use crate::mcp::protocol::{JsonRpcMessage, JsonRpcResponse};
+
+    /// Answers like a server with a read-only `echo` and a side-effecting `fail/always` tool.
+    async fn fake_server(
+        reader: impl tokio::io::AsyncRead + Unpin,
+        mut writer: impl tokio::io::AsyncWrite + Unpin,
+    ) {
+        let mut lines = BufReader::new(reader).lines();
+        while let Some(line) = lines.next_line().await.unwrap() {
+            let JsonRpcMessage::Request(request) = serde_json::from_str(&line).unwrap() else {
+                continue;
+            };
+            let Some(id) = request.id else {
+                continue;
+            };
+            let params = request.params.unwrap_or_default();
+
+            let response = match request.method.as_str() {
+                "initialize" => JsonRpcResponse::result(id, json!({ "capabilities": {} })),
+                "tools/list" if params.get("cursor").is_none() => JsonRpcResponse::result(
+                    id,
+                    json!({
+                        "tools": [{
+                            "name": "echo",
+                            "description": "Echo the text",
+                            "inputSchema": { "type": "object" },
+                            "annotations": { "readOnlyHint": true },
+                        }],
+                        "nextCursor": "2",
+                    }),
+                ),
+                "tools/list" => JsonRpcResponse::result(
+                    id,
+                    json!({ "tools": [{ "name": "fail/always", "inputSchema": { "type": "object" } }] }),
+                ),
+                "tools/call" if params["name"] == "echo" => {
+                    // A notification in between must not be mistaken for the response
+                    writer
+                        .write_all(b"{\"jsonrpc\":\"2.0\",\"method\":\"notifications/message\"}\n")
+                        .await
+                        .unwrap();
+                    JsonRpcResponse::result(
+                        id,
+                        json!({ "content": [{ "type": "text", "text": params["arguments"]["text"] }] }),
+                    )
+                }
+                "tools/call" if params["arguments"].get("text").is_some() => {
+                    JsonRpcResponse::error(id, INVALID_PARAMS, "unexpected text")
+                }
+                "tools/call" => JsonRpcResponse::result(
+                    id,
+                    json!({ "content": [{ "type": "text", "text": "boom" }], "isError": true }),
+                ),
+                _ => JsonRpcResponse::error(id, -32601, "unknown method"),
+            };
+
+            let mut line = serde_json::to_string(&response).unwrap();
+            line.push('\n');
+            writer.write_all(line.as_bytes()).await.unwrap();
+        }
+    }
+
+    async fn connect() -> McpToolset {
+        let (client, server) = tokio::io::duplex(4096);
+        let (client_reader, client_writer) = tokio::io::split(client);
+        let (server_reader, server_writer) = tokio::io::split(server);
+        tokio::spawn(fake_server(server_reader, server_writer));
+
+        McpToolset::initialize(
+            "mcp".to_string(),
+            Box::new(StdioTransport::new(client_reader, client_writer)),
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_list_tools() {
+        let toolset = connect().await;
+        let definitions = toolset.definition();
+
+        assert_eq!(
+            definitions
+                .iter()
+                .map(|definition| definition.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["mcp-echo", "mcp-fail_always"]
+        );
+        assert!(!definitions[0].metadata.side_effecting);
+        assert!(definitions[1].metadata.side_effecting);
+        assert!(!definitions[1].metadata.is_idempotent());
+        assert!(toolset.contain("mcp-fail_always"));
+    }
+
+    #[tokio::test]
+    async fn test_invoke() {
+        let toolset = connect().await;
+
+        let output = toolset
+            .invoke("mcp-echo", r#"{"text": "hello"}"#)
+            .await
+            .unwrap();
+        assert_eq!(output, ToolOutput::Text("hello".to_string()));
+
+        let output = toolset.invoke("mcp-fail_always", "").await.unwrap();
+        assert_eq!(output, ToolOutput::Fail("boom".to_string()));
+
+        let err = toolset
+            .invoke("mcp-fail_always", r#"{"text": "hello"}"#)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ToolError::InvalidArguments(_)));
+
+        let err = toolset.invoke("mcp-missing", "").await.unwrap_err();
+        assert!(matches!(err, ToolError::InvalidFunctionName(_)));
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_tool_names() {
+        let mut toolset = connect().await;
+        let tool = |name: &str| {
+            serde_json::from_value::<Tool>(json!({ "name": name, "inputSchema": {} })).unwrap()
+        };
+
+        toolset.add_tool(tool("a.b")).unwrap();
+        let err = toolset.add_tool(tool("a_b")).unwrap_err();
+        assert!(matches!(
+            err,
+            McpError::DuplicateTool { first, second, function }
+                if first == "a.b" && second == "a_b" && function == "mcp-a_b"
+        ));
+        assert_eq!(toolset.definition().len(), 3);
+    }
+
+    #[test]
+    fn test_tool_output() {
+        let output = tool_output(CallToolResult {
+            content: vec![Content::Image {
+                data: BASE64_STANDARD.encode("png"),
+                mime_type: "image/png".to_string(),
+            }],
+            ..Default::default()
+        });
+        assert_eq!(
+            output,
+            ToolOutput::Attachment(Attachment::new("image/png", "png"))
+        );
+
+        let output = tool_output(CallToolResult {
+            content: vec![
+                Content::Text {
+                    text: "see".to_string(),
+                },
+                Content::Resource {
+                    resource: json!({ "uri": "file:///a.txt", "text": "contents" }),
+                },
+                Content::Unknown,
+            ],
+            ..Default::default()
+        });
+        assert_eq!(output, ToolOutput::Text("see\ncontents".to_string()));
+    }
+}
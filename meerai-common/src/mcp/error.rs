@@ -0,0 +1,31 @@
+use std::io;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum McpError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("server returned error {code}: {message}")]
+    Rpc { code: i64, message: String },
+
+    #[error("protocol error: {0}")]
+    Protocol(String),
+
+    /// Two tools of the server end up with the same function name once sanitized
+    #[error("tools `{first}` and `{second}` are both named `{function}`")]
+    DuplicateTool {
+        first: String,
+        second: String,
+        function: String,
+    },
+
+    #[error("connection to the server is closed")]
+    Closed,
+}
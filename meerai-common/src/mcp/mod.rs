@@ -0,0 +1,9 @@
+mod client;
+mod error;
+pub mod protocol;
+mod server;
+mod transport;
+
+pub use client::{McpServerConfig, McpToolset};
+pub use error::McpError;
+pub use server::McpServer;
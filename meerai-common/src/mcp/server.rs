@@ -0,0 +1,368 @@
+use base64::{Engine, prelude::BASE64_STANDARD};
+use meerai_core::{Attachment, ToolDefinition, ToolOutput, ToolRegistry};
+use serde_json::{Value, json};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+
+use super::{
+    McpError,
+    protocol::{
+        CallToolParams, CallToolResult, Content, INVALID_PARAMS, JsonRpcMessage, JsonRpcRequest,
+        JsonRpcResponse, ListToolsResult, METHOD_NOT_FOUND, PROTOCOL_VERSION, Tool,
+        ToolAnnotations,
+    },
+};
+
+/// Error code of messages that are not valid JSON-RPC
+const PARSE_ERROR: i64 = -32700;
+
+/// Serves the tools of a registry to MCP clients.
+///
+/// Function names are used as tool names as they are. Requests are handled one at a time in
+/// the order they arrive.
+pub struct McpServer {
+    name: String,
+    tools: ToolRegistry,
+}
+
+impl McpServer {
+    pub fn new(name: impl Into<String>, tools: ToolRegistry) -> Self {
+        Self {
+            name: name.into(),
+            tools,
+        }
+    }
+
+    /// Serves over the stdin and stdout of the process until stdin is closed.
+    pub async fn serve_stdio(&self) -> Result<(), McpError> {
+        self.serve(tokio::io::stdin(), tokio::io::stdout()).await
+    }
+
+    /// Serves newline delimited JSON-RPC until the reader is closed.
+    pub async fn serve(
+        &self,
+        reader: impl AsyncRead + Unpin,
+        mut writer: impl AsyncWrite + Unpin,
+    ) -> Result<(), McpError> {
+        let mut lines = BufReader::new(reader).lines();
+
+        while let Some(line) = lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let response = match serde_json::from_str::<JsonRpcMessage>(&line) {
+                Ok(JsonRpcMessage::Request(request)) => self.handle(request).await,
+                // The server sends no requests, so there are no responses to wait for
+                Ok(JsonRpcMessage::Response(_)) => None,
+                Err(err) => Some(JsonRpcResponse::error(
+                    Value::Null,
+                    PARSE_ERROR,
+                    err.to_string(),
+                )),
+            };
+
+            if let Some(response) = response {
+                let mut line = serde_json::to_string(&response)?;
+                line.push('\n');
+                writer.write_all(line.as_bytes()).await?;
+                writer.flush().await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the response to a request, or nothing for a notification.
+    async fn handle(&self, request: JsonRpcRequest) -> Option<JsonRpcResponse> {
+        let id = request.id?;
+        let params = request.params.unwrap_or_default();
+
+        let response = match request.method.as_str() {
+            "initialize" => JsonRpcResponse::result(
+                id,
+                json!({
+                    "protocolVersion": PROTOCOL_VERSION,
+                    "capabilities": { "tools": {} },
+                    "serverInfo": {
+                        "name": self.name,
+                        "version": env!("CARGO_PKG_VERSION"),
+                    },
+                }),
+            ),
+            "ping" => JsonRpcResponse::result(id, json!({})),
+            "tools/list" => match serde_json::to_value(self.list_tools()) {
+                Ok(result) => JsonRpcResponse::result(id, result),
+                Err(err) => JsonRpcResponse::error(id, INVALID_PARAMS, err.to_string()),
+            },
+            "tools/call" => match serde_json::from_value::<CallToolParams>(params) {
+                Ok(params) => match self.call_tool(params).await {
+                    Ok(result) => JsonRpcResponse::result(
+                        id,
+                        serde_json::to_value(result).expect("result serializes"),
+                    ),
+                    Err(message) => JsonRpcResponse::error(id, INVALID_PARAMS, message),
+                },
+                Err(err) => JsonRpcResponse::error(id, INVALID_PARAMS, err.to_string()),
+            },
+            method => {
+                JsonRpcResponse::error(id, METHOD_NOT_FOUND, format!("unknown method {}", method))
+            }
+        };
+
+        Some(response)
+    }
+
+    fn list_tools(&self) -> ListToolsResult {
+        ListToolsResult {
+            tools: self.tools.definitions().iter().map(tool).collect(),
+            next_cursor: None,
+        }
+    }
+
+    /// Calls a tool, failures of the tool itself are reported in the result for the model.
+    async fn call_tool(&self, params: CallToolParams) -> Result<CallToolResult, String> {
+        let (Some(toolset), Some(definition)) = (
+            self.tools.get(&params.name),
+            self.tools.definition(&params.name),
+        ) else {
+            return Err(format!("unknown tool {}", params.name));
+        };
+
+        let args = match params.arguments {
+            Value::Null => "{}".to_string(),
+            arguments => arguments.to_string(),
+        };
+        let output = match definition.validate_args(&args) {
+            Ok(()) => toolset.invoke(&params.name, &args).await,
+            Err(err) => Err(err),
+        };
+
+        Ok(match output {
+            Ok(output) => call_tool_result(output),
+            Err(err) => CallToolResult {
+                content: vec![Content::Text {
+                    text: err.to_string(),
+                }],
+                is_error: true,
+                ..Default::default()
+            },
+        })
+    }
+}
+
+fn tool(definition: &ToolDefinition) -> Tool {
+    let metadata = &definition.metadata;
+    Tool {
+        name: definition.name.clone(),
+        description: Some(definition.description.clone()).filter(|d| !d.is_empty()),
+        input_schema: definition.parameters.clone(),
+        annotations: Some(ToolAnnotations {
+            read_only_hint: Some(!metadata.side_effecting),
+            idempotent_hint: Some(metadata.is_idempotent()),
+            ..Default::default()
+        }),
+    }
+}
+
+fn call_tool_result(output: ToolOutput) -> CallToolResult {
+    let text = |text: String| CallToolResult {
+        content: vec![Content::Text { text }],
+        ..Default::default()
+    };
+
+    match output {
+        ToolOutput::Text(output) => text(output),
+        ToolOutput::Json(value) => CallToolResult {
+            content: vec![Content::Text {
+                text: value.to_string(),
+            }],
+            // Structured content has to be an object, other values are only sent as text
+            structured_content: value.is_object().then_some(value),
+            is_error: false,
+        },
+        ToolOutput::Attachment(attachment) => CallToolResult {
+            content: vec![attachment_content(&attachment, "attachment")],
+            ..Default::default()
+        },
+        ToolOutput::Artifact(artifact) => CallToolResult {
+            content: vec![attachment_content(
+                &artifact.attachment,
+                &format!("artifact://{}", artifact.name),
+            )],
+            ..Default::default()
+        },
+        ToolOutput::Fail(message) => CallToolResult {
+            is_error: true,
+            ..text(message)
+        },
+        output @ ToolOutput::Stop(_) => text(output.to_string()),
+    }
+}
+
+fn attachment_content(attachment: &Attachment, uri: &str) -> Content {
+    let data = || BASE64_STANDARD.encode(&attachment.data);
+    let mime_type = attachment.mime_type.clone();
+
+    if mime_type.starts_with("image/") {
+        Content::Image {
+            data: data(),
+            mime_type,
+        }
+    } else if mime_type.starts_with("audio/") {
+        Content::Audio {
+            data: data(),
+            mime_type,
+        }
+    } else if let Some(text) = attachment.as_text() {
+        Content::Resource {
+            resource: json!({ "uri": uri, "mimeType": mime_type, "text": text }),
+        }
+    } else {
+        Content::Resource {
+            resource: json!({ "uri": uri, "mimeType": mime_type, "blob": data() }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::pin::Pin;
+
+    use meerai_core::{JsonSchema, ToolError, Toolset, async_trait};
+    use meerai_macros::Toolset;
+
+    use super::*;
+    use crate::mcp::{client::McpToolset, transport::StdioTransport};
+
+    #[derive(Debug, Clone, serde::Deserialize, serde::Serialize, JsonSchema)]
+    struct DivideArgs {
+        a: f64,
+        b: f64,
+    }
+
+    #[derive(Toolset)]
+    #[toolset(
+        name = "math",
+        tool(
+            name = "Divide",
+            description = "Divide a by b",
+            params = DivideArgs,
+        )
+    )]
+    struct MathToolset;
+
+    #[async_trait]
+    impl MathInvoke for MathToolset {
+        async fn divide(&self, args: &DivideArgs) -> Result<ToolOutput, ToolError> {
+            if args.b == 0.0 {
+                return Ok(ToolOutput::Fail("division by zero".to_string()));
+            }
+            Ok(ToolOutput::Json(json!({ "quotient": args.a / args.b })))
+        }
+    }
+
+    /// Connects the MCP client to a server over an in-memory pipe.
+    async fn connect() -> McpToolset {
+        let registry =
+            ToolRegistry::try_from(vec![Box::pin(MathToolset) as Pin<Box<dyn Toolset>>]).unwrap();
+        let server = McpServer::new("test", registry);
+
+        let (client, server_io) = tokio::io::duplex(4096);
+        let (server_reader, server_writer) = tokio::io::split(server_io);
+        tokio::spawn(async move { server.serve(server_reader, server_writer).await });
+
+        let (client_reader, client_writer) = tokio::io::split(client);
+        McpToolset::initialize(
+            "remote".to_string(),
+            Box::new(StdioTransport::new(client_reader, client_writer)),
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_round_trip() {
+        let client = connect().await;
+
+        let definitions = client.definition();
+        assert_eq!(definitions.len(), 1);
+        assert_eq!(definitions[0].name, "remote-math-divide");
+        assert_eq!(definitions[0].description, "Divide a by b");
+        assert!(!definitions[0].metadata.side_effecting);
+
+        let output = client
+            .invoke("remote-math-divide", r#"{"a": 1, "b": 4}"#)
+            .await
+            .unwrap();
+        assert_eq!(output, ToolOutput::Json(json!({ "quotient": 0.25 })));
+
+        let output = client
+            .invoke("remote-math-divide", r#"{"a": 1, "b": 0}"#)
+            .await
+            .unwrap();
+        assert_eq!(output, ToolOutput::Fail("division by zero".to_string()));
+
+        let output = client
+            .invoke("remote-math-divide", r#"{"a": 1}"#)
+            .await
+            .unwrap();
+        assert_eq!(
+            output,
+            ToolOutput::Fail(
+                "arguments for tool do not match its schema: $: missing required field `b`"
+                    .to_string()
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn test_protocol_errors() {
+        let server = McpServer::new("test", ToolRegistry::new());
+        let input = [
+            r#"{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"missing"}}"#,
+            r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#,
+            r#"{"jsonrpc":"2.0","id":2,"method":"resources/list"}"#,
+            "not json",
+        ]
+        .join("\n");
+
+        let mut output = vec![];
+        server.serve(input.as_bytes(), &mut output).await.unwrap();
+
+        let codes = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<JsonRpcResponse>(line).unwrap())
+            .map(|response| (response.id, response.error.unwrap().code))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            codes,
+            vec![
+                (json!(1), INVALID_PARAMS),
+                (json!(2), METHOD_NOT_FOUND),
+                (Value::Null, PARSE_ERROR),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_call_tool_result() {
+        let result = call_tool_result(ToolOutput::Attachment(Attachment::new("image/png", "png")));
+        assert_eq!(
+            result.content,
+            vec![Content::Image {
+                data: BASE64_STANDARD.encode("png"),
+                mime_type: "image/png".to_string(),
+            }]
+        );
+
+        let result = call_tool_result(ToolOutput::Json(json!([1, 2])));
+        assert_eq!(result.structured_content, None);
+        assert_eq!(
+            result.content,
+            vec![Content::Text {
+                text: "[1,2]".to_string()
+            }]
+        );
+    }
+}
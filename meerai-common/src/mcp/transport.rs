@@ -0,0 +1,249 @@
+use std::{collections::HashMap, process::Stdio, sync::Mutex};
+
+use meerai_core::async_trait;
+use reqwest::{
+    Client,
+    header::{ACCEPT, CONTENT_TYPE, HeaderMap, HeaderName, HeaderValue},
+};
+use serde::Serialize;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader},
+    process::{Child, Command},
+};
+
+use super::{
+    McpError,
+    protocol::{JsonRpcMessage, JsonRpcRequest, JsonRpcResponse, METHOD_NOT_FOUND},
+};
+
+const SESSION_ID: &str = "mcp-session-id";
+
+/// Carries JSON-RPC messages between a client and an MCP server.
+#[async_trait]
+pub(crate) trait Transport: Send + Sync {
+    /// Sends a request and waits for the response with the same id.
+    async fn request(&self, request: JsonRpcRequest) -> Result<JsonRpcResponse, McpError>;
+
+    async fn notify(&self, notification: JsonRpcRequest) -> Result<(), McpError>;
+}
+
+type BoxedReader = BufReader<Box<dyn AsyncRead + Send + Unpin>>;
+type BoxedWriter = Box<dyn AsyncWrite + Send + Unpin>;
+
+/// Newline delimited JSON-RPC over a pair of streams, usually the stdio of a child process.
+///
+/// Requests are sent one at a time, so a slow call blocks the others on the same server.
+pub(crate) struct StdioTransport {
+    io: tokio::sync::Mutex<(BoxedReader, BoxedWriter)>,
+
+    /// Kept so the server is killed together with the transport
+    _child: Option<Child>,
+}
+
+impl StdioTransport {
+    pub fn spawn(
+        command: &str,
+        args: &[String],
+        env: &HashMap<String, String>,
+    ) -> Result<Self, McpError> {
+        let mut child = Command::new(command)
+            .args(args)
+            .envs(env)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .kill_on_drop(true)
+            .spawn()?;
+
+        let reader = child.stdout.take().expect("stdout is piped");
+        let writer = child.stdin.take().expect("stdin is piped");
+        Ok(Self {
+            _child: Some(child),
+            ..Self::new(reader, writer)
+        })
+    }
+
+    pub fn new(
+        reader: impl AsyncRead + Send + Unpin + 'static,
+        writer: impl AsyncWrite + Send + Unpin + 'static,
+    ) -> Self {
+        let reader: Box<dyn AsyncRead + Send + Unpin> = Box::new(reader);
+        Self {
+            io: tokio::sync::Mutex::new((BufReader::new(reader), Box::new(writer))),
+            _child: None,
+        }
+    }
+}
+
+async fn write_line(writer: &mut BoxedWriter, message: &impl Serialize) -> Result<(), McpError> {
+    let mut line = serde_json::to_string(message)?;
+    line.push('\n');
+    writer.write_all(line.as_bytes()).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+#[async_trait]
+impl Transport for StdioTransport {
+    async fn request(&self, request: JsonRpcRequest) -> Result<JsonRpcResponse, McpError> {
+        let mut io = self.io.lock().await;
+        let (reader, writer) = &mut *io;
+        write_line(writer, &request).await?;
+
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if reader.read_line(&mut line).await? == 0 {
+                return Err(McpError::Closed);
+            }
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            match serde_json::from_str::<JsonRpcMessage>(&line)? {
+                JsonRpcMessage::Response(response) if Some(&response.id) == request.id.as_ref() => {
+                    return Ok(response);
+                }
+                // Servers may ping while a call is running, other requests are not supported
+                JsonRpcMessage::Request(JsonRpcRequest {
+                    id: Some(id),
+                    method,
+                    ..
+                }) => {
+                    let response = if method == "ping" {
+                        JsonRpcResponse::result(id, serde_json::json!({}))
+                    } else {
+                        JsonRpcResponse::error(id, METHOD_NOT_FOUND, "method not supported")
+                    };
+                    write_line(writer, &response).await?;
+                }
+                // Notifications such as log messages and stale responses are dropped
+                _ => {}
+            }
+        }
+    }
+
+    async fn notify(&self, notification: JsonRpcRequest) -> Result<(), McpError> {
+        let mut io = self.io.lock().await;
+        write_line(&mut io.1, &notification).await
+    }
+}
+
+/// The streamable HTTP transport, every message is POSTed to a single endpoint.
+pub(crate) struct HttpTransport {
+    client: Client,
+    url: String,
+    headers: HeaderMap,
+
+    /// Assigned by the server on initialization and sent back with every later message
+    session_id: Mutex<Option<String>>,
+}
+
+impl HttpTransport {
+    pub fn new(url: &str, headers: &HashMap<String, String>) -> Result<Self, McpError> {
+        let mut header_map = HeaderMap::new();
+        for (name, value) in headers {
+            let name = HeaderName::try_from(name.as_str())
+                .map_err(|err| McpError::Protocol(format!("invalid header {}: {}", name, err)))?;
+            let value = HeaderValue::try_from(value.as_str())
+                .map_err(|err| McpError::Protocol(format!("invalid header {}: {}", name, err)))?;
+            header_map.insert(name, value);
+        }
+
+        Ok(Self {
+            client: Client::new(),
+            url: url.to_string(),
+            headers: header_map,
+            session_id: Mutex::new(None),
+        })
+    }
+
+    async fn post(&self, message: &JsonRpcRequest) -> Result<reqwest::Response, McpError> {
+        let mut request = self
+            .client
+            .post(&self.url)
+            .headers(self.headers.clone())
+            .header(ACCEPT, "application/json, text/event-stream")
+            .json(message);
+        if let Some(session_id) = self.session_id.lock().expect("session lock").clone() {
+            request = request.header(SESSION_ID, session_id);
+        }
+
+        let response = request.send().await?.error_for_status()?;
+        if let Some(session_id) = response
+            .headers()
+            .get(SESSION_ID)
+            .and_then(|value| value.to_str().ok())
+        {
+            *self.session_id.lock().expect("session lock") = Some(session_id.to_string());
+        }
+
+        Ok(response)
+    }
+}
+
+#[async_trait]
+impl Transport for HttpTransport {
+    async fn request(&self, request: JsonRpcRequest) -> Result<JsonRpcResponse, McpError> {
+        let response = self.post(&request).await?;
+        let is_event_stream = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.starts_with("text/event-stream"));
+
+        if !is_event_stream {
+            return Ok(response.json().await?);
+        }
+
+        // The server closes the stream once it has sent the response
+        let body = response.text().await?;
+        for data in sse_data(&body) {
+            if let JsonRpcMessage::Response(response) = serde_json::from_str(&data)?
+                && Some(&response.id) == request.id.as_ref()
+            {
+                return Ok(response);
+            }
+        }
+
+        Err(McpError::Protocol(format!(
+            "event stream ended without a response to `{}`",
+            request.method
+        )))
+    }
+
+    async fn notify(&self, notification: JsonRpcRequest) -> Result<(), McpError> {
+        self.post(&notification).await?;
+        Ok(())
+    }
+}
+
+/// Returns the data of every event of a server-sent event stream.
+fn sse_data(body: &str) -> Vec<String> {
+    let mut events = vec![];
+    let mut data: Vec<&str> = vec![];
+
+    for line in body.lines().chain([""]) {
+        if line.is_empty() {
+            if !data.is_empty() {
+                events.push(data.join("\n"));
+                data.clear();
+            }
+        } else if let Some(value) = line.strip_prefix("data:") {
+            data.push(value.strip_prefix(' ').unwrap_or(value));
+        }
+    }
+
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sse_data() {
+        let body = "event: message\nid: 1\ndata: {\"a\":\ndata: 1}\n\n: keep-alive\n\ndata:{}\n";
+        assert_eq!(sse_data(body), vec!["{\"a\":\n1}", "{}"]);
+    }
+}
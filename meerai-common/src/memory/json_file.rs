@@ -0,0 +1,89 @@
+use std::{
+    ffi::OsString,
+    io,
+    path::{Path, PathBuf},
+};
+
+use meerai_core::async_trait;
+use tokio::sync::Mutex;
+
+use super::{MemoryEntry, MemoryError, MemoryStore, Namespaces, list_prefixed};
+
+/// Keeps memories in a JSON file, which is rewritten on every change.
+///
+/// The whole file is loaded when opened, so it suits the small amount of notes an agent keeps
+/// rather than large datasets.
+pub struct JsonFileStore {
+    path: PathBuf,
+    namespaces: Mutex<Namespaces>,
+}
+
+impl JsonFileStore {
+    /// Loads the file, which is created on the first change if it does not exist.
+    pub async fn open(path: impl AsRef<Path>) -> Result<Self, MemoryError> {
+        let path = path.as_ref().to_path_buf();
+        let namespaces = match tokio::fs::read(&path).await {
+            Ok(content) => serde_json::from_slice(&content)?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Namespaces::new(),
+            Err(err) => return Err(err.into()),
+        };
+
+        Ok(Self {
+            path,
+            namespaces: Mutex::new(namespaces),
+        })
+    }
+
+    /// Writes to a temporary file first so a crash never leaves a truncated file behind.
+    async fn save(&self, namespaces: &Namespaces) -> Result<(), MemoryError> {
+        let mut temp_name = self.path.file_name().map_or_else(OsString::new, Into::into);
+        temp_name.push(".tmp");
+        let temp_path = self.path.with_file_name(temp_name);
+
+        tokio::fs::write(&temp_path, serde_json::to_vec_pretty(namespaces)?).await?;
+        tokio::fs::rename(&temp_path, &self.path).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl MemoryStore for JsonFileStore {
+    async fn get(&self, namespace: &str, key: &str) -> Result<Option<MemoryEntry>, MemoryError> {
+        let namespaces = self.namespaces.lock().await;
+        Ok(namespaces
+            .get(namespace)
+            .and_then(|entries| entries.get(key))
+            .cloned())
+    }
+
+    async fn set(&self, namespace: &str, entry: MemoryEntry) -> Result<(), MemoryError> {
+        let mut namespaces = self.namespaces.lock().await;
+        // Changes are kept only once saved, so a failed write does not leave them in memory
+        let mut updated = namespaces.clone();
+        updated
+            .entry(namespace.to_string())
+            .or_default()
+            .insert(entry.key.clone(), entry);
+        self.save(&updated).await?;
+        *namespaces = updated;
+        Ok(())
+    }
+
+    async fn list(&self, namespace: &str, prefix: &str) -> Result<Vec<MemoryEntry>, MemoryError> {
+        let namespaces = self.namespaces.lock().await;
+        Ok(list_prefixed(namespaces.get(namespace), prefix))
+    }
+
+    async fn remove(&self, namespace: &str, key: &str) -> Result<bool, MemoryError> {
+        let mut namespaces = self.namespaces.lock().await;
+        let mut updated = namespaces.clone();
+        let removed = updated
+            .get_mut(namespace)
+            .is_some_and(|entries| entries.remove(key).is_some());
+        if removed {
+            self.save(&updated).await?;
+            *namespaces = updated;
+        }
+        Ok(removed)
+    }
+}
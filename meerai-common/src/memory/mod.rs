@@ -0,0 +1,225 @@
+mod json_file;
+mod sqlite;
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    io,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+pub use json_file::JsonFileStore;
+use meerai_core::async_trait;
+use serde::{Deserialize, Serialize};
+pub use sqlite::SqliteStore;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum MemoryError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("SQLite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+}
+
+/// A value remembered under a key.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct MemoryEntry {
+    pub key: String,
+    pub value: String,
+
+    /// Unix timestamp in seconds of the last time the value was set
+    pub updated_at: u64,
+}
+
+impl MemoryEntry {
+    pub fn new(key: impl Into<String>, value: impl Into<String>) -> Self {
+        let updated_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        Self {
+            key: key.into(),
+            value: value.into(),
+            updated_at,
+        }
+    }
+}
+
+/// Storage of memories, separated into namespaces that do not see each other's keys.
+#[async_trait]
+pub trait MemoryStore: Send + Sync {
+    async fn get(&self, namespace: &str, key: &str) -> Result<Option<MemoryEntry>, MemoryError>;
+
+    /// Inserts the entry, replacing the one with the same key.
+    async fn set(&self, namespace: &str, entry: MemoryEntry) -> Result<(), MemoryError>;
+
+    /// Returns the entries whose key starts with `prefix`, ordered by key.
+    async fn list(&self, namespace: &str, prefix: &str) -> Result<Vec<MemoryEntry>, MemoryError>;
+
+    /// Removes the entry and returns whether it existed.
+    async fn remove(&self, namespace: &str, key: &str) -> Result<bool, MemoryError>;
+}
+
+type Namespaces = HashMap<String, BTreeMap<String, MemoryEntry>>;
+
+/// Keeps memories for the lifetime of the process.
+#[derive(Debug, Default)]
+pub struct InMemoryStore {
+    namespaces: Mutex<Namespaces>,
+}
+
+impl InMemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl MemoryStore for InMemoryStore {
+    async fn get(&self, namespace: &str, key: &str) -> Result<Option<MemoryEntry>, MemoryError> {
+        let namespaces = self.namespaces.lock().expect("memory lock");
+        Ok(namespaces
+            .get(namespace)
+            .and_then(|entries| entries.get(key))
+            .cloned())
+    }
+
+    async fn set(&self, namespace: &str, entry: MemoryEntry) -> Result<(), MemoryError> {
+        let mut namespaces = self.namespaces.lock().expect("memory lock");
+        namespaces
+            .entry(namespace.to_string())
+            .or_default()
+            .insert(entry.key.clone(), entry);
+        Ok(())
+    }
+
+    async fn list(&self, namespace: &str, prefix: &str) -> Result<Vec<MemoryEntry>, MemoryError> {
+        let namespaces = self.namespaces.lock().expect("memory lock");
+        Ok(list_prefixed(namespaces.get(namespace), prefix))
+    }
+
+    async fn remove(&self, namespace: &str, key: &str) -> Result<bool, MemoryError> {
+        let mut namespaces = self.namespaces.lock().expect("memory lock");
+        Ok(namespaces
+            .get_mut(namespace)
+            .is_some_and(|entries| entries.remove(key).is_some()))
+    }
+}
+
+fn list_prefixed(
+    entries: Option<&BTreeMap<String, MemoryEntry>>,
+    prefix: &str,
+) -> Vec<MemoryEntry> {
+    entries
+        .into_iter()
+        .flat_map(|entries| entries.range(prefix.to_string()..))
+        .take_while(|(key, _)| key.starts_with(prefix))
+        .map(|(_, entry)| entry.clone())
+        .collect()
+}
+
+/// Selects and configures the store backing the memory toolsets.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(tag = "store", rename_all = "snake_case")]
+pub enum MemoryStoreConfig {
+    /// Memories are lost when the process exits
+    #[default]
+    Memory,
+
+    JsonFile {
+        path: PathBuf,
+    },
+
+    Sqlite {
+        path: PathBuf,
+    },
+}
+
+impl MemoryStoreConfig {
+    pub async fn open(&self) -> Result<Arc<dyn MemoryStore>, MemoryError> {
+        Ok(match self {
+            MemoryStoreConfig::Memory => Arc::new(InMemoryStore::new()),
+            MemoryStoreConfig::JsonFile { path } => Arc::new(JsonFileStore::open(path).await?),
+            MemoryStoreConfig::Sqlite { path } => Arc::new(SqliteStore::open(path)?),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn check_store(store: &dyn MemoryStore) {
+        for (key, value) in [("replied/bob", "1"), ("replied/alice", "2"), ("topic", "3")] {
+            store
+                .set("bluesky", MemoryEntry::new(key, value))
+                .await
+                .unwrap();
+        }
+        store
+            .set("x", MemoryEntry::new("topic", "other"))
+            .await
+            .unwrap();
+        store
+            .set("bluesky", MemoryEntry::new("topic", "4"))
+            .await
+            .unwrap();
+
+        let entry = store.get("bluesky", "topic").await.unwrap().unwrap();
+        assert_eq!(entry.value, "4");
+        assert_eq!(store.get("bluesky", "missing").await.unwrap(), None);
+        assert_eq!(store.get("other", "topic").await.unwrap(), None);
+
+        let keys = |entries: Vec<MemoryEntry>| {
+            entries
+                .into_iter()
+                .map(|entry| entry.key)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            keys(store.list("bluesky", "replied/").await.unwrap()),
+            vec!["replied/alice", "replied/bob"]
+        );
+        assert_eq!(keys(store.list("bluesky", "").await.unwrap()).len(), 3);
+        assert_eq!(keys(store.list("x", "").await.unwrap()), vec!["topic"]);
+
+        assert!(store.remove("bluesky", "replied/bob").await.unwrap());
+        assert!(!store.remove("bluesky", "replied/bob").await.unwrap());
+        assert_eq!(store.get("bluesky", "replied/bob").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_stores() {
+        check_store(&InMemoryStore::new()).await;
+        check_store(&SqliteStore::open_in_memory().unwrap()).await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("memories.json");
+        check_store(&JsonFileStore::open(&path).await.unwrap()).await;
+
+        // Memories survive reopening the file
+        let store = JsonFileStore::open(&path).await.unwrap();
+        assert_eq!(store.list("bluesky", "").await.unwrap().len(), 2);
+        assert_eq!(
+            store.get("x", "topic").await.unwrap().unwrap().value,
+            "other"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_json_file_store_failed_save() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = JsonFileStore::open(dir.path().join("missing/memories.json"))
+            .await
+            .unwrap();
+
+        let err = store.set("x", MemoryEntry::new("topic", "1")).await;
+        assert!(matches!(err, Err(MemoryError::Io(_))));
+        assert_eq!(store.get("x", "topic").await.unwrap(), None);
+    }
+}
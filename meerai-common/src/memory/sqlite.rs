@@ -0,0 +1,124 @@
+use std::{
+    io,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use meerai_core::async_trait;
+use rusqlite::{Connection, OptionalExtension, params};
+
+use super::{MemoryEntry, MemoryError, MemoryStore};
+
+/// Keeps memories in an SQLite database.
+///
+/// Queries block on disk access, so they run on tokio's blocking thread pool.
+pub struct SqliteStore {
+    connection: Arc<Mutex<Connection>>,
+}
+
+impl SqliteStore {
+    /// Opens the database, creating the file and the `memories` table if needed.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, MemoryError> {
+        Self::init(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self, MemoryError> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(connection: Connection) -> Result<Self, MemoryError> {
+        connection.execute_batch(
+            "CREATE TABLE IF NOT EXISTS memories (
+                namespace TEXT NOT NULL,
+                key TEXT NOT NULL,
+                value TEXT NOT NULL,
+                updated_at INTEGER NOT NULL,
+                PRIMARY KEY (namespace, key)
+            )",
+        )?;
+        Ok(Self {
+            connection: Arc::new(Mutex::new(connection)),
+        })
+    }
+
+    /// Runs the query on the blocking thread pool.
+    async fn run<T, F>(&self, query: F) -> Result<T, MemoryError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Connection) -> rusqlite::Result<T> + Send + 'static,
+    {
+        let connection = Arc::clone(&self.connection);
+        let result =
+            tokio::task::spawn_blocking(move || query(&connection.lock().expect("sqlite lock")))
+                .await
+                .map_err(io::Error::other)?;
+        Ok(result?)
+    }
+}
+
+fn entry_from_row(row: &rusqlite::Row) -> rusqlite::Result<MemoryEntry> {
+    Ok(MemoryEntry {
+        key: row.get(0)?,
+        value: row.get(1)?,
+        updated_at: row.get(2)?,
+    })
+}
+
+#[async_trait]
+impl MemoryStore for SqliteStore {
+    async fn get(&self, namespace: &str, key: &str) -> Result<Option<MemoryEntry>, MemoryError> {
+        let (namespace, key) = (namespace.to_string(), key.to_string());
+        self.run(move |connection| {
+            connection
+                .query_row(
+                    "SELECT key, value, updated_at FROM memories WHERE namespace = ?1 AND key = ?2",
+                    params![namespace, key],
+                    entry_from_row,
+                )
+                .optional()
+        })
+        .await
+    }
+
+    async fn set(&self, namespace: &str, entry: MemoryEntry) -> Result<(), MemoryError> {
+        let namespace = namespace.to_string();
+        self.run(move |connection| {
+            connection.execute(
+                "INSERT OR REPLACE INTO memories (namespace, key, value, updated_at)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![namespace, entry.key, entry.value, entry.updated_at],
+            )
+        })
+        .await?;
+        Ok(())
+    }
+
+    async fn list(&self, namespace: &str, prefix: &str) -> Result<Vec<MemoryEntry>, MemoryError> {
+        let (namespace, prefix) = (namespace.to_string(), prefix.to_string());
+        self.run(move |connection| {
+            // `substr` rather than `LIKE`, so `%` and `_` in the prefix are not wildcards
+            let mut statement = connection.prepare(
+                "SELECT key, value, updated_at FROM memories
+                 WHERE namespace = ?1 AND substr(key, 1, length(?2)) = ?2
+                 ORDER BY key",
+            )?;
+            statement
+                .query_map(params![namespace, prefix], entry_from_row)?
+                .collect()
+        })
+        .await
+    }
+
+    async fn remove(&self, namespace: &str, key: &str) -> Result<bool, MemoryError> {
+        let (namespace, key) = (namespace.to_string(), key.to_string());
+        let removed = self
+            .run(move |connection| {
+                connection.execute(
+                    "DELETE FROM memories WHERE namespace = ?1 AND key = ?2",
+                    params![namespace, key],
+                )
+            })
+            .await?;
+        Ok(removed > 0)
+    }
+}
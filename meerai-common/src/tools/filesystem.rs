@@ -0,0 +1,1032 @@
+use std::{
+    io::ErrorKind,
+    path::{Component, Path, PathBuf},
+};
+
+use meerai_core::{
+    JsonSchema, ToolDefinition, ToolError, ToolOutput, Toolset, ToolsetAdapter, ToolsetExt,
+    async_trait,
+};
+use meerai_macros::Toolset;
+use regex::Regex;
+
+/// Configuration for the FilesystemToolset.
+#[derive(Debug, Clone)]
+pub struct FilesystemConfig {
+    /// Directory all paths are relative to, nothing outside of it can be accessed
+    pub root: PathBuf,
+
+    /// Maximum size of a file that is read or written
+    pub max_file_bytes: u64,
+
+    /// Maximum number of matching lines returned by a search
+    pub max_search_results: usize,
+
+    /// Leaves out the tools changing files
+    pub read_only: bool,
+}
+
+impl FilesystemConfig {
+    /// Creates a configuration for a dedicated `root`, files below it can be read and written.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            max_file_bytes: 1024 * 1024,
+            max_search_results: 100,
+            read_only: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize, JsonSchema)]
+pub struct ReadFileArgs {
+    /// Path relative to the workspace root
+    pub path: String,
+}
+
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize, JsonSchema)]
+pub struct WriteFileArgs {
+    /// Path relative to the workspace root, missing parent directories are created
+    pub path: String,
+
+    pub content: String,
+}
+
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize, JsonSchema)]
+pub struct ListDirArgs {
+    /// Path relative to the workspace root, defaults to the root itself
+    #[serde(default)]
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize, JsonSchema)]
+pub struct SearchArgs {
+    /// Regular expression matched against each line
+    pub pattern: String,
+
+    /// File or directory to search in, defaults to the workspace root
+    #[serde(default)]
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize, JsonSchema)]
+pub struct ApplyPatchArgs {
+    /// Unified diff with paths relative to the workspace root
+    pub patch: String,
+}
+
+/// Functions available in read-only mode.
+const READ_FUNCTIONS: [&str; 3] = ["fs-read_file", "fs-list_dir", "fs-search"];
+
+/// Reads and edits files below a root directory.
+///
+/// Paths are resolved one component at a time and symlinks are followed as they are met,
+/// so neither `..` nor a symlink can lead outside the root.
+pub struct FilesystemToolset {
+    workspace: ToolsetAdapter<Workspace>,
+}
+
+impl FilesystemToolset {
+    pub fn new(config: FilesystemConfig) -> Self {
+        let workspace = Workspace { config };
+        let workspace = if workspace.config.read_only {
+            workspace.only(READ_FUNCTIONS)
+        } else {
+            ToolsetAdapter::new(workspace)
+        };
+        Self { workspace }
+    }
+}
+
+#[async_trait]
+impl Toolset for FilesystemToolset {
+    fn name(&self) -> String {
+        self.workspace.name()
+    }
+
+    fn definition(&self) -> Vec<ToolDefinition> {
+        self.workspace.definition()
+    }
+
+    fn contain(&self, fn_name: &str) -> bool {
+        self.workspace.contain(fn_name)
+    }
+
+    async fn invoke(&self, fn_name: &str, args: &str) -> Result<ToolOutput, ToolError> {
+        self.workspace.invoke(fn_name, args).await
+    }
+}
+
+/// All tools of the filesystem toolset, regardless of read-only mode.
+#[derive(Clone, Toolset)]
+#[toolset(
+    name = "fs",
+    tool(
+        name = "Read File",
+        description = "Read a text file of the workspace.",
+        params = ReadFileArgs,
+    ),
+    tool(
+        name = "Write File",
+        description = "Create or overwrite a text file of the workspace.",
+        params = WriteFileArgs,
+        side_effecting,
+        // Writing the same content again leaves the same file
+        idempotent = true,
+    ),
+    tool(
+        name = "List Dir",
+        description = "List the entries of a directory of the workspace.",
+        params = ListDirArgs,
+    ),
+    tool(
+        name = "Search",
+        description = "Search the files of the workspace for lines matching a regular expression.",
+        params = SearchArgs,
+    ),
+    tool(
+        name = "Apply Patch",
+        description = "Apply a unified diff to the files of the workspace. Either every file is changed or none.",
+        params = ApplyPatchArgs,
+        side_effecting,
+        idempotent = false,
+    )
+)]
+struct Workspace {
+    config: FilesystemConfig,
+}
+
+impl Workspace {
+    /// Runs blocking file system work on the blocking thread pool.
+    async fn blocking<T: Send + 'static>(
+        &self,
+        work: impl FnOnce(&Self) -> Result<T, ToolError> + Send + 'static,
+    ) -> Result<T, ToolError> {
+        let toolset = self.clone();
+        tokio::task::spawn_blocking(move || work(&toolset))
+            .await
+            .map_err(|err| ToolError::Unknown(err.into()))?
+    }
+
+    fn root(&self) -> Result<PathBuf, ToolError> {
+        self.config
+            .root
+            .canonicalize()
+            .map_err(|err| ToolError::Unknown(err.into()))
+    }
+
+    /// Resolves a path relative to the root, the path itself does not need to exist.
+    fn resolve(&self, arg: &str, path: &str) -> Result<PathBuf, ToolError> {
+        let root = self.root()?;
+        let escapes = || ToolError::invalid_argument(arg, "must stay inside the workspace");
+
+        let mut resolved = root.clone();
+        for component in Path::new(path).components() {
+            match component {
+                Component::CurDir => {}
+                Component::Normal(part) => {
+                    resolved.push(part);
+
+                    let is_symlink = resolved
+                        .symlink_metadata()
+                        .is_ok_and(|metadata| metadata.is_symlink());
+                    match resolved.canonicalize() {
+                        Ok(canonical) => resolved = canonical,
+                        // A dangling symlink could be written through to anywhere
+                        Err(_) if is_symlink => return Err(escapes()),
+                        Err(_) => {}
+                    }
+
+                    if !resolved.starts_with(&root) {
+                        return Err(escapes());
+                    }
+                }
+                Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
+                    return Err(escapes());
+                }
+            }
+        }
+
+        Ok(resolved)
+    }
+
+    /// Returns the path relative to the root for display to the model.
+    fn display(&self, path: &Path) -> String {
+        self.root()
+            .ok()
+            .and_then(|root| path.strip_prefix(root).ok().map(Path::to_path_buf))
+            .unwrap_or_else(|| path.to_path_buf())
+            .display()
+            .to_string()
+    }
+
+    fn read_text(&self, arg: &str, path: &Path) -> Result<String, ToolError> {
+        let metadata = std::fs::metadata(path).map_err(|err| io_error(arg, err))?;
+        if metadata.len() > self.config.max_file_bytes {
+            return Err(ToolError::invalid_argument(
+                arg,
+                format!(
+                    "file has {} bytes, the limit is {}",
+                    metadata.len(),
+                    self.config.max_file_bytes
+                ),
+            ));
+        }
+
+        let bytes = std::fs::read(path).map_err(|err| io_error(arg, err))?;
+        String::from_utf8(bytes)
+            .map_err(|_| ToolError::invalid_argument(arg, "file is not valid UTF-8 text"))
+    }
+
+    fn check_size(&self, arg: &str, content: &str) -> Result<(), ToolError> {
+        if content.len() as u64 > self.config.max_file_bytes {
+            return Err(ToolError::invalid_argument(
+                arg,
+                format!(
+                    "content has {} bytes, the limit is {}",
+                    content.len(),
+                    self.config.max_file_bytes
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    fn search_file(
+        &self,
+        regex: &Regex,
+        path: &Path,
+        matches: &mut Vec<String>,
+    ) -> Result<(), ToolError> {
+        // Large and binary files are skipped
+        let Ok(content) = self.read_text("$.path", path) else {
+            return Ok(());
+        };
+
+        for (number, line) in content.lines().enumerate() {
+            if matches.len() > self.config.max_search_results {
+                break;
+            }
+            if regex.is_match(line) {
+                matches.push(format!("{}:{}: {}", self.display(path), number + 1, line));
+            }
+        }
+        Ok(())
+    }
+
+    fn search_dir(
+        &self,
+        regex: &Regex,
+        dir: &Path,
+        matches: &mut Vec<String>,
+    ) -> Result<(), ToolError> {
+        let mut entries = std::fs::read_dir(dir)
+            .map_err(|err| io_error("$.path", err))?
+            .filter_map(Result::ok)
+            .collect::<Vec<_>>();
+        entries.sort_by_key(|entry| entry.file_name());
+
+        for entry in entries {
+            if matches.len() > self.config.max_search_results {
+                break;
+            }
+
+            // Symlinks are not followed so the search cannot leave the workspace
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_dir() && entry.file_name() != ".git" {
+                self.search_dir(regex, &entry.path(), matches)?;
+            } else if file_type.is_file() {
+                self.search_file(regex, &entry.path(), matches)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_file_blocking(&self, args: &WriteFileArgs) -> Result<ToolOutput, ToolError> {
+        self.check_size("$.content", &args.content)?;
+        let path = self.resolve("$.path", &args.path)?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|err| io_error("$.path", err))?;
+        }
+        std::fs::write(&path, &args.content).map_err(|err| io_error("$.path", err))?;
+
+        Ok(ToolOutput::Text(format!(
+            "wrote {} bytes to {}",
+            args.content.len(),
+            self.display(&path)
+        )))
+    }
+
+    fn list_dir_blocking(&self, args: &ListDirArgs) -> Result<ToolOutput, ToolError> {
+        let path = self.resolve("$.path", args.path.as_deref().unwrap_or("."))?;
+
+        let mut entries = std::fs::read_dir(&path)
+            .map_err(|err| io_error("$.path", err))?
+            .filter_map(Result::ok)
+            .map(|entry| {
+                let metadata = entry.path().symlink_metadata().ok();
+                let kind = match metadata.as_ref().map(|metadata| metadata.file_type()) {
+                    Some(file_type) if file_type.is_symlink() => "symlink",
+                    Some(file_type) if file_type.is_dir() => "dir",
+                    _ => "file",
+                };
+
+                serde_json::json!({
+                    "name": entry.file_name().to_string_lossy(),
+                    "type": kind,
+                    "size": metadata.map(|metadata| metadata.len()),
+                })
+            })
+            .collect::<Vec<_>>();
+        entries.sort_by(|a, b| a["name"].as_str().cmp(&b["name"].as_str()));
+
+        Ok(ToolOutput::Json(entries.into()))
+    }
+
+    fn search_blocking(&self, args: &SearchArgs) -> Result<ToolOutput, ToolError> {
+        let regex = Regex::new(&args.pattern)
+            .map_err(|err| ToolError::invalid_argument("$.pattern", err.to_string()))?;
+        let path = self.resolve("$.path", args.path.as_deref().unwrap_or("."))?;
+
+        let mut matches = vec![];
+        if path.is_dir() {
+            self.search_dir(&regex, &path, &mut matches)?;
+        } else {
+            self.search_file(&regex, &path, &mut matches)?;
+        }
+
+        if matches.is_empty() {
+            return Ok(ToolOutput::Text("no matches".to_string()));
+        }
+        if matches.len() > self.config.max_search_results {
+            matches.truncate(self.config.max_search_results);
+            matches.push(format!(
+                "[stopped after {} matches]",
+                self.config.max_search_results
+            ));
+        }
+
+        Ok(ToolOutput::Text(matches.join("\n")))
+    }
+
+    fn apply_patch_blocking(&self, args: &ApplyPatchArgs) -> Result<ToolOutput, ToolError> {
+        let patches = patch::parse(&args.patch)
+            .map_err(|message| ToolError::invalid_argument("$.patch", message))?;
+
+        // Every file is patched in memory first so a failing hunk leaves the workspace untouched
+        let mut changes = vec![];
+        for file_patch in &patches {
+            let source = match &file_patch.old_path {
+                Some(path) => Some(self.resolve("$.patch", path)?),
+                None => None,
+            };
+            let target = match &file_patch.new_path {
+                Some(path) => Some(self.resolve("$.patch", path)?),
+                None => None,
+            };
+
+            // Created and renamed files must not replace a file the model did not see
+            if let Some(target) = &target
+                && source.as_ref() != Some(target)
+                && target.symlink_metadata().is_ok()
+            {
+                return Err(ToolError::invalid_argument(
+                    "$.patch",
+                    format!("{} already exists", self.display(target)),
+                ));
+            }
+
+            let original = match &source {
+                Some(source) => self.read_text("$.patch", source)?,
+                None => String::new(),
+            };
+            let content = match &target {
+                Some(_) => {
+                    let content = file_patch
+                        .apply(&original)
+                        .map_err(|message| ToolError::invalid_argument("$.patch", message))?;
+                    self.check_size("$.patch", &content)?;
+                    Some(content)
+                }
+                None => None,
+            };
+
+            changes.push((source, target, content));
+        }
+
+        let mut transaction = PatchTransaction::default();
+        let mut summary = vec![];
+        for (source, target, content) in changes {
+            if let (Some(target), Some(content)) = (&target, content) {
+                transaction
+                    .stage(target, &content)
+                    .map_err(|err| io_error("$.patch", err))?;
+            }
+
+            match (source, target) {
+                (Some(source), Some(target)) if source != target => {
+                    summary.push(format!(
+                        "renamed {} to {}",
+                        self.display(&source),
+                        self.display(&target)
+                    ));
+                    transaction.remove(source);
+                }
+                (Some(source), None) => {
+                    summary.push(format!("deleted {}", self.display(&source)));
+                    transaction.remove(source);
+                }
+                (None, Some(target)) => summary.push(format!("created {}", self.display(&target))),
+                (_, Some(target)) => summary.push(format!("patched {}", self.display(&target))),
+                (None, None) => {}
+            }
+        }
+        transaction
+            .commit()
+            .map_err(|err| io_error("$.patch", err))?;
+
+        Ok(ToolOutput::Text(summary.join("\n")))
+    }
+}
+
+#[async_trait]
+impl FsInvoke for Workspace {
+    async fn read_file(&self, args: &ReadFileArgs) -> Result<ToolOutput, ToolError> {
+        let args = args.clone();
+        self.blocking(move |toolset| {
+            let path = toolset.resolve("$.path", &args.path)?;
+            Ok(ToolOutput::Text(toolset.read_text("$.path", &path)?))
+        })
+        .await
+    }
+
+    async fn write_file(&self, args: &WriteFileArgs) -> Result<ToolOutput, ToolError> {
+        let args = args.clone();
+        self.blocking(move |toolset| toolset.write_file_blocking(&args))
+            .await
+    }
+
+    async fn list_dir(&self, args: &ListDirArgs) -> Result<ToolOutput, ToolError> {
+        let args = args.clone();
+        self.blocking(move |toolset| toolset.list_dir_blocking(&args))
+            .await
+    }
+
+    async fn search(&self, args: &SearchArgs) -> Result<ToolOutput, ToolError> {
+        let args = args.clone();
+        self.blocking(move |toolset| toolset.search_blocking(&args))
+            .await
+    }
+
+    async fn apply_patch(&self, args: &ApplyPatchArgs) -> Result<ToolOutput, ToolError> {
+        let args = args.clone();
+        self.blocking(move |toolset| toolset.apply_patch_blocking(&args))
+            .await
+    }
+}
+
+/// Changes of a patch, applied together or not at all.
+///
+/// New content is staged in temporary files next to its target and files are replaced by
+/// renaming, replaced and removed files are moved aside until every change succeeded.
+#[derive(Debug, Default)]
+struct PatchTransaction {
+    /// Temporary files and the targets they replace
+    staged: Vec<(PathBuf, PathBuf)>,
+
+    /// Files removed by the patch
+    removed: Vec<PathBuf>,
+
+    /// Directories created for new files, outermost first
+    created_dirs: Vec<PathBuf>,
+}
+
+impl PatchTransaction {
+    /// Writes the new content of `target` to a temporary file.
+    fn stage(&mut self, target: &Path, content: &str) -> std::io::Result<()> {
+        if let Some(parent) = target.parent() {
+            self.create_dirs(parent)?;
+        }
+
+        let staged = sibling(target, "staged");
+        self.staged.push((staged.clone(), target.to_path_buf()));
+        std::fs::write(&staged, content).inspect_err(|_| self.rollback(&[], &[]))
+    }
+
+    fn remove(&mut self, path: PathBuf) {
+        self.removed.push(path);
+    }
+
+    fn create_dirs(&mut self, dir: &Path) -> std::io::Result<()> {
+        let missing = dir
+            .ancestors()
+            .take_while(|ancestor| !ancestor.exists())
+            .map(Path::to_path_buf)
+            .collect::<Vec<_>>();
+        self.created_dirs.extend(missing.into_iter().rev());
+
+        std::fs::create_dir_all(dir).inspect_err(|_| self.rollback(&[], &[]))
+    }
+
+    /// Moves the staged files into place, undoing every change if one fails.
+    fn commit(self) -> std::io::Result<()> {
+        let mut backups = vec![];
+        let mut placed = vec![];
+
+        let result = (|| {
+            // Existing files are moved aside so they can be restored
+            let replaced = self.staged.iter().map(|(_, target)| target);
+            for path in self.removed.iter().chain(replaced) {
+                if path.exists() && !backups.iter().any(|(original, _)| original == path) {
+                    let backup = sibling(path, "backup");
+                    std::fs::rename(path, &backup)?;
+                    backups.push((path.clone(), backup));
+                }
+            }
+
+            for (staged, target) in &self.staged {
+                std::fs::rename(staged, target)?;
+                placed.push(target.clone());
+            }
+            Ok(())
+        })();
+
+        match result {
+            Ok(()) => {
+                for (_, backup) in backups {
+                    let _ = std::fs::remove_file(backup);
+                }
+                Ok(())
+            }
+            Err(err) => {
+                self.rollback(&placed, &backups);
+                Err(err)
+            }
+        }
+    }
+
+    /// Best effort, the original error is reported rather than one of the cleanup.
+    fn rollback(&self, placed: &[PathBuf], backups: &[(PathBuf, PathBuf)]) {
+        for path in placed {
+            let _ = std::fs::remove_file(path);
+        }
+        for (original, backup) in backups.iter().rev() {
+            let _ = std::fs::rename(backup, original);
+        }
+        for (staged, _) in &self.staged {
+            let _ = std::fs::remove_file(staged);
+        }
+        for dir in self.created_dirs.iter().rev() {
+            let _ = std::fs::remove_dir(dir);
+        }
+    }
+}
+
+/// Returns a hidden path next to `path` for temporary content.
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!(".{}.meerai-{}", name, suffix))
+}
+
+/// Errors the model can act on, e.g. by choosing another path, are reported as invalid
+/// arguments, others are left to the retry logic.
+fn io_error(arg: &str, err: std::io::Error) -> ToolError {
+    match err.kind() {
+        ErrorKind::NotFound
+        | ErrorKind::AlreadyExists
+        | ErrorKind::PermissionDenied
+        | ErrorKind::ReadOnlyFilesystem
+        | ErrorKind::StorageFull
+        | ErrorKind::QuotaExceeded
+        | ErrorKind::FileTooLarge
+        | ErrorKind::IsADirectory
+        | ErrorKind::NotADirectory
+        | ErrorKind::DirectoryNotEmpty
+        | ErrorKind::InvalidFilename => ToolError::invalid_argument(arg, err.to_string()),
+        _ => ToolError::Unknown(err.into()),
+    }
+}
+
+mod patch {
+    use std::sync::LazyLock;
+
+    use regex::Regex;
+
+    static HUNK_HEADER: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r"^@@ -(\d+)(?:,(\d+))? \+(\d+)(?:,(\d+))? @@").expect("valid regex")
+    });
+
+    #[derive(Debug, PartialEq)]
+    pub struct FilePatch {
+        /// `None` for created files
+        pub old_path: Option<String>,
+
+        /// `None` for deleted files
+        pub new_path: Option<String>,
+
+        pub hunks: Vec<Hunk>,
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub struct Hunk {
+        pub old_start: usize,
+        pub old_lines: Vec<String>,
+        pub new_lines: Vec<String>,
+    }
+
+    /// Parses a unified diff as produced by `diff -u` or `git diff`.
+    pub fn parse(patch: &str) -> Result<Vec<FilePatch>, String> {
+        let mut lines = patch.lines().peekable();
+        let mut patches = vec![];
+
+        while let Some(line) = lines.next() {
+            let Some(old_path) = line.strip_prefix("--- ") else {
+                // Headers such as `diff --git` or `index` carry nothing we need
+                continue;
+            };
+            let new_path = lines
+                .next()
+                .and_then(|line| line.strip_prefix("+++ "))
+                .ok_or_else(|| format!("expected `+++` after `{}`", line))?;
+
+            let mut file_patch = FilePatch {
+                old_path: parse_path(old_path),
+                new_path: parse_path(new_path),
+                hunks: vec![],
+            };
+
+            while let Some(header) = lines.next_if(|line| line.starts_with("@@")) {
+                let captures = HUNK_HEADER
+                    .captures(header)
+                    .ok_or_else(|| format!("invalid hunk header `{}`", header))?;
+                let number = |index: usize| {
+                    captures
+                        .get(index)
+                        .map_or(Ok(1), |value| value.as_str().parse::<usize>())
+                        .map_err(|err| err.to_string())
+                };
+
+                let mut hunk = Hunk {
+                    old_start: number(1)?,
+                    old_lines: vec![],
+                    new_lines: vec![],
+                };
+                let (old_count, new_count) = (number(2)?, number(4)?);
+
+                // The counts decide where the hunk ends, as removed lines may look like headers
+                while hunk.old_lines.len() < old_count || hunk.new_lines.len() < new_count {
+                    let line = lines
+                        .next()
+                        .ok_or_else(|| format!("hunk `{}` ends early", header))?;
+                    match line.chars().next() {
+                        Some('-') => hunk.old_lines.push(line[1..].to_string()),
+                        Some('+') => hunk.new_lines.push(line[1..].to_string()),
+                        Some(' ') => {
+                            hunk.old_lines.push(line[1..].to_string());
+                            hunk.new_lines.push(line[1..].to_string());
+                        }
+                        // Some editors strip the space of empty context lines
+                        None => {
+                            hunk.old_lines.push(String::new());
+                            hunk.new_lines.push(String::new());
+                        }
+                        Some('\\') => {}
+                        _ => return Err(format!("invalid line `{}` in hunk `{}`", line, header)),
+                    }
+                }
+                lines.next_if(|line| line.starts_with('\\'));
+
+                file_patch.hunks.push(hunk);
+            }
+
+            patches.push(file_patch);
+        }
+
+        if patches.is_empty() {
+            return Err("no file changes found, expected a unified diff".to_string());
+        }
+        Ok(patches)
+    }
+
+    fn parse_path(path: &str) -> Option<String> {
+        // Timestamps of `diff -u` follow a tab
+        let path = path.split('\t').next().unwrap_or(path).trim();
+        if path == "/dev/null" {
+            return None;
+        }
+
+        let path = path
+            .strip_prefix("a/")
+            .or_else(|| path.strip_prefix("b/"))
+            .unwrap_or(path);
+        Some(path.to_string())
+    }
+
+    impl FilePatch {
+        /// Applies the hunks to `content`, allowing them to have moved since the diff was made.
+        pub fn apply(&self, content: &str) -> Result<String, String> {
+            let mut lines = content.lines().map(ToString::to_string).collect::<Vec<_>>();
+            let ends_with_newline = content.is_empty() || content.ends_with('\n');
+            let mut offset = 0isize;
+
+            for (index, hunk) in self.hunks.iter().enumerate() {
+                let expected = (hunk.old_start.saturating_sub(1) as isize + offset).max(0) as usize;
+                let matches_at = |position: usize| {
+                    lines
+                        .get(position..position + hunk.old_lines.len())
+                        .is_some_and(|window| window == hunk.old_lines.as_slice())
+                };
+
+                let position = (0..=lines.len())
+                    .filter(|position| matches_at(*position))
+                    .min_by_key(|position| position.abs_diff(expected))
+                    .ok_or_else(|| {
+                        format!(
+                            "hunk {} of {} does not match the file",
+                            index + 1,
+                            self.old_path.as_deref().unwrap_or("a new file")
+                        )
+                    })?;
+
+                lines.splice(
+                    position..position + hunk.old_lines.len(),
+                    hunk.new_lines.iter().cloned(),
+                );
+                offset += hunk.new_lines.len() as isize - hunk.old_lines.len() as isize;
+            }
+
+            let mut content = lines.join("\n");
+            if ends_with_newline && !content.is_empty() {
+                content.push('\n');
+            }
+            Ok(content)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace(name: &str) -> (PathBuf, Workspace) {
+        let root = std::env::temp_dir().join(format!("meerai_fs_{}", name));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("docs")).unwrap();
+        std::fs::write(root.join("docs/guide.md"), "# Guide\n\nHello world\n").unwrap();
+
+        let toolset = Workspace {
+            config: FilesystemConfig {
+                max_file_bytes: 1024,
+                ..FilesystemConfig::new(&root)
+            },
+        };
+        (root, toolset)
+    }
+
+    #[tokio::test]
+    async fn test_read_write_list() {
+        let (root, toolset) = workspace("read_write");
+
+        toolset
+            .write_file(&WriteFileArgs {
+                path: "notes/todo.txt".to_string(),
+                content: "ship it".to_string(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(root.join("notes/todo.txt")).unwrap(),
+            "ship it"
+        );
+
+        let output = toolset
+            .read_file(&ReadFileArgs {
+                path: "./docs/guide.md".to_string(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            output,
+            ToolOutput::Text("# Guide\n\nHello world\n".to_string())
+        );
+
+        let output = toolset.list_dir(&ListDirArgs { path: None }).await.unwrap();
+        assert_eq!(
+            output,
+            ToolOutput::Json(serde_json::json!([
+                { "name": "docs", "type": "dir", "size": output_size(&root, "docs") },
+                { "name": "notes", "type": "dir", "size": output_size(&root, "notes") },
+            ]))
+        );
+    }
+
+    fn output_size(root: &Path, name: &str) -> u64 {
+        root.join(name).symlink_metadata().unwrap().len()
+    }
+
+    #[tokio::test]
+    async fn test_confined_to_root() {
+        let (root, toolset) = workspace("confined");
+        let outside = std::env::temp_dir().join("meerai_fs_outside.txt");
+        std::fs::write(&outside, "secret").unwrap();
+        std::os::unix::fs::symlink(&outside, root.join("link")).unwrap();
+        std::os::unix::fs::symlink(root.join("missing/../../x"), root.join("dangling")).unwrap();
+
+        for path in [
+            "../meerai_fs_outside.txt",
+            "/etc/passwd",
+            "link",
+            "docs/../link",
+        ] {
+            let err = toolset
+                .read_file(&ReadFileArgs {
+                    path: path.to_string(),
+                })
+                .await
+                .unwrap_err();
+            assert!(matches!(err, ToolError::InvalidArguments(_)), "{}", path);
+        }
+
+        let err = toolset
+            .write_file(&WriteFileArgs {
+                path: "dangling".to_string(),
+                content: "x".to_string(),
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ToolError::InvalidArguments(_)));
+    }
+
+    #[tokio::test]
+    async fn test_limits_and_read_only() {
+        let (root, toolset) = workspace("limits");
+
+        let err = toolset
+            .write_file(&WriteFileArgs {
+                path: "big.txt".to_string(),
+                content: "x".repeat(2048),
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ToolError::InvalidArguments(_)));
+
+        let read_only = FilesystemToolset::new(FilesystemConfig {
+            read_only: true,
+            ..toolset.config.clone()
+        });
+        assert_eq!(
+            read_only
+                .definition()
+                .iter()
+                .map(|definition| definition.name.as_str())
+                .collect::<Vec<_>>(),
+            READ_FUNCTIONS
+        );
+        let err = read_only
+            .invoke(
+                "fs-write_file",
+                r#"{"path": "docs/guide.md", "content": "gone"}"#,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ToolError::InvalidFunctionName(_)));
+        assert!(
+            std::fs::read_to_string(root.join("docs/guide.md"))
+                .unwrap()
+                .starts_with("# Guide")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search() {
+        let (_, toolset) = workspace("search");
+
+        let output = toolset
+            .search(&SearchArgs {
+                pattern: "(?i)hello".to_string(),
+                path: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            output,
+            ToolOutput::Text("docs/guide.md:3: Hello world".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_apply_patch() {
+        let (root, toolset) = workspace("patch");
+
+        let patch = "\
+--- a/docs/guide.md
++++ b/docs/guide.md
+@@ -2,2 +2,3 @@
+
+-Hello world
++Hello meerai
++Bye
+--- /dev/null
++++ b/docs/new.md
+@@ -0,0 +1 @@
++new
+";
+        let output = toolset
+            .apply_patch(&ApplyPatchArgs {
+                patch: patch.to_string(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            output,
+            ToolOutput::Text("patched docs/guide.md\ncreated docs/new.md".to_string())
+        );
+        assert_eq!(
+            std::fs::read_to_string(root.join("docs/guide.md")).unwrap(),
+            "# Guide\n\nHello meerai\nBye\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(root.join("docs/new.md")).unwrap(),
+            "new\n"
+        );
+
+        // A hunk that does not match leaves every file untouched
+        let patch = "\
+--- /dev/null
++++ b/docs/other.md
+@@ -0,0 +1 @@
++other
+--- a/docs/guide.md
++++ b/docs/guide.md
+@@ -1 +1 @@
+-# Missing
++# Guide 2
+";
+        let err = toolset
+            .apply_patch(&ApplyPatchArgs {
+                patch: patch.to_string(),
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "arguments for tool do not match its schema: $.patch: hunk 1 of docs/guide.md does not match the file"
+        );
+        assert!(!root.join("docs/other.md").exists());
+    }
+
+    #[tokio::test]
+    async fn test_apply_patch_all_or_nothing() {
+        let (root, toolset) = workspace("patch_atomic");
+
+        // Creating a file does not overwrite an existing one
+        let patch = "\
+--- /dev/null
++++ b/docs/guide.md
+@@ -0,0 +1 @@
++replaced
+";
+        let err = toolset
+            .apply_patch(&ApplyPatchArgs {
+                patch: patch.to_string(),
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "arguments for tool do not match its schema: $.patch: docs/guide.md already exists"
+        );
+
+        // The second file cannot be written below a file, the staged first one is discarded
+        let patch = "\
+--- a/docs/guide.md
++++ b/docs/guide.md
+@@ -1 +1 @@
+-# Guide
++# Guide 2
+--- /dev/null
++++ b/docs/guide.md/nested.md
+@@ -0,0 +1 @@
++nested
+";
+        let err = toolset
+            .apply_patch(&ApplyPatchArgs {
+                patch: patch.to_string(),
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ToolError::InvalidArguments(_)));
+        assert_eq!(
+            std::fs::read_to_string(root.join("docs/guide.md")).unwrap(),
+            "# Guide\n\nHello world\n"
+        );
+        let mut entries = std::fs::read_dir(root.join("docs"))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect::<Vec<_>>();
+        entries.sort();
+        assert_eq!(entries, vec!["guide.md"]);
+    }
+}
@@ -0,0 +1,260 @@
+use std::sync::Arc;
+
+use meerai_core::{JsonSchema, ToolError, ToolOutput, async_trait};
+use meerai_macros::Toolset;
+
+use crate::memory::{MemoryEntry, MemoryError, MemoryStore};
+
+/// Configuration for the MemoryToolset.
+#[derive(Debug, Clone)]
+pub struct MemoryConfig {
+    /// Maximum length of a key in bytes
+    pub max_key_bytes: usize,
+
+    /// Maximum length of a remembered value in bytes
+    pub max_value_bytes: usize,
+
+    /// Maximum number of entries returned by a single list call
+    pub max_list_results: usize,
+}
+
+impl Default for MemoryConfig {
+    fn default() -> Self {
+        Self {
+            max_key_bytes: 256,
+            max_value_bytes: 4 * 1024,
+            max_list_results: 100,
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize, JsonSchema)]
+pub struct RememberArgs {
+    /// Key to remember the value under, e.g. `replied/alice.bsky.social`
+    pub key: String,
+
+    /// Value to remember, replaces the value previously remembered under the key
+    pub value: String,
+}
+
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize, JsonSchema)]
+pub struct RecallArgs {
+    /// Key the value was remembered under
+    pub key: String,
+}
+
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize, JsonSchema)]
+pub struct ListMemoriesArgs {
+    /// Only list keys starting with this prefix, e.g. `replied/`
+    #[serde(default)]
+    pub prefix: Option<String>,
+}
+
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize, JsonSchema)]
+pub struct ForgetArgs {
+    /// Key of the value to forget
+    pub key: String,
+}
+
+/// Key-value memory that outlives the conversation of a single prompt.
+///
+/// Every toolset works in its own namespace of the store, so agents sharing a store do not see
+/// each other's memories.
+#[derive(Toolset)]
+#[toolset(
+    name = "memory",
+    tool(
+        name = "Remember",
+        description = "Remember a value under a key so it is available in later tasks, e.g. that you already replied to a user. Use `/` in keys to group related memories.",
+        params = RememberArgs,
+    ),
+    tool(
+        name = "Recall",
+        description = "Recall the value remembered under a key.",
+        params = RecallArgs,
+    ),
+    tool(
+        name = "List",
+        description = "List remembered keys and values, optionally only those whose key starts with a prefix.",
+        params = ListMemoriesArgs,
+    ),
+    tool(
+        name = "Forget",
+        description = "Forget the value remembered under a key.",
+        params = ForgetArgs,
+    )
+)]
+pub struct MemoryToolset {
+    namespace: String,
+    store: Arc<dyn MemoryStore>,
+    config: MemoryConfig,
+}
+
+impl MemoryToolset {
+    pub fn new(namespace: impl Into<String>, store: Arc<dyn MemoryStore>) -> Self {
+        Self::new_with_config(namespace, store, MemoryConfig::default())
+    }
+
+    pub fn new_with_config(
+        namespace: impl Into<String>,
+        store: Arc<dyn MemoryStore>,
+        config: MemoryConfig,
+    ) -> Self {
+        Self {
+            namespace: namespace.into(),
+            store,
+            config,
+        }
+    }
+
+    fn check_key(&self, key: &str) -> Result<(), ToolError> {
+        if key.trim().is_empty() {
+            return Err(ToolError::invalid_argument("$.key", "must not be empty"));
+        }
+        if key.len() > self.config.max_key_bytes {
+            return Err(ToolError::invalid_argument(
+                "$.key",
+                format!("must be at most {} bytes", self.config.max_key_bytes),
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn store_error(err: MemoryError) -> ToolError {
+    ToolError::Unknown(err.into())
+}
+
+#[async_trait]
+impl MemoryInvoke for MemoryToolset {
+    async fn remember(&self, args: &RememberArgs) -> Result<ToolOutput, ToolError> {
+        self.check_key(&args.key)?;
+        if args.value.len() > self.config.max_value_bytes {
+            return Err(ToolError::invalid_argument(
+                "$.value",
+                format!("must be at most {} bytes", self.config.max_value_bytes),
+            ));
+        }
+
+        self.store
+            .set(&self.namespace, MemoryEntry::new(&args.key, &args.value))
+            .await
+            .map_err(store_error)?;
+        Ok(ToolOutput::Text(format!("Remembered `{}`", args.key)))
+    }
+
+    async fn recall(&self, args: &RecallArgs) -> Result<ToolOutput, ToolError> {
+        self.check_key(&args.key)?;
+        let entry = self
+            .store
+            .get(&self.namespace, &args.key)
+            .await
+            .map_err(store_error)?;
+
+        Ok(match entry {
+            Some(entry) => ToolOutput::Json(serde_json::to_value(entry).map_err(ToolError::from)?),
+            None => ToolOutput::Text(format!("Nothing is remembered under `{}`", args.key)),
+        })
+    }
+
+    async fn list(&self, args: &ListMemoriesArgs) -> Result<ToolOutput, ToolError> {
+        let mut entries = self
+            .store
+            .list(&self.namespace, args.prefix.as_deref().unwrap_or_default())
+            .await
+            .map_err(store_error)?;
+
+        let truncated = entries.len() > self.config.max_list_results;
+        entries.truncate(self.config.max_list_results);
+        Ok(ToolOutput::Json(serde_json::json!({
+            "entries": entries,
+            "truncated": truncated,
+        })))
+    }
+
+    async fn forget(&self, args: &ForgetArgs) -> Result<ToolOutput, ToolError> {
+        self.check_key(&args.key)?;
+        let removed = self
+            .store
+            .remove(&self.namespace, &args.key)
+            .await
+            .map_err(store_error)?;
+
+        Ok(ToolOutput::Text(if removed {
+            format!("Forgot `{}`", args.key)
+        } else {
+            format!("Nothing is remembered under `{}`", args.key)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use meerai_core::Toolset;
+
+    use super::*;
+    use crate::memory::InMemoryStore;
+
+    #[tokio::test]
+    async fn test_memory_toolset() {
+        let store: Arc<dyn MemoryStore> = Arc::new(InMemoryStore::new());
+        let bluesky = MemoryToolset::new("bluesky", store.clone());
+        let x = MemoryToolset::new("x", store);
+
+        let output = bluesky
+            .invoke(
+                "memory-remember",
+                r#"{"key": "replied/alice", "value": "post 1"}"#,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            output,
+            ToolOutput::Text("Remembered `replied/alice`".to_string())
+        );
+
+        let ToolOutput::Json(entry) = bluesky
+            .invoke("memory-recall", r#"{"key": "replied/alice"}"#)
+            .await
+            .unwrap()
+        else {
+            panic!("expected JSON output");
+        };
+        assert_eq!(entry["value"], "post 1");
+
+        // Namespaces do not share memories
+        let output = x
+            .invoke("memory-recall", r#"{"key": "replied/alice"}"#)
+            .await
+            .unwrap();
+        assert_eq!(
+            output,
+            ToolOutput::Text("Nothing is remembered under `replied/alice`".to_string())
+        );
+
+        let ToolOutput::Json(list) = bluesky
+            .invoke("memory-list", r#"{"prefix": "replied/"}"#)
+            .await
+            .unwrap()
+        else {
+            panic!("expected JSON output");
+        };
+        assert_eq!(list["entries"].as_array().unwrap().len(), 1);
+        assert_eq!(list["truncated"], false);
+
+        bluesky
+            .invoke("memory-forget", r#"{"key": "replied/alice"}"#)
+            .await
+            .unwrap();
+        let ToolOutput::Json(list) = bluesky.invoke("memory-list", "{}").await.unwrap() else {
+            panic!("expected JSON output");
+        };
+        assert_eq!(list["entries"], serde_json::json!([]));
+
+        let err = bluesky
+            .invoke("memory-remember", r#"{"key": " ", "value": "x"}"#)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ToolError::InvalidArguments(_)));
+    }
+}
@@ -1 +1,16 @@
+mod filesystem;
+mod memory;
+mod openapi;
+mod shell;
+mod web;
 
+pub use filesystem::{
+    ApplyPatchArgs, FilesystemConfig, FilesystemToolset, ListDirArgs, ReadFileArgs, SearchArgs,
+    WriteFileArgs,
+};
+pub use memory::{
+    ForgetArgs, ListMemoriesArgs, MemoryConfig, MemoryToolset, RecallArgs, RememberArgs,
+};
+pub use openapi::{OpenApiAuth, OpenApiConfig, OpenApiError, OpenApiToolset};
+pub use shell::{RunCommandArgs, ShellConfig, ShellToolset};
+pub use web::{FetchUrlArgs, WebConfig, WebToolset};
@@ -0,0 +1,785 @@
+use std::time::Duration;
+
+use meerai_core::{ToolDefinition, ToolError, ToolMetadata, ToolOutput, Toolset, async_trait};
+use reqwest::{Client, Method, StatusCode, header};
+use serde_json::{Map, Value, json};
+use thiserror::Error;
+use url::Url;
+
+/// Nesting depth after which `$ref`s are no longer inlined, which also stops recursive schemas
+const MAX_REF_DEPTH: usize = 8;
+
+#[derive(Debug, Error)]
+pub enum OpenApiError {
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("YAML parsing error: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+
+    #[error("invalid OpenAPI document: {0}")]
+    Invalid(String),
+}
+
+/// Credentials added to every request.
+#[derive(Debug, Clone, Default)]
+pub enum OpenApiAuth {
+    #[default]
+    None,
+
+    Bearer(String),
+
+    Basic {
+        username: String,
+        password: Option<String>,
+    },
+
+    /// An API key sent as a header, e.g. `X-Api-Key`
+    Header {
+        name: String,
+        value: String,
+    },
+
+    /// An API key sent as a query parameter
+    Query {
+        name: String,
+        value: String,
+    },
+}
+
+/// Configuration for the OpenApiToolset.
+#[derive(Debug, Clone)]
+pub struct OpenApiConfig {
+    /// Used instead of the first server of the document
+    pub base_url: Option<String>,
+
+    pub auth: OpenApiAuth,
+
+    /// Maximum duration of a request
+    pub timeout: Duration,
+
+    /// Maximum number of bytes of a response returned to the model
+    pub max_response_bytes: usize,
+}
+
+impl Default for OpenApiConfig {
+    fn default() -> Self {
+        Self {
+            base_url: None,
+            auth: OpenApiAuth::None,
+            timeout: Duration::from_secs(30),
+            max_response_bytes: 256 * 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Location {
+    Path,
+    Query,
+    Header,
+}
+
+#[derive(Debug, Clone)]
+struct Parameter {
+    name: String,
+    location: Location,
+}
+
+/// What is needed to turn the arguments of a tool into a request.
+#[derive(Debug, Clone)]
+struct Operation {
+    method: Method,
+    path: String,
+    parameters: Vec<Parameter>,
+    has_body: bool,
+}
+
+/// Turns every operation of an OpenAPI 3 document into a tool.
+///
+/// Tools are named after the `operationId`, or the method and path when there is none. Their
+/// arguments are the parameters of the operation plus a `body` for a JSON request body.
+pub struct OpenApiToolset {
+    name: String,
+    client: Client,
+    base_url: Url,
+    config: OpenApiConfig,
+    definitions: Vec<ToolDefinition>,
+
+    /// Operations in the order of the definitions
+    operations: Vec<Operation>,
+}
+
+impl OpenApiToolset {
+    /// Builds the toolset from a JSON or YAML document.
+    pub fn from_spec(
+        name: impl Into<String>,
+        spec: &str,
+        config: OpenApiConfig,
+    ) -> Result<Self, OpenApiError> {
+        let document: Value = serde_yaml::from_str(spec)?;
+        let name = name.into();
+
+        let base_url = config
+            .base_url
+            .clone()
+            .or_else(|| document["servers"][0]["url"].as_str().map(String::from))
+            .ok_or_else(|| OpenApiError::Invalid("no server url is given".to_string()))?;
+        let base_url = Url::parse(&base_url)
+            .map_err(|err| OpenApiError::Invalid(format!("server url {}: {}", base_url, err)))?;
+
+        let mut definitions = vec![];
+        let mut operations = vec![];
+        let paths = document["paths"]
+            .as_object()
+            .ok_or_else(|| OpenApiError::Invalid("`paths` is missing".to_string()))?;
+        for (path, item) in paths {
+            let item = resolve(&document, item, 0);
+
+            for (method, operation) in item.as_object().into_iter().flatten() {
+                let Ok(method) = method.to_uppercase().parse::<Method>() else {
+                    continue;
+                };
+                if !matches!(
+                    method,
+                    Method::GET
+                        | Method::POST
+                        | Method::PUT
+                        | Method::PATCH
+                        | Method::DELETE
+                        | Method::HEAD
+                ) {
+                    continue;
+                }
+
+                // Parameters of the path item apply to each of its operations
+                let parameters = item["parameters"]
+                    .as_array()
+                    .into_iter()
+                    .chain(operation["parameters"].as_array())
+                    .flatten()
+                    .map(|parameter| resolve(&document, parameter, 0));
+                let (definition, operation) =
+                    tool(&document, &name, &method, path, operation, parameters)?;
+                definitions.push(definition);
+                operations.push(operation);
+            }
+        }
+
+        let client = Client::builder().timeout(config.timeout).build()?;
+        Ok(Self {
+            name,
+            client,
+            base_url,
+            config,
+            definitions,
+            operations,
+        })
+    }
+
+    /// Downloads the document and builds the toolset from it.
+    pub async fn from_url(
+        name: impl Into<String>,
+        url: &str,
+        config: OpenApiConfig,
+    ) -> Result<Self, OpenApiError> {
+        let spec = reqwest::get(url).await?.error_for_status()?.text().await?;
+        Self::from_spec(name, &spec, config)
+    }
+
+    fn request(
+        &self,
+        operation: &Operation,
+        args: &Map<String, Value>,
+    ) -> Result<reqwest::RequestBuilder, ToolError> {
+        let mut url = self.base_url.clone();
+        {
+            let mut segments = url.path_segments_mut().map_err(|_| {
+                ToolError::Unknown(anyhow::anyhow!("{} cannot be a base", self.base_url))
+            })?;
+            segments.pop_if_empty();
+            for segment in operation.path.split('/').filter(|s| !s.is_empty()) {
+                let mut segment = segment.to_string();
+                for parameter in &operation.parameters {
+                    if parameter.location == Location::Path
+                        && let Some(value) = args.get(&parameter.name)
+                    {
+                        segment = segment
+                            .replace(&format!("{{{}}}", parameter.name), &parameter_value(value));
+                    }
+                }
+                segments.push(&segment);
+            }
+        }
+
+        {
+            let mut query = url.query_pairs_mut();
+            for parameter in &operation.parameters {
+                match (parameter.location, args.get(&parameter.name)) {
+                    (Location::Query, Some(Value::Array(values))) => {
+                        for value in values {
+                            query.append_pair(&parameter.name, &parameter_value(value));
+                        }
+                    }
+                    (Location::Query, Some(value)) if !value.is_null() => {
+                        query.append_pair(&parameter.name, &parameter_value(value));
+                    }
+                    _ => {}
+                }
+            }
+            if let OpenApiAuth::Query { name, value } = &self.config.auth {
+                query.append_pair(name, value);
+            }
+        }
+        // An empty query would leave a trailing `?`
+        if url.query() == Some("") {
+            url.set_query(None);
+        }
+
+        let mut request = self.client.request(operation.method.clone(), url);
+        for parameter in &operation.parameters {
+            if parameter.location == Location::Header
+                && let Some(value) = args.get(&parameter.name)
+            {
+                request = request.header(&parameter.name, parameter_value(value));
+            }
+        }
+        if operation.has_body
+            && let Some(body) = args.get("body")
+        {
+            request = request.json(body);
+        }
+
+        request = match &self.config.auth {
+            OpenApiAuth::Bearer(token) => request.bearer_auth(token),
+            OpenApiAuth::Basic { username, password } => {
+                request.basic_auth(username, password.as_ref())
+            }
+            OpenApiAuth::Header { name, value } => request.header(name, value),
+            OpenApiAuth::None | OpenApiAuth::Query { .. } => request,
+        };
+
+        Ok(request)
+    }
+}
+
+#[async_trait]
+impl Toolset for OpenApiToolset {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn definition(&self) -> Vec<ToolDefinition> {
+        self.definitions.clone()
+    }
+
+    fn contain(&self, fn_name: &str) -> bool {
+        self.definitions
+            .iter()
+            .any(|definition| definition.name == fn_name)
+    }
+
+    async fn invoke(&self, fn_name: &str, args: &str) -> Result<ToolOutput, ToolError> {
+        let index = self
+            .definitions
+            .iter()
+            .position(|definition| definition.name == fn_name)
+            .ok_or_else(|| ToolError::InvalidFunctionName(fn_name.to_string()))?;
+        let args: Map<String, Value> = if args.trim().is_empty() {
+            Map::new()
+        } else {
+            serde_json::from_str(args)?
+        };
+
+        let request_error = |err: reqwest::Error| {
+            if err.is_timeout() {
+                ToolError::Timeout(self.config.timeout)
+            } else {
+                ToolError::Unknown(err.into())
+            }
+        };
+        let mut response = self
+            .request(&self.operations[index], &args)?
+            .send()
+            .await
+            .map_err(request_error)?;
+
+        let status = response.status();
+        let is_json = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.contains("json"));
+
+        // Stops reading at the limit instead of buffering whatever the server sends
+        let mut body = Vec::new();
+        let mut truncated = false;
+        while let Some(chunk) = response.chunk().await.map_err(request_error)? {
+            let remaining = self.config.max_response_bytes - body.len();
+            if chunk.len() > remaining {
+                body.extend_from_slice(&chunk[..remaining]);
+                truncated = true;
+                break;
+            }
+            body.extend_from_slice(&chunk);
+        }
+        let mut body = String::from_utf8_lossy(&body).into_owned();
+
+        if status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+            return Err(ToolError::Transient(anyhow::anyhow!(
+                "{} returned {}: {}",
+                fn_name,
+                status,
+                body
+            )));
+        }
+
+        if truncated {
+            body.push_str(&format!(
+                "\n[truncated after {} bytes]",
+                self.config.max_response_bytes
+            ));
+        } else if is_json
+            && status.is_success()
+            && let Ok(value) = serde_json::from_str(&body)
+        {
+            return Ok(ToolOutput::Json(value));
+        }
+
+        // Client errors usually explain what is wrong with the arguments
+        if !status.is_success() {
+            return Ok(ToolOutput::Fail(format!("{}: {}", status, body)));
+        }
+        Ok(ToolOutput::Text(body))
+    }
+}
+
+/// Builds the definition of one operation.
+fn tool<'a>(
+    document: &Value,
+    toolset_name: &str,
+    method: &Method,
+    path: &str,
+    operation: &Value,
+    parameters: impl Iterator<Item = &'a Value>,
+) -> Result<(ToolDefinition, Operation), OpenApiError> {
+    let mut properties = Map::new();
+    let mut required = vec![];
+    let mut operation_parameters = vec![];
+
+    for parameter in parameters {
+        let name = parameter["name"]
+            .as_str()
+            .ok_or_else(|| OpenApiError::Invalid(format!("parameter of {} has no name", path)))?;
+        let location = match parameter["in"].as_str() {
+            Some("path") => Location::Path,
+            Some("query") => Location::Query,
+            Some("header") => Location::Header,
+            // Cookies are left to the auth configuration
+            _ => continue,
+        };
+
+        let mut schema = schema(document, &parameter["schema"], 0);
+        if let Some(description) = parameter["description"].as_str() {
+            schema["description"] = description.into();
+        }
+        properties.insert(name.to_string(), schema);
+        if location == Location::Path || parameter["required"].as_bool() == Some(true) {
+            required.push(Value::from(name));
+        }
+
+        // A parameter repeated on the operation overrides the one of the path item
+        operation_parameters.retain(|existing: &Parameter| existing.name != name);
+        operation_parameters.push(Parameter {
+            name: name.to_string(),
+            location,
+        });
+    }
+
+    let request_body = resolve(document, &operation["requestBody"], 0);
+    let body_schema = request_body["content"]
+        .as_object()
+        .and_then(|content| {
+            content
+                .iter()
+                .find(|(media_type, _)| media_type.contains("json"))
+        })
+        .map(|(_, media_type)| schema(document, &media_type["schema"], 0));
+    if let Some(mut body_schema) = body_schema.clone() {
+        if let Some(description) = request_body["description"].as_str() {
+            body_schema["description"] = description.into();
+        }
+        properties.insert("body".to_string(), body_schema);
+        if request_body["required"].as_bool() == Some(true) {
+            required.push("body".into());
+        }
+    }
+
+    let function_name = operation["operationId"]
+        .as_str()
+        .map(snake_case)
+        .unwrap_or_else(|| snake_case(&format!("{} {}", method, path)));
+    let description = [&operation["summary"], &operation["description"]]
+        .into_iter()
+        .filter_map(Value::as_str)
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    // Safe methods only read, PUT and DELETE can be repeated with the same result
+    let metadata = match *method {
+        Method::GET | Method::HEAD => ToolMetadata::default(),
+        Method::PUT | Method::DELETE => ToolMetadata {
+            side_effecting: true,
+            idempotent: Some(true),
+            ..Default::default()
+        },
+        _ => ToolMetadata {
+            side_effecting: true,
+            ..Default::default()
+        },
+    };
+
+    let definition = ToolDefinition {
+        r#type: "function".to_string(),
+        name: format!("{}-{}", toolset_name, function_name),
+        description,
+        parameters: json!({
+            "type": "object",
+            "properties": properties,
+            "required": required,
+        }),
+        metadata,
+    };
+    let operation = Operation {
+        method: method.clone(),
+        path: path.to_string(),
+        parameters: operation_parameters,
+        has_body: body_schema.is_some(),
+    };
+
+    Ok((definition, operation))
+}
+
+/// Follows a `$ref` to a component of the same document.
+fn resolve<'a>(document: &'a Value, value: &'a Value, depth: usize) -> &'a Value {
+    match value["$ref"].as_str() {
+        Some(reference) if depth < MAX_REF_DEPTH => reference
+            .strip_prefix('#')
+            .and_then(|pointer| document.pointer(pointer))
+            .map_or(&Value::Null, |target| resolve(document, target, depth + 1)),
+        _ => value,
+    }
+}
+
+/// Inlines the references of a schema and converts OpenAPI 3.0 `nullable` to a JSON schema type.
+///
+/// Only keywords holding subschemas are walked, so properties named like the removed OpenAPI
+/// keywords and literal values such as `enum` or `default` are kept as they are.
+fn schema(document: &Value, value: &Value, depth: usize) -> Value {
+    if depth >= MAX_REF_DEPTH {
+        return json!({});
+    }
+
+    let is_ref = value.get("$ref").is_some();
+    let value = resolve(document, value, 0);
+    let depth = if is_ref { depth + 1 } else { depth };
+
+    match value {
+        Value::Object(object) => {
+            let mut schema = object
+                .iter()
+                .filter(|(key, _)| !matches!(key.as_str(), "nullable" | "example" | "xml"))
+                .map(|(key, value)| {
+                    let value = match key.as_str() {
+                        "properties" | "patternProperties" | "$defs" | "definitions" => match value
+                        {
+                            Value::Object(schemas) => Value::Object(
+                                schemas
+                                    .iter()
+                                    .map(|(name, value)| {
+                                        (name.clone(), schema(document, value, depth))
+                                    })
+                                    .collect(),
+                            ),
+                            value => value.clone(),
+                        },
+                        "items"
+                        | "additionalItems"
+                        | "prefixItems"
+                        | "additionalProperties"
+                        | "propertyNames"
+                        | "contains"
+                        | "not"
+                        | "if"
+                        | "then"
+                        | "else"
+                        | "allOf"
+                        | "anyOf"
+                        | "oneOf" => schema(document, value, depth),
+                        _ => value.clone(),
+                    };
+                    (key.clone(), value)
+                })
+                .collect::<Map<_, _>>();
+
+            if object.get("nullable") == Some(&Value::Bool(true))
+                && let Some(Value::String(kind)) = schema.get("type")
+            {
+                schema["type"] = json!([kind, "null"]);
+            }
+            Value::Object(schema)
+        }
+        // Lists of subschemas, e.g. of `allOf`
+        Value::Array(values) => values
+            .iter()
+            .map(|value| schema(document, value, depth))
+            .collect(),
+        Value::Null => json!({}),
+        value => value.clone(),
+    }
+}
+
+fn parameter_value(value: &Value) -> String {
+    match value {
+        Value::String(value) => value.clone(),
+        Value::Array(values) => values
+            .iter()
+            .map(parameter_value)
+            .collect::<Vec<_>>()
+            .join(","),
+        value => value.to_string(),
+    }
+}
+
+/// Converts `listPets` or `GET /pets/{id}` to `list_pets` or `get_pets_id`.
+fn snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    let mut previous: Option<char> = None;
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            if c.is_ascii_uppercase()
+                && previous.is_some_and(|p| p.is_ascii_lowercase() || p.is_ascii_digit())
+            {
+                snake.push('_');
+            }
+            snake.push(c.to_ascii_lowercase());
+        } else if !snake.is_empty() && !snake.ends_with('_') {
+            snake.push('_');
+        }
+        previous = Some(c);
+    }
+
+    snake.trim_end_matches('_').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use meerai_core::test_utils::{StubResponse, serve_with};
+
+    use super::*;
+
+    const SPEC: &str = r##"
+openapi: 3.0.3
+info:
+  title: Pets
+  version: "1"
+servers:
+  - url: https://pets.example.com/v1
+paths:
+  /pets:
+    get:
+      operationId: listPets
+      summary: List pets
+      parameters:
+        - name: tag
+          in: query
+          schema: { type: array, items: { type: string } }
+        - name: limit
+          in: query
+          schema: { type: integer, nullable: true }
+      responses: {}
+    post:
+      summary: Create a pet
+      requestBody:
+        required: true
+        content:
+          application/json:
+            schema: { $ref: "#/components/schemas/Pet" }
+      responses: {}
+  /pets/{petId}:
+    parameters:
+      - $ref: "#/components/parameters/PetId"
+    delete:
+      operationId: deletePet
+      responses: {}
+  /broken:
+    post:
+      operationId: broken
+      responses: {}
+components:
+  parameters:
+    PetId:
+      name: petId
+      in: path
+      description: Id of the pet
+      schema: { type: string }
+  schemas:
+    Pet:
+      type: object
+      required: [name]
+      properties:
+        name: { type: string }
+        parent: { $ref: "#/components/schemas/Pet" }
+        nullable: { type: boolean, default: false }
+        example: { type: string, example: Rex, enum: [Rex, xml] }
+"##;
+
+    /// Answers every request with a JSON description of it.
+    fn echo_server() -> String {
+        let url = serve_with(|request| {
+            let (head, body) = request.split_once("\r\n\r\n").unwrap();
+            let mut lines = head.lines();
+            let request_line = lines.next().unwrap();
+            let authorization = lines
+                .find_map(|line| line.strip_prefix("authorization: "))
+                .unwrap_or_default();
+
+            if request_line.contains("/broken") {
+                return StubResponse::json(400, "\"name is missing\"");
+            }
+            StubResponse::json(
+                200,
+                json!({
+                    "request": request_line,
+                    "authorization": authorization,
+                    "body": body,
+                })
+                .to_string(),
+            )
+        });
+
+        format!("{}/api", url)
+    }
+
+    #[test]
+    fn test_definitions() {
+        let toolset = OpenApiToolset::from_spec("pets", SPEC, OpenApiConfig::default()).unwrap();
+        let definitions = toolset.definition();
+
+        assert_eq!(
+            definitions
+                .iter()
+                .map(|definition| definition.name.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "pets-broken",
+                "pets-list_pets",
+                "pets-post_pets",
+                "pets-delete_pet"
+            ]
+        );
+        assert_eq!(toolset.base_url.as_str(), "https://pets.example.com/v1");
+
+        assert_eq!(
+            definitions[1].parameters["properties"]["limit"],
+            json!({ "type": ["integer", "null"] })
+        );
+        assert!(!definitions[1].metadata.side_effecting);
+
+        let body = &definitions[2].parameters["properties"]["body"];
+        assert_eq!(body["required"], json!(["name"]));
+        assert_eq!(body["properties"]["parent"]["type"], "object");
+        assert_eq!(
+            body["properties"]["nullable"],
+            json!({ "type": "boolean", "default": false })
+        );
+        assert_eq!(
+            body["properties"]["example"],
+            json!({ "type": "string", "enum": ["Rex", "xml"] })
+        );
+        assert_eq!(definitions[2].parameters["required"], json!(["body"]));
+        assert!(!definitions[2].metadata.is_idempotent());
+
+        assert_eq!(
+            definitions[3].parameters["properties"]["petId"],
+            json!({ "type": "string", "description": "Id of the pet" })
+        );
+        assert_eq!(definitions[3].parameters["required"], json!(["petId"]));
+        assert!(definitions[3].metadata.side_effecting);
+        assert!(definitions[3].metadata.is_idempotent());
+    }
+
+    #[tokio::test]
+    async fn test_invoke() {
+        let base_url = echo_server();
+        let toolset = OpenApiToolset::from_spec(
+            "pets",
+            SPEC,
+            OpenApiConfig {
+                base_url: Some(base_url.clone()),
+                auth: OpenApiAuth::Bearer("secret".to_string()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let output = toolset
+            .invoke(
+                "pets-list_pets",
+                r#"{"tag": ["cat", "dog house"], "limit": 2}"#,
+            )
+            .await
+            .unwrap();
+        let ToolOutput::Json(echo) = output else {
+            panic!("unexpected output {:?}", output);
+        };
+        assert_eq!(
+            echo["request"],
+            "GET /api/pets?tag=cat&tag=dog+house&limit=2 HTTP/1.1"
+        );
+        assert_eq!(echo["authorization"], "Bearer secret");
+
+        let output = toolset
+            .invoke("pets-delete_pet", r#"{"petId": "a/b"}"#)
+            .await
+            .unwrap();
+        assert!(
+            matches!(output, ToolOutput::Json(echo) if echo["request"] == "DELETE /api/pets/a%2Fb HTTP/1.1")
+        );
+
+        let output = toolset
+            .invoke("pets-post_pets", r#"{"body": {"name": "Rex"}}"#)
+            .await
+            .unwrap();
+        assert!(matches!(output, ToolOutput::Json(echo) if echo["body"] == r#"{"name":"Rex"}"#));
+
+        let output = toolset.invoke("pets-broken", "").await.unwrap();
+        assert_eq!(
+            output,
+            ToolOutput::Fail("400 Bad Request: \"name is missing\"".to_string())
+        );
+
+        let toolset = OpenApiToolset::from_spec(
+            "pets",
+            SPEC,
+            OpenApiConfig {
+                base_url: Some(base_url),
+                max_response_bytes: 10,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let output = toolset.invoke("pets-delete_pet", r#"{"petId": "1"}"#).await;
+        assert_eq!(
+            output.unwrap(),
+            ToolOutput::Text("{\"authoriz\n[truncated after 10 bytes]".to_string())
+        );
+    }
+
+    #[test]
+    fn test_snake_case() {
+        assert_eq!(snake_case("listPets"), "list_pets");
+        assert_eq!(snake_case("GET /pets/{petId}"), "get_pets_pet_id");
+        assert_eq!(snake_case("getV2Users"), "get_v2_users");
+    }
+}
@@ -0,0 +1,479 @@
+use std::{
+    path::{Component, Path, PathBuf},
+    process::Stdio,
+    time::Duration,
+};
+
+use meerai_core::{CommandError, CommandOutput, JsonSchema, ToolError, ToolOutput, async_trait};
+use meerai_macros::Toolset;
+use tokio::{
+    io::{AsyncRead, AsyncReadExt},
+    process::Command,
+};
+
+/// Configuration for the ShellToolset.
+#[derive(Debug, Clone)]
+pub struct ShellConfig {
+    /// Program names that may be run, e.g. `git` or `cargo`
+    pub allowed_commands: Vec<String>,
+
+    /// Directory commands run in, they cannot be started outside of it. Commands can read and
+    /// change everything below it, so it should be dedicated to the agent
+    pub working_dir: PathBuf,
+
+    /// Environment variables passed through to commands, all others are removed
+    pub env_allowlist: Vec<String>,
+
+    /// Maximum number of bytes kept from each of stdout and stderr
+    pub max_output_bytes: usize,
+
+    /// Maximum duration of a command before it is killed
+    pub timeout: Duration,
+}
+
+impl ShellConfig {
+    /// Creates a configuration for a dedicated `working_dir`, no program may be run until it
+    /// is added to `allowed_commands`.
+    pub fn new(working_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            allowed_commands: vec![],
+            working_dir: working_dir.into(),
+            env_allowlist: ["PATH", "HOME", "LANG", "TERM"]
+                .into_iter()
+                .map(ToString::to_string)
+                .collect(),
+            max_output_bytes: 64 * 1024,
+            timeout: Duration::from_secs(60),
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize, JsonSchema)]
+pub struct RunCommandArgs {
+    /// Name of the program to run, e.g. `git`
+    pub command: String,
+
+    /// Arguments passed to the program as is, no shell expansion is applied
+    #[serde(default)]
+    pub args: Vec<String>,
+
+    /// Directory to run in, relative to the working directory
+    #[serde(default)]
+    pub cwd: Option<String>,
+}
+
+/// Options of allowed programs that change their configuration or name other programs to run,
+/// e.g. `git -c core.pager=...` or `git -c alias.x=!cmd`.
+const DENIED_OPTIONS: &[(&str, &[&str])] = &[(
+    "git",
+    &[
+        "-c",
+        "--config-env",
+        "--exec-path",
+        "--upload-pack",
+        "--receive-pack",
+        "--exec",
+    ],
+)];
+
+/// Runs allowlisted programs inside a working directory.
+///
+/// Commands are started directly rather than through a shell, so pipes, redirects and
+/// variable expansion are not available to the model.
+#[derive(Toolset)]
+#[toolset(
+    name = "shell",
+    tool(
+        name = "Run Command",
+        description = "Run a program with arguments in the working directory and return its exit output. No shell features such as pipes or redirects are available.",
+        params = RunCommandArgs,
+        side_effecting,
+        idempotent = false,
+    )
+)]
+pub struct ShellToolset {
+    config: ShellConfig,
+}
+
+impl ShellToolset {
+    pub fn new(config: ShellConfig) -> Self {
+        Self { config }
+    }
+
+    /// Resolves the directory to run in, rejecting anything outside the working directory.
+    ///
+    /// Returns the canonical working directory together with the directory to run in.
+    fn resolve_cwd(&self, cwd: Option<&str>) -> Result<(PathBuf, PathBuf), ToolError> {
+        let root = self
+            .config
+            .working_dir
+            .canonicalize()
+            .map_err(|err| ToolError::Unknown(err.into()))?;
+
+        let Some(cwd) = cwd else {
+            return Ok((root.clone(), root));
+        };
+
+        let escapes =
+            || ToolError::invalid_argument("$.cwd", "must stay inside the working directory");
+        if Path::new(cwd).is_absolute() {
+            return Err(escapes());
+        }
+
+        // Symlinks are resolved before checking the jail
+        let resolved = root
+            .join(cwd)
+            .canonicalize()
+            .map_err(|err| ToolError::invalid_argument("$.cwd", err.to_string()))?;
+        if !resolved.starts_with(&root) {
+            return Err(escapes());
+        }
+
+        Ok((root, resolved))
+    }
+
+    /// Checks the command and its arguments, paths in them are relative to `cwd` and must
+    /// stay inside `root`.
+    fn check_command(
+        &self,
+        args: &RunCommandArgs,
+        root: &Path,
+        cwd: &Path,
+    ) -> Result<(), ToolError> {
+        let allowed = self
+            .config
+            .allowed_commands
+            .iter()
+            .any(|command| command == &args.command);
+        if !allowed || args.command.contains(['/', '\\']) {
+            return Err(ToolError::invalid_argument(
+                "$.command",
+                format!(
+                    "`{}` is not allowed, allowed commands are: {}",
+                    args.command,
+                    self.config.allowed_commands.join(", ")
+                ),
+            ));
+        }
+
+        let denied_options = DENIED_OPTIONS
+            .iter()
+            .filter(|(command, _)| *command == args.command)
+            .flat_map(|(_, options)| options.iter());
+        for option in denied_options {
+            if let Some(index) = args.args.iter().position(|arg| is_option(arg, option)) {
+                return Err(ToolError::invalid_argument(
+                    format!("$.args[{}]", index),
+                    format!("`{}` is not allowed for `{}`", option, args.command),
+                ));
+            }
+        }
+
+        // Arguments are not interpreted, but paths in them could still point outside the jail
+        for (index, arg) in args.args.iter().enumerate() {
+            if escapes_jail(arg, root, cwd) {
+                return Err(ToolError::invalid_argument(
+                    format!("$.args[{}]", index),
+                    "paths must be relative and stay inside the working directory",
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ShellInvoke for ShellToolset {
+    async fn run_command(&self, args: &RunCommandArgs) -> Result<ToolOutput, ToolError> {
+        let (root, cwd) = self.resolve_cwd(args.cwd.as_deref())?;
+        self.check_command(args, &root, &cwd)?;
+
+        let mut command = Command::new(&args.command);
+        command
+            .args(&args.args)
+            .current_dir(cwd)
+            .env_clear()
+            .envs(std::env::vars().filter(|(key, _)| self.config.env_allowlist.contains(key)))
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+
+        let mut child = command
+            .spawn()
+            .map_err(|err| CommandError::ExecutorError(err.into()))?;
+        let stdout = child.stdout.take().expect("stdout is piped");
+        let stderr = child.stderr.take().expect("stderr is piped");
+
+        let max_bytes = self.config.max_output_bytes;
+        let run = async {
+            let (stdout, stderr, status) = tokio::join!(
+                read_capped(stdout, max_bytes),
+                read_capped(stderr, max_bytes),
+                child.wait()
+            );
+            Ok::<_, std::io::Error>((stdout?, stderr?, status?))
+        };
+
+        // Dropping the future on timeout kills the child
+        let (stdout, stderr, status) = tokio::time::timeout(self.config.timeout, run)
+            .await
+            .map_err(|_| ToolError::Timeout(self.config.timeout))?
+            .map_err(|err| CommandError::ExecutorError(err.into()))?;
+
+        let output = CommandOutput {
+            output: format_output(&stdout, &stderr),
+        };
+
+        if status.success() {
+            Ok(ToolOutput::Text(output.output))
+        } else {
+            let output = CommandOutput {
+                output: format!("{}\n{}", status, output),
+            };
+            Err(CommandError::NonZeroExit(output).into())
+        }
+    }
+}
+
+/// Matches `option` given on its own, with an `=` value or, for short options, with the value
+/// attached, e.g. `-ccore.pager=less`.
+fn is_option(arg: &str, option: &str) -> bool {
+    let Some(rest) = arg.strip_prefix(option) else {
+        return false;
+    };
+    rest.is_empty() || rest.starts_with('=') || !option.starts_with("--")
+}
+
+/// Whether the argument or a value within it is a path leading outside `root`, either
+/// lexically or through a symlink once resolved against `cwd`.
+fn escapes_jail(arg: &str, root: &Path, cwd: &Path) -> bool {
+    let escapes = |value: &str| {
+        let path = Path::new(value);
+        path.is_absolute()
+            || path.components().any(|c| c == Component::ParentDir)
+            || !resolves_inside(&cwd.join(path), root)
+    };
+
+    // Values of `--key=value` and `key=value`
+    let values = arg.match_indices('=').map(|(index, _)| &arg[index + 1..]);
+    // Short options may carry their value without a space, e.g. `-C/etc` or `-xf../secret`
+    let attached = match arg.strip_prefix('-') {
+        Some(flags) if !flags.starts_with('-') => flags
+            .char_indices()
+            .map(|(index, _)| &flags[index..])
+            .collect(),
+        _ => vec![],
+    };
+
+    escapes(arg) || values.chain(attached).any(escapes)
+}
+
+/// Resolves the symlinks of `path`, or of its deepest existing ancestor for paths that do not
+/// exist yet, and checks that the result is below `root`.
+fn resolves_inside(path: &Path, root: &Path) -> bool {
+    let mut path = path.to_path_buf();
+    loop {
+        if let Ok(resolved) = path.canonicalize() {
+            return resolved.starts_with(root);
+        }
+        if !path.pop() {
+            return false;
+        }
+    }
+}
+
+/// Reads at most `max_bytes` and discards the rest, so the child never blocks on a full pipe.
+async fn read_capped(
+    mut reader: impl AsyncRead + Unpin,
+    max_bytes: usize,
+) -> std::io::Result<(String, u64)> {
+    let mut buffer = Vec::new();
+    (&mut reader)
+        .take(max_bytes as u64)
+        .read_to_end(&mut buffer)
+        .await?;
+    let discarded = tokio::io::copy(&mut reader, &mut tokio::io::sink()).await?;
+
+    Ok((String::from_utf8_lossy(&buffer).into_owned(), discarded))
+}
+
+fn format_output(stdout: &(String, u64), stderr: &(String, u64)) -> String {
+    let section = |name: &str, (text, discarded): &(String, u64)| {
+        let mut section = format!("{}:\n{}", name, text);
+        if *discarded > 0 {
+            section.push_str(&format!("\n[truncated {} bytes]", discarded));
+        }
+        section
+    };
+
+    format!(
+        "{}\n{}",
+        section("stdout", stdout),
+        section("stderr", stderr)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn toolset(dir: &Path) -> ShellToolset {
+        ShellToolset::new(ShellConfig {
+            allowed_commands: ["echo", "false", "sleep", "env", "git", "ls", "pwd"]
+                .into_iter()
+                .map(ToString::to_string)
+                .collect(),
+            max_output_bytes: 16,
+            timeout: Duration::from_secs(5),
+            ..ShellConfig::new(dir)
+        })
+    }
+
+    fn run(command: &str, args: &[&str]) -> RunCommandArgs {
+        RunCommandArgs {
+            command: command.to_string(),
+            args: args.iter().map(ToString::to_string).collect(),
+            cwd: None,
+        }
+    }
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("meerai_shell_{}", name));
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn test_run_command() {
+        let dir = scratch_dir("run");
+        std::fs::write(dir.join("sub").join("marker"), "").unwrap();
+        let toolset = toolset(&dir);
+
+        let output = toolset.run_command(&run("echo", &["hello"])).await.unwrap();
+        assert_eq!(
+            output,
+            ToolOutput::Text("stdout:\nhello\n\nstderr:\n".to_string())
+        );
+
+        let output = toolset
+            .run_command(&RunCommandArgs {
+                cwd: Some("sub".to_string()),
+                ..run("ls", &[])
+            })
+            .await
+            .unwrap();
+        assert!(matches!(output, ToolOutput::Text(text) if text.contains("marker\n")));
+    }
+
+    #[tokio::test]
+    async fn test_run_command_rejected() {
+        let toolset = toolset(&scratch_dir("rejected"));
+
+        for args in [
+            run("rm", &["-rf", "sub"]),
+            run("/bin/echo", &["hello"]),
+            run("echo", &["../secret"]),
+            run("echo", &["--path=/etc"]),
+            run("ls", &["-C/etc"]),
+            run("ls", &["-la../secret"]),
+            run("git", &["-c", "core.pager=sh -c id", "log"]),
+            run("git", &["-calias.x=!id", "x"]),
+            run("git", &["fetch", "--upload-pack=id"]),
+            RunCommandArgs {
+                cwd: Some("..".to_string()),
+                ..run("pwd", &[])
+            },
+        ] {
+            let err = toolset.run_command(&args).await.unwrap_err();
+            assert!(
+                matches!(err, ToolError::InvalidArguments(_)),
+                "{:?} was not rejected",
+                args
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_command_rejects_symlink_escape() {
+        let dir = scratch_dir("symlink");
+        let _ = std::fs::remove_file(dir.join("link"));
+        std::os::unix::fs::symlink("/etc", dir.join("link")).unwrap();
+        let toolset = toolset(&dir);
+
+        for args in [
+            run("ls", &["link/passwd"]),
+            run("ls", &["link"]),
+            run("echo", &["--path=link/missing"]),
+            run("ls", &["-Clink"]),
+        ] {
+            let err = toolset.run_command(&args).await.unwrap_err();
+            assert!(
+                matches!(err, ToolError::InvalidArguments(_)),
+                "{:?} was not rejected",
+                args
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_command_limits() {
+        let dir = scratch_dir("limits");
+        let toolset = toolset(&dir);
+
+        let err = toolset.run_command(&run("false", &[])).await.unwrap_err();
+        assert!(matches!(
+            err,
+            ToolError::ExecutionFailed(CommandError::NonZeroExit(_))
+        ));
+
+        let output = toolset
+            .run_command(&run("echo", &["0123456789abcdefghij"]))
+            .await
+            .unwrap();
+        assert_eq!(
+            output,
+            ToolOutput::Text(
+                "stdout:\n0123456789abcdef\n[truncated 5 bytes]\nstderr:\n".to_string()
+            )
+        );
+
+        let timeout_toolset = ShellToolset::new(ShellConfig {
+            timeout: Duration::from_millis(50),
+            ..toolset.config.clone()
+        });
+        let err = timeout_toolset
+            .run_command(&run("sleep", &["5"]))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ToolError::Timeout(_)));
+    }
+
+    #[tokio::test]
+    async fn test_run_command_scrubs_env() {
+        let toolset = ShellToolset::new(ShellConfig {
+            max_output_bytes: 64 * 1024,
+            ..toolset(&scratch_dir("env")).config
+        });
+
+        let output = toolset.run_command(&run("env", &[])).await.unwrap();
+        let ToolOutput::Text(text) = output else {
+            panic!("unexpected output {:?}", output);
+        };
+        let keys = text
+            .lines()
+            .skip(1)
+            .take_while(|line| !line.is_empty())
+            .filter_map(|line| line.split_once('=').map(|(key, _)| key))
+            .collect::<Vec<_>>();
+        assert!(!keys.is_empty());
+        assert!(keys.iter().all(|key| {
+            toolset
+                .config
+                .env_allowlist
+                .iter()
+                .any(|allowed| allowed == key)
+        }));
+    }
+}
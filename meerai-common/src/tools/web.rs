@@ -0,0 +1,902 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    net::IpAddr,
+    sync::{Arc, LazyLock, Mutex},
+    time::Duration,
+};
+
+use meerai_core::{JsonSchema, ToolError, ToolOutput, async_trait};
+use meerai_macros::Toolset;
+use regex::Regex;
+use reqwest::{
+    Client, Response, StatusCode,
+    dns::{Addrs, Name, Resolve, Resolving},
+    header, redirect,
+};
+use scraper::{ElementRef, Html, Node, Selector};
+use url::{Host, Url};
+
+/// Redirects followed before a fetch is given up
+const MAX_REDIRECTS: usize = 10;
+
+/// RFC 9309 asks crawlers to parse at least 500 KiB of a robots.txt, the rest is ignored
+const MAX_ROBOTS_TXT_BYTES: usize = 500 * 1024;
+
+/// Elements whose content is never part of the readable text
+const SKIPPED_ELEMENTS: &[&str] = &[
+    "script", "style", "noscript", "template", "svg", "canvas", "iframe", "form", "button", "nav",
+    "header", "footer", "aside", "menu", "dialog",
+];
+
+/// Elements that start a new line in the readable text
+const BLOCK_ELEMENTS: &[&str] = &[
+    "address",
+    "article",
+    "blockquote",
+    "dd",
+    "div",
+    "dl",
+    "dt",
+    "figcaption",
+    "figure",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "li",
+    "main",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "td",
+    "th",
+    "tr",
+    "ul",
+];
+
+/// Class and id names of page furniture such as cookie banners and share buttons
+static BOILERPLATE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)\b(cookies?|consent|banner|share|social|comments?|sidebar|related|adverts?|ads?|promo|newsletter|subscribe|breadcrumbs?)\b",
+    )
+    .expect("valid regex")
+});
+
+/// Configuration for the WebToolset.
+#[derive(Debug, Clone)]
+pub struct WebConfig {
+    /// Domains that may be fetched, including their subdomains. Empty allows every domain
+    pub allowed_domains: Vec<String>,
+
+    /// Domains that may never be fetched, including their subdomains. Takes precedence over
+    /// the allowlist
+    pub denied_domains: Vec<String>,
+
+    /// Maximum number of bytes read from a response body
+    pub max_bytes: usize,
+
+    /// Maximum number of links returned for a page
+    pub max_links: usize,
+
+    /// Maximum duration of a request, including reading the body
+    pub timeout: Duration,
+
+    /// Sent with every request, its product token is matched against robots.txt groups
+    pub user_agent: String,
+
+    /// Refuses to fetch pages that robots.txt disallows
+    pub respect_robots_txt: bool,
+
+    /// Allows fetching loopback, private and link-local addresses. They are blocked by default
+    /// so the model cannot reach internal services such as cloud metadata endpoints
+    pub allow_private_networks: bool,
+}
+
+impl Default for WebConfig {
+    fn default() -> Self {
+        Self {
+            allowed_domains: vec![],
+            denied_domains: vec![],
+            max_bytes: 2 * 1024 * 1024,
+            max_links: 50,
+            timeout: Duration::from_secs(20),
+            user_agent: "meerai/0.1".to_string(),
+            respect_robots_txt: true,
+            allow_private_networks: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize, JsonSchema)]
+pub struct FetchUrlArgs {
+    /// Absolute http or https URL
+    pub url: String,
+}
+
+/// Fetches web pages and reduces them to their readable text.
+///
+/// Every request and redirect is checked against the domain lists, the robots.txt of its origin
+/// and, unless private networks are allowed, against the addresses its host resolves to.
+/// Robots.txt files are cached per origin for the lifetime of the toolset.
+#[derive(Toolset)]
+#[toolset(
+    name = "web",
+    tool(
+        name = "Fetch Url",
+        description = "Fetch a web page and return its title, description, readable text, links and metadata.",
+        params = FetchUrlArgs,
+    )
+)]
+pub struct WebToolset {
+    config: WebConfig,
+    client: Client,
+
+    /// Robots.txt rules by origin
+    robots: Mutex<HashMap<String, Arc<RobotsTxt>>>,
+}
+
+impl WebToolset {
+    pub fn new(config: WebConfig) -> Result<Self, reqwest::Error> {
+        let mut builder = Client::builder();
+        if !config.allow_private_networks {
+            builder = builder.dns_resolver(Arc::new(PublicResolver));
+        }
+        let client = builder
+            .user_agent(&config.user_agent)
+            .timeout(config.timeout)
+            // Redirects are followed by `get`, so every target is checked like the first URL
+            .redirect(redirect::Policy::none())
+            .build()?;
+
+        Ok(Self {
+            config,
+            client,
+            robots: Mutex::new(HashMap::new()),
+        })
+    }
+
+    async fn robots_txt(&self, url: &Url) -> Arc<RobotsTxt> {
+        let origin = url.origin().ascii_serialization();
+        if let Some(robots) = self.robots.lock().expect("robots lock").get(&origin) {
+            return robots.clone();
+        }
+
+        let response = match Url::parse(&format!("{}/robots.txt", origin)) {
+            Ok(robots_url) => self.get(robots_url, false).await,
+            Err(err) => Err(ToolError::Unknown(err.into())),
+        };
+        let robots = match response {
+            Ok(Fetched::Response(mut response)) if response.status().is_success() => {
+                let (content, _) = read_body(&mut response, MAX_ROBOTS_TXT_BYTES)
+                    .await
+                    .unwrap_or_default();
+                RobotsTxt::parse(&String::from_utf8_lossy(&content), &self.config.user_agent)
+            }
+            // A missing robots.txt allows everything
+            Ok(Fetched::Response(response)) if response.status().is_client_error() => {
+                RobotsTxt::default()
+            }
+            // RFC 9309 treats an unreachable robots.txt as disallowing everything. It is not
+            // cached, so the next request tries again
+            _ => return Arc::new(RobotsTxt::disallow_all()),
+        };
+
+        let robots = Arc::new(robots);
+        self.robots
+            .lock()
+            .expect("robots lock")
+            .insert(origin, robots.clone());
+        robots
+    }
+
+    /// Sends a GET request and follows its redirects. Every URL on the way is checked against
+    /// the config and, when `check_robots` is set, against the robots.txt of its origin.
+    async fn get(&self, mut url: Url, check_robots: bool) -> Result<Fetched, ToolError> {
+        for _ in 0..=MAX_REDIRECTS {
+            check_url(&self.config, &url)
+                .map_err(|message| ToolError::invalid_argument("$.url", message))?;
+            // Boxed, as fetching the robots.txt goes through `get` again
+            if check_robots && !Box::pin(self.robots_txt(&url)).await.is_allowed(&url) {
+                return Ok(Fetched::Disallowed(url));
+            }
+
+            let response = self
+                .client
+                .get(url.clone())
+                .send()
+                .await
+                .map_err(|err| self.request_error(err))?;
+            let location = response
+                .headers()
+                .get(header::LOCATION)
+                .filter(|_| response.status().is_redirection())
+                .map(|location| location.to_str().map(|location| url.join(location)));
+            url = match location {
+                Some(Ok(Ok(location))) => location,
+                Some(_) => {
+                    return Err(ToolError::invalid_argument(
+                        "$.url",
+                        format!("{} redirects to an invalid location", url),
+                    ));
+                }
+                None => return Ok(Fetched::Response(response)),
+            };
+        }
+
+        Err(ToolError::invalid_argument("$.url", "too many redirects"))
+    }
+
+    fn request_error(&self, err: reqwest::Error) -> ToolError {
+        if err.is_timeout() {
+            ToolError::Timeout(self.config.timeout)
+        } else {
+            ToolError::Unknown(err.into())
+        }
+    }
+}
+
+/// Where following a URL through its redirects ended.
+enum Fetched {
+    Response(Response),
+
+    /// The robots.txt of its origin does not allow fetching the URL
+    Disallowed(Url),
+}
+
+/// Reads at most `max_bytes` of the body, along with whether the rest was cut off.
+async fn read_body(
+    response: &mut Response,
+    max_bytes: usize,
+) -> Result<(Vec<u8>, bool), reqwest::Error> {
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        let remaining = max_bytes - body.len();
+        if chunk.len() > remaining {
+            body.extend_from_slice(&chunk[..remaining]);
+            return Ok((body, true));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok((body, false))
+}
+
+#[async_trait]
+impl WebInvoke for WebToolset {
+    async fn fetch_url(&self, args: &FetchUrlArgs) -> Result<ToolOutput, ToolError> {
+        let url = Url::parse(&args.url)
+            .map_err(|err| ToolError::invalid_argument("$.url", err.to_string()))?;
+
+        let mut response = match self.get(url, self.config.respect_robots_txt).await? {
+            Fetched::Response(response) => response,
+            Fetched::Disallowed(url) => {
+                return Ok(ToolOutput::Fail(format!(
+                    "robots.txt of {} does not allow fetching {}",
+                    url.host_str().unwrap_or_default(),
+                    url
+                )));
+            }
+        };
+
+        let status = response.status();
+        if status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+            return Err(ToolError::Transient(anyhow::anyhow!(
+                "{} returned {}",
+                response.url(),
+                status
+            )));
+        }
+        if !status.is_success() {
+            return Ok(ToolOutput::Fail(format!(
+                "{} returned {}",
+                response.url(),
+                status
+            )));
+        }
+
+        let final_url = response.url().clone();
+        let content_type = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or("text/html")
+            .to_string();
+
+        let (body, truncated) = read_body(&mut response, self.config.max_bytes)
+            .await
+            .map_err(|err| self.request_error(err))?;
+        let body = String::from_utf8_lossy(&body);
+
+        let mut page = if content_type.contains("html") {
+            Page::from_html(&body, &final_url, self.config.max_links)
+        } else if content_type.starts_with("text/")
+            || content_type.contains("json")
+            || content_type.contains("xml")
+        {
+            Page {
+                text: body.into_owned(),
+                ..Default::default()
+            }
+        } else {
+            return Ok(ToolOutput::Fail(format!(
+                "{} has unsupported content type {}",
+                final_url, content_type
+            )));
+        };
+        page.url = final_url.to_string();
+        page.content_type = content_type;
+        page.truncated = truncated;
+
+        Ok(ToolOutput::Json(
+            serde_json::to_value(page).map_err(|err| ToolError::Unknown(err.into()))?,
+        ))
+    }
+}
+
+/// Checks the scheme, the domain lists and hosts given as private addresses.
+///
+/// Domain names are checked against private addresses when they are resolved.
+fn check_url(config: &WebConfig, url: &Url) -> Result<(), String> {
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!("scheme `{}` is not supported", url.scheme()));
+    }
+
+    if !config.allow_private_networks {
+        let ip = match url.host() {
+            Some(Host::Ipv4(ip)) => Some(IpAddr::V4(ip)),
+            Some(Host::Ipv6(ip)) => Some(IpAddr::V6(ip)),
+            Some(Host::Domain(_)) | None => None,
+        };
+        let is_localhost = url.domain().is_some_and(|domain| {
+            let domain = domain.trim_end_matches('.').to_lowercase();
+            domain == "localhost" || domain.ends_with(".localhost")
+        });
+        if is_localhost || ip.is_some_and(|ip| !is_public(ip)) {
+            return Err(format!(
+                "{} is a private network address",
+                url.host_str().unwrap_or_default()
+            ));
+        }
+    }
+
+    let host = url
+        .host_str()
+        .ok_or_else(|| format!("{} has no host", url))?
+        .to_lowercase();
+    let matches = |domain: &String| {
+        let domain = domain.to_lowercase();
+        host == domain || host.ends_with(&format!(".{}", domain))
+    };
+
+    if config.denied_domains.iter().any(matches)
+        || (!config.allowed_domains.is_empty() && !config.allowed_domains.iter().any(matches))
+    {
+        return Err(format!("domain {} is not allowed", host));
+    }
+
+    Ok(())
+}
+
+/// Whether the address is reachable on the public internet, rather than e.g. the loopback,
+/// a private network or the link-local cloud metadata endpoint `169.254.169.254`.
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [first, second, ..] = ip.octets();
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                // 0.0.0.0/8 is "this network", 100.64.0.0/10 is shared carrier-grade NAT space
+                || first == 0
+                || (first == 100 && (64..128).contains(&second)))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public(IpAddr::V4(ip)),
+            None => {
+                let first = ip.segments()[0];
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    // Unique local fc00::/7 and link-local fe80::/10
+                    || (first & 0xfe00) == 0xfc00
+                    || (first & 0xffc0) == 0xfe80)
+            }
+        },
+    }
+}
+
+/// Resolves domain names with the system resolver and drops every address that is not public,
+/// so neither a request nor a redirect can reach a private network through DNS.
+#[derive(Debug)]
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let addresses = tokio::net::lookup_host((host.as_str(), 0))
+                .await?
+                .filter(|address| is_public(address.ip()))
+                .collect::<Vec<_>>();
+            if addresses.is_empty() {
+                return Err(format!("{} does not resolve to a public address", host).into());
+            }
+            Ok(Box::new(addresses.into_iter()) as Addrs)
+        })
+    }
+}
+
+#[derive(Debug, Default, PartialEq, serde::Serialize)]
+struct Link {
+    text: String,
+    href: String,
+}
+
+/// Readable content of a fetched page.
+#[derive(Debug, Default, serde::Serialize)]
+struct Page {
+    url: String,
+    content_type: String,
+    title: Option<String>,
+    description: Option<String>,
+    text: String,
+    links: Vec<Link>,
+
+    /// Content of the `<meta>` tags by name or property, e.g. `og:image`
+    metadata: BTreeMap<String, String>,
+
+    /// The body was cut at the configured maximum size
+    truncated: bool,
+}
+
+impl Page {
+    fn from_html(html: &str, base: &Url, max_links: usize) -> Self {
+        let document = Html::parse_document(html);
+        let select = |selector: &str| Selector::parse(selector).expect("valid selector");
+
+        let mut metadata = BTreeMap::new();
+        for meta in document.select(&select("meta[content]")) {
+            let element = meta.value();
+            if let Some(key) = element.attr("property").or(element.attr("name"))
+                && let Some(content) = element.attr("content")
+            {
+                metadata.insert(key.to_lowercase(), content.trim().to_string());
+            }
+        }
+        if let Some(canonical) = document
+            .select(&select("link[rel=canonical][href]"))
+            .next()
+            .and_then(|link| link.value().attr("href"))
+        {
+            metadata.insert("canonical".to_string(), canonical.to_string());
+        }
+
+        let title = document
+            .select(&select("title"))
+            .next()
+            .map(|title| collapse_whitespace(&title.text().collect::<String>()))
+            .filter(|title| !title.is_empty())
+            .or_else(|| metadata.get("og:title").cloned());
+        let description = metadata
+            .get("description")
+            .or_else(|| metadata.get("og:description"))
+            .cloned();
+
+        let mut links: Vec<Link> = vec![];
+        for anchor in document.select(&select("a[href]")) {
+            let Some(href) = anchor
+                .value()
+                .attr("href")
+                .and_then(|href| base.join(href).ok())
+                .filter(|href| matches!(href.scheme(), "http" | "https"))
+            else {
+                continue;
+            };
+
+            let href = href.to_string();
+            if links.len() >= max_links || links.iter().any(|link| link.href == href) {
+                continue;
+            }
+            links.push(Link {
+                text: collapse_whitespace(&anchor.text().collect::<String>()),
+                href,
+            });
+        }
+
+        // The main content is preferred over the whole body when the page marks it
+        let content = ["article", "main", "[role=main]", "body"]
+            .into_iter()
+            .find_map(|selector| document.select(&select(selector)).next())
+            .unwrap_or_else(|| document.root_element());
+
+        let mut text = String::new();
+        collect_text(content, &mut text);
+        let text = text
+            .lines()
+            .map(collapse_whitespace)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        Self {
+            title,
+            description,
+            text,
+            links,
+            metadata,
+            ..Default::default()
+        }
+    }
+}
+
+fn collect_text(element: ElementRef, text: &mut String) {
+    for child in element.children() {
+        match child.value() {
+            // Line breaks in the source are layout, only block elements start new lines
+            Node::Text(content) => text.extend(
+                content
+                    .chars()
+                    .map(|c| if c.is_whitespace() { ' ' } else { c }),
+            ),
+            Node::Element(child_element) => {
+                let name = child_element.name();
+                let is_boilerplate = SKIPPED_ELEMENTS.contains(&name)
+                    || child_element.attr("hidden").is_some()
+                    || child_element.attr("aria-hidden") == Some("true")
+                    || child_element
+                        .attr("class")
+                        .into_iter()
+                        .chain(child_element.attr("id"))
+                        .any(|names| BOILERPLATE.is_match(names));
+                if is_boilerplate {
+                    continue;
+                }
+
+                let is_block = BLOCK_ELEMENTS.contains(&name);
+                if is_block || name == "br" {
+                    text.push('\n');
+                }
+                if let Some(child) = ElementRef::wrap(child) {
+                    collect_text(child, text);
+                }
+                if is_block {
+                    text.push('\n');
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Path pattern of a robots.txt rule and whether it allows or disallows
+type RobotsRule = (String, bool);
+
+/// Rules of a robots.txt file that apply to one user agent.
+#[derive(Debug, Default)]
+struct RobotsTxt {
+    rules: Vec<RobotsRule>,
+}
+
+impl RobotsTxt {
+    fn disallow_all() -> Self {
+        Self {
+            rules: vec![("/".to_string(), false)],
+        }
+    }
+
+    /// Keeps the rules of the groups naming the product token of `user_agent`, or of the `*`
+    /// groups if none does.
+    fn parse(content: &str, user_agent: &str) -> Self {
+        let token = user_agent
+            .split('/')
+            .next()
+            .unwrap_or(user_agent)
+            .trim()
+            .to_lowercase();
+
+        let mut groups: Vec<(Vec<String>, Vec<RobotsRule>)> = vec![];
+        let mut in_rules = false;
+        for line in content.lines() {
+            let line = line.split('#').next().unwrap_or_default();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+
+            match key.trim().to_lowercase().as_str() {
+                "user-agent" => {
+                    // Consecutive user-agent lines share the rules that follow them
+                    if in_rules || groups.is_empty() {
+                        groups.push((vec![], vec![]));
+                        in_rules = false;
+                    }
+                    if let Some((agents, _)) = groups.last_mut() {
+                        agents.push(value.to_lowercase());
+                    }
+                }
+                rule @ ("allow" | "disallow") => {
+                    in_rules = true;
+                    if let Some((_, rules)) = groups.last_mut()
+                        && !value.is_empty()
+                    {
+                        rules.push((value.to_string(), rule == "allow"));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let applies = |agent: &str| {
+            groups
+                .iter()
+                .any(|(agents, _)| agents.iter().any(|a| a == agent))
+        };
+        let agent = if applies(&token) { token.as_str() } else { "*" };
+        let rules = groups
+            .into_iter()
+            .filter(|(agents, _)| agents.iter().any(|a| a == agent))
+            .flat_map(|(_, rules)| rules)
+            .collect();
+
+        Self { rules }
+    }
+
+    /// The longest matching pattern decides, allow wins a tie.
+    fn is_allowed(&self, url: &Url) -> bool {
+        let path = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        };
+
+        self.rules
+            .iter()
+            .filter(|(pattern, _)| robots_pattern_matches(pattern, &path))
+            .max_by_key(|(pattern, allow)| (pattern.len(), *allow))
+            .is_none_or(|(_, allow)| *allow)
+    }
+}
+
+/// Matches a robots.txt path pattern, where `*` matches anything and a trailing `$` anchors the
+/// end of the path.
+fn robots_pattern_matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+
+    let mut parts = pattern.split('*');
+    let Some(mut rest) = parts.next().and_then(|prefix| path.strip_prefix(prefix)) else {
+        return false;
+    };
+
+    let parts = parts.collect::<Vec<_>>();
+    for (index, part) in parts.iter().enumerate() {
+        if anchored && index == parts.len() - 1 {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(position) => rest = &rest[position + part.len()..],
+            None => return false,
+        }
+    }
+
+    !anchored || rest.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use meerai_core::test_utils::{StubResponse, serve_with};
+
+    use super::*;
+
+    const ARTICLE: &str = r#"<!doctype html>
+<html>
+<head>
+  <title>  Rust 2024 is out </title>
+  <meta name="description" content="Release notes">
+  <meta property="og:image" content="https://example.com/cover.png">
+</head>
+<body>
+  <header><nav><a href="/">Home</a></nav></header>
+  <div class="cookie-banner">We use cookies</div>
+  <article>
+    <h1>Rust 2024</h1>
+    <p>The new   edition is
+    <a href="/editions">here</a>.</p>
+    <script>track()</script>
+    <ul><li>Let chains</li><li>Async closures</li></ul>
+    <div class="share-buttons">Share on X</div>
+  </article>
+  <footer>Copyright</footer>
+</body>
+</html>"#;
+
+    /// Serves fixed responses by path until the test ends.
+    fn serve(routes: Vec<(&'static str, u16, &'static str)>) -> String {
+        serve_with(move |request| {
+            let path = request.split_whitespace().nth(1).unwrap_or("/");
+            let (status, body) = routes
+                .iter()
+                .find(|(route, _, _)| *route == path)
+                .map_or((404, ""), |(_, status, body)| (*status, *body));
+            StubResponse::new(status, "text/html", body)
+        })
+    }
+
+    #[test]
+    fn test_page_from_html() {
+        let base = Url::parse("https://example.com/blog/rust").unwrap();
+        let page = Page::from_html(ARTICLE, &base, 10);
+
+        assert_eq!(page.title.as_deref(), Some("Rust 2024 is out"));
+        assert_eq!(page.description.as_deref(), Some("Release notes"));
+        assert_eq!(
+            page.metadata.get("og:image").map(String::as_str),
+            Some("https://example.com/cover.png")
+        );
+        assert_eq!(
+            page.text,
+            "Rust 2024\nThe new edition is here.\nLet chains\nAsync closures"
+        );
+        assert_eq!(
+            page.links,
+            vec![
+                Link {
+                    text: "Home".to_string(),
+                    href: "https://example.com/".to_string(),
+                },
+                Link {
+                    text: "here".to_string(),
+                    href: "https://example.com/editions".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_robots_txt() {
+        let robots = RobotsTxt::parse(
+            "User-agent: *\nDisallow: /\n\n# ours\nUser-agent: other\nUser-agent: meerai\nDisallow: /private\nAllow: /private/public\nDisallow: /*.pdf$\n",
+            "meerai/0.1",
+        );
+        let allowed = |path: &str| {
+            robots.is_allowed(&Url::parse(&format!("https://example.com{}", path)).unwrap())
+        };
+
+        assert!(allowed("/blog"));
+        assert!(!allowed("/private/notes"));
+        assert!(allowed("/private/public/page"));
+        assert!(!allowed("/files/report.pdf"));
+        assert!(allowed("/files/report.pdf?download"));
+
+        let robots = RobotsTxt::parse("User-agent: *\nDisallow: /\n", "meerai/0.1");
+        assert!(!robots.is_allowed(&Url::parse("https://example.com/a").unwrap()));
+    }
+
+    #[test]
+    fn test_check_url() {
+        let config = WebConfig {
+            allowed_domains: vec!["example.com".to_string()],
+            denied_domains: vec!["ads.example.com".to_string()],
+            ..Default::default()
+        };
+        let check = |url: &str| check_url(&config, &Url::parse(url).unwrap());
+
+        assert!(check("https://example.com/a").is_ok());
+        assert!(check("https://blog.example.com/a").is_ok());
+        assert!(check("https://notexample.com/a").is_err());
+        assert!(check("https://x.ads.example.com/a").is_err());
+        assert!(check("ftp://example.com/a").is_err());
+
+        let check = |url: &str| check_url(&WebConfig::default(), &Url::parse(url).unwrap());
+        assert!(check("https://93.184.215.14/a").is_ok());
+        for url in [
+            "http://127.0.0.1:8080/",
+            "http://localhost/",
+            "http://api.localhost/",
+            "http://10.0.0.1/",
+            "http://192.168.1.1/",
+            "http://169.254.169.254/latest/meta-data/",
+            "http://[::1]/",
+            "http://[::ffff:127.0.0.1]/",
+            "http://[fd00::1]/",
+        ] {
+            assert!(check(url).is_err(), "{} was not blocked", url);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_public_resolver() {
+        let resolved = PublicResolver.resolve("localhost".parse().unwrap()).await;
+        assert!(resolved.is_err());
+
+        let toolset = WebToolset::new(WebConfig::default()).unwrap();
+        let err = toolset
+            .fetch_url(&FetchUrlArgs {
+                url: "http://127.0.0.1:9/".to_string(),
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ToolError::InvalidArguments(_)));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_url() {
+        let base = serve_with(|request| match request.split_whitespace().nth(1) {
+            Some("/robots.txt") => {
+                StubResponse::new(200, "text/plain", "User-agent: *\nDisallow: /private\n")
+            }
+            Some("/article" | "/private") => StubResponse::new(200, "text/html", ARTICLE),
+            Some("/moved") => StubResponse::redirect(302, "/private"),
+            Some("/busy") => StubResponse::new(503, "text/html", ""),
+            _ => StubResponse::new(404, "text/html", ""),
+        });
+        let toolset = WebToolset::new(WebConfig {
+            max_bytes: 256,
+            allow_private_networks: true,
+            ..Default::default()
+        })
+        .unwrap();
+        let fetch = |path: &str| FetchUrlArgs {
+            url: format!("{}{}", base, path),
+        };
+
+        let output = toolset.fetch_url(&fetch("/article")).await.unwrap();
+        let ToolOutput::Json(page) = output else {
+            panic!("unexpected output {:?}", output);
+        };
+        assert_eq!(page["title"], "Rust 2024 is out");
+        assert_eq!(page["truncated"], true);
+
+        let output = toolset.fetch_url(&fetch("/private")).await.unwrap();
+        assert!(matches!(output, ToolOutput::Fail(message) if message.contains("robots.txt")));
+
+        let err = toolset.fetch_url(&fetch("/busy")).await.unwrap_err();
+        assert!(matches!(err, ToolError::Transient(_)));
+
+        let output = toolset.fetch_url(&fetch("/missing")).await.unwrap();
+        assert!(matches!(output, ToolOutput::Fail(message) if message.ends_with("404 Not Found")));
+
+        // Redirect targets are checked against robots.txt as well
+        let output = toolset.fetch_url(&fetch("/moved")).await.unwrap();
+        assert!(matches!(output, ToolOutput::Fail(message) if message.contains("robots.txt")));
+
+        // A failing robots.txt disallows everything
+        let base = serve(vec![("/robots.txt", 500, ""), ("/article", 200, ARTICLE)]);
+        let output = toolset
+            .fetch_url(&FetchUrlArgs {
+                url: format!("{}/article", base),
+            })
+            .await
+            .unwrap();
+        assert!(matches!(output, ToolOutput::Fail(message) if message.contains("robots.txt")));
+    }
+
+    #[tokio::test]
+    async fn test_robots_txt_size_cap() {
+        // Rules past the cap are ignored
+        let content = format!(
+            "User-agent: *\nDisallow: /private\n{}\nDisallow: /\n",
+            "#".repeat(MAX_ROBOTS_TXT_BYTES)
+        );
+        let base = serve_with(move |_| StubResponse::new(200, "text/plain", content.clone()));
+        let toolset = WebToolset::new(WebConfig {
+            allow_private_networks: true,
+            ..Default::default()
+        })
+        .unwrap();
+
+        let robots = toolset.robots_txt(&Url::parse(&base).unwrap()).await;
+        assert!(robots.is_allowed(&Url::parse(&format!("{}/article", base)).unwrap()));
+        assert!(!robots.is_allowed(&Url::parse(&format!("{}/private", base)).unwrap()));
+    }
+}
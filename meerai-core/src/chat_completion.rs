@@ -2,14 +2,18 @@ use std::fmt::Debug;
 
 use anyhow::Result;
 use async_openai::types::{
-    ChatCompletionRequestAssistantMessageArgs, ChatCompletionRequestDeveloperMessageArgs,
-    ChatCompletionRequestMessage, ChatCompletionRequestSystemMessageArgs,
-    ChatCompletionRequestUserMessageArgs,
+    ChatCompletionMessageToolCall, ChatCompletionRequestAssistantMessageArgs,
+    ChatCompletionRequestDeveloperMessageArgs, ChatCompletionRequestMessage,
+    ChatCompletionRequestMessageContentPartAudio, ChatCompletionRequestMessageContentPartImage,
+    ChatCompletionRequestSystemMessageArgs, ChatCompletionRequestToolMessageArgs,
+    ChatCompletionRequestUserMessageArgs, ChatCompletionRequestUserMessageContent,
+    ChatCompletionRequestUserMessageContentPart, ChatCompletionToolType, FunctionCall, ImageDetail,
+    ImageUrl, InputAudio, InputAudioFormat,
 };
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use crate::{ToolCall, ToolDefinition, async_trait};
+use crate::{Attachment, ToolCall, ToolDefinition, async_trait};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum ChatMessage {
@@ -17,19 +21,79 @@ pub enum ChatMessage {
     Developer(String),
     System(String),
     User(String),
+
+    /// Tool calls requested by the assistant
+    ToolCalls(Vec<ToolCall>),
+
+    /// Result of a tool call, sent back to the model with the ID of the call
+    ToolResult(ToolCall, String),
+
+    /// Media sent as user content, e.g. an image returned by a tool. Providers that cannot
+    /// read the MIME type receive a short description instead
+    Attachment(Attachment),
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct ChatCompletionRequest {
     pub model: Option<String>,
     pub messages: Vec<ChatMessage>,
     pub tool_definitions: Vec<ToolDefinition>,
+
+    /// Ends of prompt prefixes that stay the same across requests and may be cached.
+    /// Providers without prompt caching ignore them.
+    pub cache_breakpoints: Vec<CacheBreakpoint>,
+}
+
+/// Marks the end of a prompt prefix that the provider may cache.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CacheBreakpoint {
+    /// Cache the tool definitions
+    Tools,
+
+    /// Cache every message up to and including the message at this index
+    Message(usize),
 }
 
 #[derive(Clone, Debug)]
 pub struct ChatCompletionResponse {
     pub messages: Vec<ChatMessage>,
     pub tool_calls: Vec<ToolCall>,
+    pub usage: Option<Usage>,
+}
+
+/// Token usage reported by the provider for a single completion.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Usage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub total_tokens: u64,
+
+    /// Prompt tokens read from the provider's prompt cache
+    pub cache_read_tokens: u64,
+
+    /// Prompt tokens written to the provider's prompt cache, zero for providers that do not
+    /// charge for cache writes
+    pub cache_write_tokens: u64,
+
+    /// Cost in USD, when the provider reports it
+    pub cost: Option<f64>,
+}
+
+impl From<&async_openai::types::CompletionUsage> for Usage {
+    fn from(usage: &async_openai::types::CompletionUsage) -> Self {
+        Self {
+            prompt_tokens: usage.prompt_tokens as u64,
+            completion_tokens: usage.completion_tokens as u64,
+            total_tokens: usage.total_tokens as u64,
+            cache_read_tokens: usage
+                .prompt_tokens_details
+                .as_ref()
+                .and_then(|details| details.cached_tokens)
+                .unwrap_or_default() as u64,
+            cache_write_tokens: 0,
+            cost: None,
+        }
+    }
 }
 
 #[derive(Error, Debug)]
@@ -40,6 +104,9 @@ pub enum ChatCompletionError {
     #[error("llm returned an error: {0}")]
     LLM(#[from] async_openai::error::OpenAIError),
 
+    #[error("http request failed: {0}")]
+    Http(#[from] reqwest::Error),
+
     #[error(transparent)]
     Unknown(#[from] anyhow::Error),
 }
@@ -76,7 +143,72 @@ pub fn message_to_openai(message: &ChatMessage) -> Result<ChatCompletionRequestM
             .content(content.clone())
             .build()?
             .into(),
+        ChatMessage::ToolCalls(tool_calls) => ChatCompletionRequestAssistantMessageArgs::default()
+            .tool_calls(
+                tool_calls
+                    .iter()
+                    .map(|tool_call| ChatCompletionMessageToolCall {
+                        id: tool_call.id.clone(),
+                        r#type: ChatCompletionToolType::Function,
+                        function: FunctionCall {
+                            name: tool_call.name.clone(),
+                            arguments: tool_call.args.clone(),
+                        },
+                    })
+                    .collect::<Vec<_>>(),
+            )
+            .build()?
+            .into(),
+        ChatMessage::ToolResult(tool_call, content) => {
+            ChatCompletionRequestToolMessageArgs::default()
+                .tool_call_id(tool_call.id.clone())
+                .content(content.clone())
+                .build()?
+                .into()
+        }
+        ChatMessage::Attachment(attachment) => ChatCompletionRequestUserMessageArgs::default()
+            .content(attachment_to_openai(attachment))
+            .build()?
+            .into(),
     };
 
     Ok(openai_message)
 }
+
+/// Images are sent as data URLs and WAV or MP3 audio as input audio, the only media types of
+/// the chat completions API.
+fn attachment_to_openai(attachment: &Attachment) -> ChatCompletionRequestUserMessageContent {
+    let audio_format = match attachment.mime_type.as_str() {
+        "audio/wav" | "audio/x-wav" | "audio/wave" => Some(InputAudioFormat::Wav),
+        "audio/mpeg" | "audio/mp3" => Some(InputAudioFormat::Mp3),
+        _ => None,
+    };
+
+    let part = if attachment.mime_type.starts_with("image/") {
+        ChatCompletionRequestUserMessageContentPart::ImageUrl(
+            ChatCompletionRequestMessageContentPartImage {
+                image_url: ImageUrl {
+                    url: format!(
+                        "data:{};base64,{}",
+                        attachment.mime_type,
+                        attachment.to_base64()
+                    ),
+                    detail: Some(ImageDetail::Auto),
+                },
+            },
+        )
+    } else if let Some(format) = audio_format {
+        ChatCompletionRequestUserMessageContentPart::InputAudio(
+            ChatCompletionRequestMessageContentPartAudio {
+                input_audio: InputAudio {
+                    data: attachment.to_base64(),
+                    format,
+                },
+            },
+        )
+    } else {
+        return ChatCompletionRequestUserMessageContent::Text(attachment.to_string());
+    };
+
+    ChatCompletionRequestUserMessageContent::Array(vec![part])
+}
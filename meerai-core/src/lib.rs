@@ -1,12 +1,37 @@
 pub mod chat_completion;
 pub mod errors;
+pub mod model_catalog;
 mod providers;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
+mod tool_cache;
+mod tool_registry;
 mod tools;
+mod toolset_adapter;
+mod validation;
 
 pub use async_trait::async_trait;
 pub use providers::{
     gemini::{Gemini, GeminiConfig, Options as GeminiOptions},
-    openrouter::{OpenRouter, OpenRouterConfig, Options as OpenRouterOptions},
+    gemini_native::{
+        FunctionCallingMode, GeminiNative, GeminiNativeConfig, HarmBlockThreshold, HarmCategory,
+        Options as GeminiNativeOptions, SafetySetting,
+    },
+    ollama::{
+        ModelOptions as OllamaModelOptions, Ollama, OllamaConfig, OllamaModel, OllamaModelDetails,
+        Options as OllamaOptions,
+    },
+    openrouter::{
+        DataCollection, OpenRouter, OpenRouterConfig, Options as OpenRouterOptions,
+        ProviderPreferences,
+    },
 };
 pub use schemars::JsonSchema;
-pub use tools::{ToolCall, ToolDefinition, ToolError, ToolOutput, Toolset};
+pub use tool_cache::{CachedToolset, ToolCacheConfig};
+pub use tool_registry::{ToolRegistry, ToolRegistryError};
+pub use tools::{
+    Artifact, Attachment, CommandError, CommandOutput, RateLimit, ToolCall, ToolDefinition,
+    ToolError, ToolErrorKind, ToolMetadata, ToolOutput, Toolset,
+};
+pub use toolset_adapter::{MergedToolset, ToolsetAdapter, ToolsetExt};
+pub use validation::ValidationError;
@@ -0,0 +1,396 @@
+use std::{collections::BTreeMap, path::Path};
+
+use serde::{Deserialize, Deserializer, Serialize};
+use thiserror::Error;
+
+/// Input or output modality supported by a model.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Modality {
+    Text,
+    Image,
+    Audio,
+    Video,
+    File,
+    #[serde(other)]
+    Other,
+}
+
+/// Prices in USD per unit, as published by the provider.
+///
+/// Token prices are per single token, `request` is charged once per request
+/// and `image` once per input image.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ModelPricing {
+    pub prompt: f64,
+    pub completion: f64,
+    pub request: f64,
+    pub image: f64,
+    pub input_cache_read: f64,
+    pub input_cache_write: f64,
+}
+
+impl ModelPricing {
+    /// Estimates the cost in USD of a single request with the given token counts.
+    pub fn cost(&self, prompt_tokens: u64, completion_tokens: u64) -> f64 {
+        self.request
+            + self.prompt * prompt_tokens as f64
+            + self.completion * completion_tokens as f64
+    }
+}
+
+/// Metadata about a single model ID.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct ModelInfo {
+    pub id: String,
+
+    #[serde(default)]
+    pub name: String,
+
+    /// Maximum number of tokens (prompt + completion) the model accepts
+    #[serde(default)]
+    pub context_length: Option<u64>,
+
+    /// Maximum number of tokens the model produces in a single completion
+    #[serde(default)]
+    pub max_completion_tokens: Option<u64>,
+
+    #[serde(default = "default_modalities")]
+    pub input_modalities: Vec<Modality>,
+
+    #[serde(default = "default_modalities")]
+    pub output_modalities: Vec<Modality>,
+
+    #[serde(default)]
+    pub supports_tools: bool,
+
+    #[serde(default)]
+    pub pricing: ModelPricing,
+}
+
+fn default_modalities() -> Vec<Modality> {
+    vec![Modality::Text]
+}
+
+#[derive(Error, Debug)]
+pub enum ModelCatalogError {
+    #[error("failed to read model catalog: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("failed to parse model catalog: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("failed to parse model catalog: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+
+    #[error("failed to fetch model catalog: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("unsupported model catalog format: {0}")]
+    UnsupportedFormat(String),
+}
+
+/// A lookup table of model metadata keyed by model ID.
+///
+/// The catalog can be fetched from OpenRouter's `/models` endpoint or loaded from
+/// a local JSON/YAML file with a top-level `models` list.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct ModelCatalog {
+    #[serde(with = "models_as_list")]
+    models: BTreeMap<String, ModelInfo>,
+}
+
+impl ModelCatalog {
+    pub fn new(models: impl IntoIterator<Item = ModelInfo>) -> Self {
+        Self {
+            models: models
+                .into_iter()
+                .map(|model| (model.id.clone(), model))
+                .collect(),
+        }
+    }
+
+    /// Loads a catalog from a `.json`, `.yml` or `.yaml` file.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ModelCatalogError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)?;
+
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => Ok(serde_json::from_str(&content)?),
+            Some("yml") | Some("yaml") => Ok(serde_yaml::from_str(&content)?),
+            _ => Err(ModelCatalogError::UnsupportedFormat(
+                path.display().to_string(),
+            )),
+        }
+    }
+
+    /// Parses the body of an OpenRouter `/models` response.
+    pub fn from_openrouter_json(content: &str) -> Result<Self, ModelCatalogError> {
+        let response: OpenRouterModels = serde_json::from_str(content)?;
+        Ok(Self::new(response.data.into_iter().map(ModelInfo::from)))
+    }
+
+    /// Fetches the catalog from the `/models` endpoint of an OpenRouter compatible API.
+    pub async fn fetch(
+        config: &impl async_openai::config::Config,
+    ) -> Result<Self, ModelCatalogError> {
+        let content = reqwest::Client::new()
+            .get(config.url("/models"))
+            .headers(config.headers())
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+
+        Self::from_openrouter_json(&content)
+    }
+
+    pub fn get(&self, id: &str) -> Option<&ModelInfo> {
+        self.models.get(id)
+    }
+
+    pub fn insert(&mut self, model: ModelInfo) {
+        self.models.insert(model.id.clone(), model);
+    }
+
+    pub fn models(&self) -> impl Iterator<Item = &ModelInfo> {
+        self.models.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.models.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.models.is_empty()
+    }
+}
+
+mod models_as_list {
+    use std::collections::BTreeMap;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use super::ModelInfo;
+
+    pub fn serialize<S: Serializer>(
+        models: &BTreeMap<String, ModelInfo>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(models.values())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<BTreeMap<String, ModelInfo>, D::Error> {
+        let models = Vec::<ModelInfo>::deserialize(deserializer)?;
+        Ok(models
+            .into_iter()
+            .map(|model| (model.id.clone(), model))
+            .collect())
+    }
+}
+
+// [OpenRouter API Reference](https://openrouter.ai/docs/api-reference/list-available-models)
+#[derive(Debug, Deserialize)]
+struct OpenRouterModels {
+    data: Vec<OpenRouterModel>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenRouterModel {
+    id: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    context_length: Option<u64>,
+    #[serde(default)]
+    architecture: OpenRouterArchitecture,
+    #[serde(default)]
+    pricing: OpenRouterPricing,
+    #[serde(default)]
+    top_provider: OpenRouterTopProvider,
+    #[serde(default)]
+    supported_parameters: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct OpenRouterArchitecture {
+    input_modalities: Vec<Modality>,
+    output_modalities: Vec<Modality>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct OpenRouterTopProvider {
+    context_length: Option<u64>,
+    max_completion_tokens: Option<u64>,
+}
+
+// OpenRouter publishes prices as decimal strings
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct OpenRouterPricing {
+    #[serde(deserialize_with = "price_from_str")]
+    prompt: f64,
+    #[serde(deserialize_with = "price_from_str")]
+    completion: f64,
+    #[serde(deserialize_with = "price_from_str")]
+    request: f64,
+    #[serde(deserialize_with = "price_from_str")]
+    image: f64,
+    #[serde(deserialize_with = "price_from_str")]
+    input_cache_read: f64,
+    #[serde(deserialize_with = "price_from_str")]
+    input_cache_write: f64,
+}
+
+fn price_from_str<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    let price = Option::<String>::deserialize(deserializer)?;
+    match price {
+        Some(price) => price.parse().map_err(serde::de::Error::custom),
+        None => Ok(0.0),
+    }
+}
+
+impl From<OpenRouterModel> for ModelInfo {
+    fn from(model: OpenRouterModel) -> Self {
+        let OpenRouterModel {
+            id,
+            name,
+            context_length,
+            architecture,
+            pricing,
+            top_provider,
+            supported_parameters,
+        } = model;
+
+        let non_empty_or_text = |modalities: Vec<Modality>| {
+            if modalities.is_empty() {
+                default_modalities()
+            } else {
+                modalities
+            }
+        };
+
+        Self {
+            id,
+            name,
+            context_length: context_length.or(top_provider.context_length),
+            max_completion_tokens: top_provider.max_completion_tokens,
+            input_modalities: non_empty_or_text(architecture.input_modalities),
+            output_modalities: non_empty_or_text(architecture.output_modalities),
+            supports_tools: supported_parameters.iter().any(|param| param == "tools"),
+            pricing: ModelPricing {
+                prompt: pricing.prompt,
+                completion: pricing.completion,
+                request: pricing.request,
+                image: pricing.image,
+                input_cache_read: pricing.input_cache_read,
+                input_cache_write: pricing.input_cache_write,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OPENROUTER_MODELS: &str = r#"{
+        "data": [
+            {
+                "id": "meta-llama/llama-4-maverick",
+                "name": "Meta: Llama 4 Maverick",
+                "context_length": 1048576,
+                "architecture": {
+                    "modality": "text+image->text",
+                    "input_modalities": ["text", "image"],
+                    "output_modalities": ["text"],
+                    "tokenizer": "Llama4"
+                },
+                "pricing": {
+                    "prompt": "0.00000017",
+                    "completion": "0.0000006",
+                    "request": "0",
+                    "image": "0.0006684",
+                    "web_search": "0"
+                },
+                "top_provider": {
+                    "context_length": 1048576,
+                    "max_completion_tokens": 16384,
+                    "is_moderated": false
+                },
+                "supported_parameters": ["tools", "tool_choice", "max_tokens"]
+            },
+            {
+                "id": "some/text-model",
+                "name": "Text Model",
+                "context_length": null,
+                "architecture": { "modality": "text->text" },
+                "pricing": { "prompt": "0.000001", "completion": "0.000002" },
+                "top_provider": { "context_length": 8192 }
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn test_from_openrouter_json() {
+        let catalog = ModelCatalog::from_openrouter_json(OPENROUTER_MODELS).unwrap();
+        assert_eq!(catalog.len(), 2);
+
+        let maverick = catalog.get("meta-llama/llama-4-maverick").unwrap();
+        assert_eq!(maverick.context_length, Some(1048576));
+        assert_eq!(maverick.max_completion_tokens, Some(16384));
+        assert_eq!(
+            maverick.input_modalities,
+            vec![Modality::Text, Modality::Image]
+        );
+        assert!(maverick.supports_tools);
+        assert_eq!(maverick.pricing.prompt, 0.00000017);
+        assert_eq!(maverick.pricing.image, 0.0006684);
+
+        let text_model = catalog.get("some/text-model").unwrap();
+        assert_eq!(text_model.context_length, Some(8192));
+        assert_eq!(text_model.input_modalities, vec![Modality::Text]);
+        assert!(!text_model.supports_tools);
+        assert!((text_model.pricing.cost(1000, 500) - 0.002).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_from_file() {
+        let path = std::env::temp_dir().join("meerai_model_catalog_test.yml");
+        std::fs::write(
+            &path,
+            r#"
+models:
+  - id: gemini-1.5-flash
+    context_length: 1048576
+    input_modalities: [text, image, audio, video]
+    supports_tools: true
+    pricing:
+      prompt: 0.000000075
+      completion: 0.0000003
+"#,
+        )
+        .unwrap();
+
+        let catalog = ModelCatalog::from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let flash = catalog.get("gemini-1.5-flash").unwrap();
+        assert_eq!(flash.context_length, Some(1048576));
+        assert_eq!(flash.output_modalities, vec![Modality::Text]);
+        assert!(flash.supports_tools);
+        assert_eq!(flash.pricing.completion, 0.0000003);
+
+        let json = serde_json::to_string(&catalog).unwrap();
+        assert_eq!(
+            serde_json::from_str::<ModelCatalog>(&json).unwrap(),
+            catalog
+        );
+    }
+}
@@ -9,7 +9,7 @@ use crate::{
     ToolCall, async_trait,
     chat_completion::{
         ChatCompletion, ChatCompletionError, ChatCompletionRequest, ChatCompletionResponse,
-        ChatMessage, message_to_openai,
+        ChatMessage, Usage, message_to_openai,
     },
 };
 
@@ -178,11 +178,12 @@ impl ChatCompletion for Gemini {
                 .unwrap_or_default()
                 .iter()
                 .map(|tool_call| ToolCall {
-                    // id: tool_call.id,
+                    id: tool_call.id.clone(),
                     name: tool_call.function.name.clone(),
                     args: tool_call.function.arguments.clone(),
                 })
                 .collect::<Vec<ToolCall>>(),
+            usage: res.usage.as_ref().map(Usage::from),
         };
 
         Ok(chat_completion_response)
@@ -0,0 +1,803 @@
+use std::{
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
+use secrecy::{ExposeSecret, SecretString};
+use serde::Deserialize;
+use serde_json::json;
+
+use super::types::{
+    Blob, CachedContent, Content, FunctionCall, FunctionCallingConfig, FunctionCallingMode,
+    FunctionDeclaration, FunctionResponse, GenerateContentRequest, GenerateContentResponse,
+    GenerationConfig, Part, SafetySetting, Tool, ToolConfig,
+};
+use crate::{
+    Attachment, ToolCall, async_trait,
+    chat_completion::{
+        CacheBreakpoint, ChatCompletion, ChatCompletionError, ChatCompletionRequest,
+        ChatCompletionResponse, ChatMessage, Usage,
+    },
+};
+
+const GEMINI_API_URL: &str = "https://generativelanguage.googleapis.com/v1beta";
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct GeminiNativeConfig {
+    api_url: String,
+    api_key: SecretString,
+}
+
+impl Default for GeminiNativeConfig {
+    fn default() -> Self {
+        Self {
+            api_url: GEMINI_API_URL.to_string(),
+            api_key: std::env::var("GEMINI_API_KEY")
+                .unwrap_or_else(|_| String::new())
+                .into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Options {
+    pub prompt_model: String,
+
+    /// Per-category blocking thresholds, the API defaults apply to omitted categories
+    pub safety_settings: Vec<SafetySetting>,
+
+    pub function_calling_mode: FunctionCallingMode,
+
+    /// Restricts the functions the model may call when `function_calling_mode` is `Any`
+    pub allowed_function_names: Vec<String>,
+
+    /// Whether to ground responses with Google Search
+    pub google_search: bool,
+
+    pub temperature: Option<f32>,
+
+    pub max_output_tokens: Option<u32>,
+
+    /// Lifetime of the `cachedContents` created for requests with cache breakpoints
+    pub cache_ttl: Duration,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            prompt_model: "gemini-1.5-flash".to_string(),
+            safety_settings: vec![],
+            function_calling_mode: FunctionCallingMode::default(),
+            allowed_function_names: vec![],
+            google_search: false,
+            temperature: None,
+            max_output_tokens: None,
+            cache_ttl: Duration::from_secs(300),
+        }
+    }
+}
+
+/// Gemini provider using the native `generateContent` API instead of the
+/// OpenAI compatibility layer.
+#[derive(Debug, Clone)]
+pub struct GeminiNative {
+    pub client: reqwest::Client,
+    pub config: GeminiNativeConfig,
+    pub default_options: Options,
+
+    /// `cachedContents` created so far, keyed by a hash of the cached prefix
+    cached_contents: Arc<Mutex<HashMap<u64, CachedContentEntry>>>,
+}
+
+#[derive(Debug, Clone)]
+struct CachedContentEntry {
+    /// `None` when the prefix could not be cached, e.g. because it is below the minimum size
+    name: Option<String>,
+    expires_at: Instant,
+}
+
+// Don't reuse a cache that may expire while the request is in flight
+const CACHE_EXPIRY_MARGIN: Duration = Duration::from_secs(30);
+
+impl Default for GeminiNative {
+    fn default() -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            config: GeminiNativeConfig::default(),
+            default_options: Options::default(),
+            cached_contents: Arc::default(),
+        }
+    }
+}
+
+impl GeminiNative {
+    pub fn new(api_url: &str, api_key: &str) -> Self {
+        Self::new_with_options(api_url, api_key, Options::default())
+    }
+
+    pub fn new_with_options(api_url: &str, api_key: &str, options: Options) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            config: GeminiNativeConfig {
+                api_url: api_url.trim_end_matches('/').to_string(),
+                api_key: api_key.to_string().into(),
+            },
+            default_options: options,
+            cached_contents: Arc::default(),
+        }
+    }
+
+    pub fn set_default_options(&mut self, options: Options) {
+        self.default_options = options;
+    }
+
+    fn build_request(&self, request: &ChatCompletionRequest) -> GenerateContentRequest {
+        let options = &self.default_options;
+
+        let (system_instructions, contents) = to_contents(&request.messages);
+
+        let mut tools = Vec::new();
+        if !request.tool_definitions.is_empty() {
+            tools.push(Tool::FunctionDeclarations(
+                request
+                    .tool_definitions
+                    .iter()
+                    .map(|tool| FunctionDeclaration {
+                        name: tool.name.clone(),
+                        description: tool.description.clone(),
+                        parameters_json_schema: tool.parameters.clone(),
+                    })
+                    .collect(),
+            ));
+        }
+        if options.google_search {
+            tools.push(Tool::GoogleSearch {});
+        }
+
+        let tool_config = (!request.tool_definitions.is_empty()).then(|| ToolConfig {
+            function_calling_config: FunctionCallingConfig {
+                mode: options.function_calling_mode,
+                allowed_function_names: options.allowed_function_names.clone(),
+            },
+        });
+
+        let generation_config = (options.temperature.is_some()
+            || options.max_output_tokens.is_some())
+        .then_some(GenerationConfig {
+            temperature: options.temperature,
+            max_output_tokens: options.max_output_tokens,
+        });
+
+        GenerateContentRequest {
+            contents,
+            cached_content: None,
+            system_instruction: (!system_instructions.is_empty()).then_some(Content {
+                role: None,
+                parts: system_instructions,
+            }),
+            tools,
+            tool_config,
+            safety_settings: options.safety_settings.clone(),
+            generation_config,
+        }
+    }
+
+    /// Number of leading `contents` covered by the furthest cache breakpoint.
+    ///
+    /// The system instruction and tools are always part of the cached prefix.
+    fn cached_prefix_len(request: &ChatCompletionRequest) -> Option<usize> {
+        request
+            .cache_breakpoints
+            .iter()
+            .map(|breakpoint| match breakpoint {
+                CacheBreakpoint::Tools => 0,
+                CacheBreakpoint::Message(index) => {
+                    let end = (index + 1).min(request.messages.len());
+                    to_contents(&request.messages[..end]).1.len()
+                }
+            })
+            .max()
+    }
+
+    /// Moves the first `prefix_len` contents, the system instruction and the tools of
+    /// `request` into a `cachedContents` resource, reusing a live one when possible.
+    ///
+    /// Returns the number of tokens written to a newly created cache.
+    ///
+    /// [Gemini Context Caching](https://ai.google.dev/gemini-api/docs/caching)
+    async fn use_cached_content(
+        &self,
+        model: &str,
+        request: &mut GenerateContentRequest,
+        prefix_len: usize,
+    ) -> Result<u64, ChatCompletionError> {
+        let prefix_len = prefix_len.min(request.contents.len());
+        let mut cached_content = json!({
+            "model": format!("models/{}", model),
+            "contents": &request.contents[..prefix_len],
+            "systemInstruction": &request.system_instruction,
+            "tools": &request.tools,
+            "toolConfig": &request.tool_config,
+        });
+
+        let mut hasher = DefaultHasher::new();
+        cached_content.to_string().hash(&mut hasher);
+        let key = hasher.finish();
+
+        let entry = self
+            .cached_contents
+            .lock()
+            .unwrap()
+            .get(&key)
+            .filter(|entry| entry.expires_at > Instant::now() + CACHE_EXPIRY_MARGIN)
+            .cloned();
+
+        let mut written_tokens = 0;
+        let name = match entry {
+            Some(entry) => entry.name,
+            None => {
+                let ttl = self.default_options.cache_ttl;
+                cached_content["ttl"] = json!(format!("{}s", ttl.as_secs()));
+
+                let res = self
+                    .client
+                    .post(format!("{}/cachedContents", self.config.api_url))
+                    .header("x-goog-api-key", self.config.api_key.expose_secret())
+                    .json(&cached_content)
+                    .send()
+                    .await?;
+
+                // Caching is best effort, the API rejects prefixes below a minimum token count.
+                // Only such rejections are remembered, other failures are retried next request
+                let status = res.status();
+                let name = if status.is_success() {
+                    let cached = res.json::<CachedContent>().await?;
+                    written_tokens = cached.usage_metadata.total_token_count;
+                    Some(cached.name)
+                } else if status.is_client_error()
+                    && status != reqwest::StatusCode::TOO_MANY_REQUESTS
+                {
+                    None
+                } else {
+                    return Ok(0);
+                };
+
+                self.cached_contents.lock().unwrap().insert(
+                    key,
+                    CachedContentEntry {
+                        name: name.clone(),
+                        expires_at: Instant::now() + ttl,
+                    },
+                );
+
+                name
+            }
+        };
+
+        if let Some(name) = name {
+            request.cached_content = Some(name);
+            request.contents.drain(..prefix_len);
+            request.system_instruction = None;
+            request.tools.clear();
+            request.tool_config = None;
+        }
+
+        Ok(written_tokens)
+    }
+
+    fn parse_response(
+        response: GenerateContentResponse,
+    ) -> Result<ChatCompletionResponse, ChatCompletionError> {
+        if response.candidates.is_empty() {
+            let reason = response
+                .prompt_feedback
+                .and_then(|feedback| feedback.block_reason)
+                .unwrap_or_else(|| "no candidates returned".to_string());
+            return Err(ChatCompletionError::Literal(format!(
+                "gemini blocked the prompt: {}",
+                reason
+            )));
+        }
+
+        let candidate = response.candidates.into_iter().next().unwrap_or_default();
+        let parts = match (candidate.content, candidate.finish_reason) {
+            (Some(content), _) => content.parts,
+            (None, Some(reason)) if reason != "STOP" => {
+                return Err(ChatCompletionError::Literal(format!(
+                    "gemini stopped without content: {}",
+                    reason
+                )));
+            }
+            (None, _) => vec![],
+        };
+
+        let mut messages = Vec::new();
+        let mut tool_calls = Vec::new();
+
+        for part in parts {
+            if part.thought == Some(true) {
+                continue;
+            }
+
+            if let Some(text) = part.text {
+                messages.push(ChatMessage::Assistant(text));
+            }
+
+            if let Some(call) = part.function_call {
+                tool_calls.push(ToolCall {
+                    // Older models don't return call IDs
+                    id: call
+                        .id
+                        .unwrap_or_else(|| format!("call_{}", tool_calls.len())),
+                    name: call.name,
+                    args: call.args.to_string(),
+                });
+            }
+        }
+
+        Ok(ChatCompletionResponse {
+            messages,
+            tool_calls,
+            usage: response.usage_metadata.map(|usage| Usage {
+                prompt_tokens: usage.prompt_token_count,
+                completion_tokens: usage.candidates_token_count,
+                total_tokens: usage.total_token_count,
+                cache_read_tokens: usage.cached_content_token_count,
+                ..Default::default()
+            }),
+        })
+    }
+}
+
+/// Splits messages into the system instruction parts and the conversation contents.
+///
+/// Consecutive tool results are merged into a single turn, as Gemini expects one function
+/// response part per function call of the previous turn. Attachments join the user turn
+/// before them, so media returned by tools arrives together with the function responses.
+fn to_contents(messages: &[ChatMessage]) -> (Vec<Part>, Vec<Content>) {
+    let mut system_instructions = Vec::new();
+    let mut contents: Vec<Content> = Vec::new();
+
+    for message in messages {
+        match message {
+            ChatMessage::System(content) | ChatMessage::Developer(content) => {
+                system_instructions.push(Part::text(content.clone()))
+            }
+            ChatMessage::User(content) => contents.push(Content {
+                role: Some("user".to_string()),
+                parts: vec![Part::text(content.clone())],
+            }),
+            ChatMessage::Assistant(content) => contents.push(Content {
+                role: Some("model".to_string()),
+                parts: vec![Part::text(content.clone())],
+            }),
+            ChatMessage::ToolCalls(tool_calls) => contents.push(Content {
+                role: Some("model".to_string()),
+                parts: tool_calls
+                    .iter()
+                    .map(|tool_call| Part {
+                        function_call: Some(FunctionCall {
+                            id: Some(tool_call.id.clone()),
+                            name: tool_call.name.clone(),
+                            args: serde_json::from_str(&tool_call.args).unwrap_or(json!({})),
+                        }),
+                        ..Default::default()
+                    })
+                    .collect(),
+            }),
+            ChatMessage::ToolResult(tool_call, content) => {
+                let part = Part {
+                    function_response: Some(FunctionResponse {
+                        // Matches the response to its call when a turn has several calls
+                        id: Some(tool_call.id.clone()),
+                        name: tool_call.name.clone(),
+                        response: json!({ "result": content }),
+                    }),
+                    ..Default::default()
+                };
+
+                match contents.last_mut() {
+                    Some(last)
+                        if last
+                            .parts
+                            .iter()
+                            .all(|part| part.function_response.is_some()) =>
+                    {
+                        last.parts.push(part)
+                    }
+                    _ => contents.push(Content {
+                        role: Some("user".to_string()),
+                        parts: vec![part],
+                    }),
+                }
+            }
+            ChatMessage::Attachment(attachment) => {
+                let part = attachment_to_part(attachment);
+                match contents.last_mut() {
+                    Some(last) if last.role.as_deref() == Some("user") => last.parts.push(part),
+                    _ => contents.push(Content {
+                        role: Some("user".to_string()),
+                        parts: vec![part],
+                    }),
+                }
+            }
+        }
+    }
+
+    (system_instructions, contents)
+}
+
+/// Sends the media types Gemini reads as inline data and describes anything else.
+fn attachment_to_part(attachment: &Attachment) -> Part {
+    let mime_type = attachment.mime_type.as_str();
+    let is_media = ["image/", "audio/", "video/"]
+        .iter()
+        .any(|prefix| mime_type.starts_with(prefix))
+        || mime_type == "application/pdf";
+
+    if attachment.as_text().is_some() || !is_media {
+        return Part::text(attachment.to_string());
+    }
+
+    Part {
+        inline_data: Some(Blob {
+            mime_type: attachment.mime_type.clone(),
+            data: attachment.to_base64(),
+        }),
+        ..Default::default()
+    }
+}
+
+#[async_trait]
+impl ChatCompletion for GeminiNative {
+    async fn send(
+        &self,
+        request: &ChatCompletionRequest,
+    ) -> Result<ChatCompletionResponse, ChatCompletionError> {
+        let model = request
+            .model
+            .clone()
+            .unwrap_or(self.default_options.prompt_model.clone());
+
+        let api_key = self.config.api_key.expose_secret();
+        if api_key.is_empty() {
+            return Err(ChatCompletionError::Literal(
+                "API key for Gemini is required".to_string(),
+            ));
+        }
+
+        let mut body = self.build_request(request);
+        let mut cache_write_tokens = 0;
+        if let Some(prefix_len) = Self::cached_prefix_len(request) {
+            cache_write_tokens = self
+                .use_cached_content(&model, &mut body, prefix_len)
+                .await?;
+        }
+
+        let res = self
+            .client
+            .post(format!(
+                "{}/models/{}:generateContent",
+                self.config.api_url, model
+            ))
+            .header("x-goog-api-key", api_key)
+            .json(&body)
+            .send()
+            .await?;
+
+        let status = res.status();
+        if !status.is_success() {
+            let body = res.text().await.unwrap_or_default();
+            return Err(ChatCompletionError::Literal(format!(
+                "gemini returned {}: {}",
+                status, body
+            )));
+        }
+
+        let response: GenerateContentResponse = res
+            .json()
+            .await
+            .context("Failed to parse generateContent response")?;
+
+        let mut response = Self::parse_response(response)?;
+        if let Some(usage) = &mut response.usage {
+            usage.cache_write_tokens = cache_write_tokens;
+        }
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::*;
+    use crate::{
+        ToolDefinition,
+        providers::gemini_native::{HarmBlockThreshold, HarmCategory},
+        test_utils::{serve_json, serve_json_once},
+    };
+
+    fn request() -> ChatCompletionRequest {
+        ChatCompletionRequest {
+            model: None,
+            messages: vec![
+                ChatMessage::System("You are helpful".to_string()),
+                ChatMessage::User("What's the weather?".to_string()),
+                ChatMessage::Assistant("Let me check".to_string()),
+            ],
+            tool_definitions: vec![ToolDefinition {
+                r#type: "function".to_string(),
+                name: "weather-get_weather".to_string(),
+                description: "Get weather".to_string(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": { "location": { "type": "string" } }
+                }),
+                metadata: Default::default(),
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_build_request() {
+        let gemini = GeminiNative::new_with_options(
+            GEMINI_API_URL,
+            "key",
+            Options {
+                safety_settings: vec![SafetySetting {
+                    category: HarmCategory::Harassment,
+                    threshold: HarmBlockThreshold::BlockOnlyHigh,
+                }],
+                function_calling_mode: FunctionCallingMode::Any,
+                google_search: true,
+                temperature: Some(0.5),
+                ..Default::default()
+            },
+        );
+
+        let body = serde_json::to_value(gemini.build_request(&request())).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "contents": [
+                    { "role": "user", "parts": [{ "text": "What's the weather?" }] },
+                    { "role": "model", "parts": [{ "text": "Let me check" }] }
+                ],
+                "systemInstruction": { "parts": [{ "text": "You are helpful" }] },
+                "tools": [
+                    {
+                        "functionDeclarations": [{
+                            "name": "weather-get_weather",
+                            "description": "Get weather",
+                            "parametersJsonSchema": {
+                                "type": "object",
+                                "properties": { "location": { "type": "string" } }
+                            }
+                        }]
+                    },
+                    { "googleSearch": {} }
+                ],
+                "toolConfig": { "functionCallingConfig": { "mode": "ANY" } },
+                "safetySettings": [{
+                    "category": "HARM_CATEGORY_HARASSMENT",
+                    "threshold": "BLOCK_ONLY_HIGH"
+                }],
+                "generationConfig": { "temperature": 0.5 }
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send_without_api_key() {
+        let gemini = GeminiNative::new(GEMINI_API_URL, "");
+        let err = gemini.send(&request()).await.unwrap_err();
+        assert!(matches!(err, ChatCompletionError::Literal(_)));
+    }
+
+    #[test]
+    fn test_tool_messages_to_contents() {
+        let call = |id: &str, location: &str| ToolCall {
+            id: id.to_string(),
+            name: "weather-get_weather".to_string(),
+            args: json!({ "location": location }).to_string(),
+        };
+
+        let (_, contents) = to_contents(&[
+            ChatMessage::User("Weather in Paris and Hanoi?".to_string()),
+            ChatMessage::ToolCalls(vec![call("call_0", "Paris"), call("call_1", "Hanoi")]),
+            ChatMessage::ToolResult(call("call_0", "Paris"), "Sunny".to_string()),
+            ChatMessage::ToolResult(call("call_1", "Hanoi"), "Rainy".to_string()),
+        ]);
+
+        assert_eq!(
+            serde_json::to_value(&contents[1..]).unwrap(),
+            json!([
+                {
+                    "role": "model",
+                    "parts": [
+                        { "functionCall": {
+                            "id": "call_0",
+                            "name": "weather-get_weather",
+                            "args": { "location": "Paris" }
+                        } },
+                        { "functionCall": {
+                            "id": "call_1",
+                            "name": "weather-get_weather",
+                            "args": { "location": "Hanoi" }
+                        } }
+                    ]
+                },
+                {
+                    "role": "user",
+                    "parts": [
+                        { "functionResponse": {
+                            "id": "call_0",
+                            "name": "weather-get_weather",
+                            "response": { "result": "Sunny" }
+                        } },
+                        { "functionResponse": {
+                            "id": "call_1",
+                            "name": "weather-get_weather",
+                            "response": { "result": "Rainy" }
+                        } }
+                    ]
+                }
+            ])
+        );
+    }
+
+    #[test]
+    fn test_attachments_to_contents() {
+        let call = ToolCall {
+            id: "call_0".to_string(),
+            name: "camera-snapshot".to_string(),
+            args: "{}".to_string(),
+        };
+        let image = Attachment::new("image/png", vec![1, 2, 3]);
+        let archive = Attachment::new("application/zip", vec![1, 2, 3]);
+
+        let (_, contents) = to_contents(&[
+            ChatMessage::ToolCalls(vec![call.clone()]),
+            ChatMessage::ToolResult(call, image.to_string()),
+            ChatMessage::Attachment(image),
+            ChatMessage::Attachment(archive),
+        ]);
+
+        assert_eq!(contents.len(), 2);
+        assert_eq!(
+            serde_json::to_value(&contents[1].parts[1..]).unwrap(),
+            json!([
+                { "inlineData": { "mimeType": "image/png", "data": "AQID" } },
+                { "text": "<application/zip attachment, 3 bytes>" }
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_blocked_response() {
+        let response: GenerateContentResponse = serde_json::from_value(json!({
+            "promptFeedback": { "blockReason": "SAFETY" }
+        }))
+        .unwrap();
+
+        let err = GeminiNative::parse_response(response).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "unexpected error: gemini blocked the prompt: SAFETY"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send_against_stub() {
+        let (url, server) = serve_json_once(
+            json!({
+                "candidates": [{
+                    "content": {
+                        "role": "model",
+                        "parts": [
+                            { "text": "Checking the weather" },
+                            { "functionCall": {
+                                "name": "weather-get_weather",
+                                "args": { "location": "Paris" }
+                            } }
+                        ]
+                    },
+                    "finishReason": "STOP"
+                }],
+                "usageMetadata": {
+                    "promptTokenCount": 12,
+                    "candidatesTokenCount": 8,
+                    "totalTokenCount": 20
+                }
+            })
+            .to_string(),
+        );
+        let api_url = format!("{}/v1beta", url);
+
+        let gemini = GeminiNative::new(&api_url, "test-key");
+        let response = gemini.send(&request()).await.unwrap();
+
+        let raw_request = server.join().unwrap();
+        assert!(raw_request.starts_with("POST /v1beta/models/gemini-1.5-flash:generateContent"));
+        assert!(raw_request.contains("x-goog-api-key: test-key"));
+
+        assert!(matches!(
+            response.messages.as_slice(),
+            [ChatMessage::Assistant(text)] if text == "Checking the weather"
+        ));
+        assert_eq!(response.tool_calls.len(), 1);
+        assert_eq!(response.tool_calls[0].name, "weather-get_weather");
+        assert_eq!(
+            serde_json::from_str::<Value>(&response.tool_calls[0].args).unwrap(),
+            json!({ "location": "Paris" })
+        );
+        assert_eq!(response.usage.unwrap().total_tokens, 20);
+    }
+
+    #[tokio::test]
+    async fn test_send_with_cache_breakpoints() {
+        let response = json!({
+            "candidates": [{
+                "content": { "role": "model", "parts": [{ "text": "Sunny" }] },
+                "finishReason": "STOP"
+            }],
+            "usageMetadata": {
+                "promptTokenCount": 4100,
+                "candidatesTokenCount": 2,
+                "totalTokenCount": 4102,
+                "cachedContentTokenCount": 4096
+            }
+        })
+        .to_string();
+        let (url, server) = serve_json(vec![
+            json!({
+                "name": "cachedContents/abc123",
+                "usageMetadata": { "totalTokenCount": 4096 }
+            })
+            .to_string(),
+            response.clone(),
+            response,
+        ]);
+
+        let gemini = GeminiNative::new(&url, "test-key");
+        let request = ChatCompletionRequest {
+            cache_breakpoints: vec![CacheBreakpoint::Tools, CacheBreakpoint::Message(1)],
+            ..request()
+        };
+        let first = gemini.send(&request).await.unwrap();
+        let second = gemini.send(&request).await.unwrap();
+
+        let raw_requests = server.join().unwrap();
+        assert_eq!(raw_requests.len(), 3);
+        assert!(raw_requests[0].starts_with("POST /cachedContents"));
+        assert!(
+            raw_requests[0]
+                .contains(r#""systemInstruction":{"parts":[{"text":"You are helpful"}]}"#)
+        );
+        assert!(raw_requests[0].contains(r#""ttl":"300s""#));
+
+        // Both requests reuse the cache and only send the contents after the breakpoint
+        for raw_request in &raw_requests[1..] {
+            let body: Value =
+                serde_json::from_str(raw_request.split_once("\r\n\r\n").unwrap().1).unwrap();
+            assert_eq!(
+                body,
+                json!({
+                    "cachedContent": "cachedContents/abc123",
+                    "contents": [{ "role": "model", "parts": [{ "text": "Let me check" }] }]
+                })
+            );
+        }
+
+        let usage = first.usage.unwrap();
+        assert_eq!(usage.cache_read_tokens, 4096);
+        assert_eq!(usage.cache_write_tokens, 4096);
+        assert_eq!(second.usage.unwrap().cache_write_tokens, 0);
+        assert_eq!(second.messages.len(), 1);
+    }
+}
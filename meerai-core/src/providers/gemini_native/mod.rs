@@ -0,0 +1,5 @@
+mod client;
+mod types;
+
+pub use client::{GeminiNative, GeminiNativeConfig, Options};
+pub use types::{FunctionCallingMode, HarmBlockThreshold, HarmCategory, SafetySetting};
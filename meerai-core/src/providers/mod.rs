@@ -1,2 +1,4 @@
 pub mod gemini;
+pub mod gemini_native;
+pub mod ollama;
 pub mod openrouter;
@@ -0,0 +1,499 @@
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    ToolCall, ToolDefinition, async_trait,
+    chat_completion::{
+        ChatCompletion, ChatCompletionError, ChatCompletionRequest, ChatCompletionResponse,
+        ChatMessage, Usage,
+    },
+};
+
+const OLLAMA_API_URL: &str = "http://localhost:11434";
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct OllamaConfig {
+    api_url: String,
+}
+
+impl Default for OllamaConfig {
+    fn default() -> Self {
+        Self {
+            // OLLAMA_HOST is commonly set without a scheme, e.g. `0.0.0.0:11434`
+            api_url: match std::env::var("OLLAMA_HOST") {
+                Ok(host) if host.starts_with("http") => host,
+                Ok(host) => format!("http://{}", host),
+                Err(_) => OLLAMA_API_URL.to_string(),
+            },
+        }
+    }
+}
+
+/// Model parameters sent in the `options` field of a request.
+///
+/// [Ollama Modelfile Reference](https://github.com/ollama/ollama/blob/main/docs/modelfile.md#valid-parameters-and-values)
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct ModelOptions {
+    /// Size of the context window in tokens
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_ctx: Option<u32>,
+
+    /// Maximum number of tokens to predict, `-1` for infinite generation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_predict: Option<i32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<u32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repeat_penalty: Option<f32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<i64>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub stop: Vec<String>,
+}
+
+impl ModelOptions {
+    fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Options {
+    pub prompt_model: String,
+
+    /// How long the model stays loaded after the request, e.g. `5m`, `1h` or `-1` to keep it
+    /// loaded indefinitely
+    pub keep_alive: Option<String>,
+
+    pub model_options: ModelOptions,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            prompt_model: "llama3.1".to_string(),
+            keep_alive: None,
+            model_options: ModelOptions::default(),
+        }
+    }
+}
+
+/// A model installed in the local Ollama instance, as listed by `/api/tags`.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct OllamaModel {
+    pub name: String,
+
+    #[serde(default)]
+    pub modified_at: String,
+
+    /// Size on disk in bytes
+    #[serde(default)]
+    pub size: u64,
+
+    #[serde(default)]
+    pub digest: String,
+
+    #[serde(default)]
+    pub details: OllamaModelDetails,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct OllamaModelDetails {
+    pub format: String,
+    pub family: String,
+    pub parameter_size: String,
+    pub quantization_level: String,
+}
+
+/// Ollama provider using the native `/api/chat` API.
+#[derive(Debug, Clone)]
+pub struct Ollama {
+    pub client: reqwest::Client,
+    pub config: OllamaConfig,
+    pub default_options: Options,
+}
+
+impl Default for Ollama {
+    fn default() -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            config: OllamaConfig::default(),
+            default_options: Options::default(),
+        }
+    }
+}
+
+impl Ollama {
+    pub fn new(api_url: &str) -> Self {
+        Self::new_with_options(api_url, Options::default())
+    }
+
+    pub fn new_with_options(api_url: &str, options: Options) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            config: OllamaConfig {
+                api_url: api_url.trim_end_matches('/').to_string(),
+            },
+            default_options: options,
+        }
+    }
+
+    pub fn set_default_options(&mut self, options: Options) {
+        self.default_options = options;
+    }
+
+    /// Lists the models installed in the local Ollama instance.
+    pub async fn list_models(&self) -> Result<Vec<OllamaModel>, ChatCompletionError> {
+        let res: TagsResponse = self
+            .client
+            .get(format!("{}/api/tags", self.config.api_url))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(res.models)
+    }
+
+    fn build_request(&self, request: &ChatCompletionRequest) -> ChatRequest {
+        let options = &self.default_options;
+
+        ChatRequest {
+            model: request
+                .model
+                .clone()
+                .unwrap_or(options.prompt_model.clone()),
+            messages: request.messages.iter().map(Message::from).collect(),
+            tools: request.tool_definitions.iter().map(Tool::from).collect(),
+            stream: false,
+            keep_alive: options.keep_alive.clone(),
+            options: (!options.model_options.is_empty()).then(|| options.model_options.clone()),
+        }
+    }
+
+    fn parse_response(response: ChatResponse) -> ChatCompletionResponse {
+        let ChatResponse {
+            message,
+            prompt_eval_count,
+            eval_count,
+        } = response;
+
+        ChatCompletionResponse {
+            messages: if message.content.is_empty() {
+                vec![]
+            } else {
+                vec![ChatMessage::Assistant(message.content)]
+            },
+            tool_calls: message
+                .tool_calls
+                .into_iter()
+                .enumerate()
+                // Ollama doesn't return call IDs
+                .map(|(index, tool_call)| ToolCall {
+                    id: format!("call_{}", index),
+                    name: tool_call.function.name,
+                    args: tool_call.function.arguments.to_string(),
+                })
+                .collect(),
+            usage: Some(Usage {
+                prompt_tokens: prompt_eval_count,
+                completion_tokens: eval_count,
+                total_tokens: prompt_eval_count + eval_count,
+                ..Default::default()
+            }),
+        }
+    }
+}
+
+#[async_trait]
+impl ChatCompletion for Ollama {
+    async fn send(
+        &self,
+        request: &ChatCompletionRequest,
+    ) -> Result<ChatCompletionResponse, ChatCompletionError> {
+        let res = self
+            .client
+            .post(format!("{}/api/chat", self.config.api_url))
+            .json(&self.build_request(request))
+            .send()
+            .await?;
+
+        let status = res.status();
+        if !status.is_success() {
+            let body = res.text().await.unwrap_or_default();
+            return Err(ChatCompletionError::Literal(format!(
+                "ollama returned {}: {}",
+                status, body
+            )));
+        }
+
+        let response: ChatResponse = res.json().await.context("Failed to parse chat response")?;
+
+        Ok(Self::parse_response(response))
+    }
+}
+
+// [Ollama API Reference](https://github.com/ollama/ollama/blob/main/docs/api.md)
+
+#[derive(Debug, Serialize)]
+struct ChatRequest {
+    model: String,
+    messages: Vec<Message>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<Tool>,
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    keep_alive: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    options: Option<ModelOptions>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Message {
+    role: String,
+    #[serde(default)]
+    content: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tool_calls: Vec<OllamaToolCall>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tool_name: Option<String>,
+    /// Base64 encoded images for multimodal models
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    images: Vec<String>,
+}
+
+impl Message {
+    fn new(role: &str, content: &str) -> Self {
+        Self {
+            role: role.to_string(),
+            content: content.to_string(),
+            tool_calls: vec![],
+            tool_name: None,
+            images: vec![],
+        }
+    }
+}
+
+impl From<&ChatMessage> for Message {
+    fn from(message: &ChatMessage) -> Self {
+        match message {
+            ChatMessage::Assistant(content) => Self::new("assistant", content),
+            ChatMessage::Developer(content) | ChatMessage::System(content) => {
+                Self::new("system", content)
+            }
+            ChatMessage::User(content) => Self::new("user", content),
+            ChatMessage::ToolCalls(tool_calls) => Self {
+                tool_calls: tool_calls
+                    .iter()
+                    .map(|tool_call| OllamaToolCall {
+                        function: OllamaFunctionCall {
+                            name: tool_call.name.clone(),
+                            arguments: serde_json::from_str(&tool_call.args)
+                                .unwrap_or(Value::Object(Default::default())),
+                        },
+                    })
+                    .collect(),
+                ..Self::new("assistant", "")
+            },
+            ChatMessage::ToolResult(tool_call, content) => Self {
+                tool_name: Some(tool_call.name.clone()),
+                ..Self::new("tool", content)
+            },
+            // Ollama reads images only, other media is described
+            ChatMessage::Attachment(attachment) if attachment.mime_type.starts_with("image/") => {
+                Self {
+                    images: vec![attachment.to_base64()],
+                    ..Self::new("user", "")
+                }
+            }
+            ChatMessage::Attachment(attachment) => Self::new("user", &attachment.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct OllamaToolCall {
+    function: OllamaFunctionCall,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct OllamaFunctionCall {
+    name: String,
+    #[serde(default)]
+    arguments: Value,
+}
+
+#[derive(Debug, Serialize)]
+struct Tool {
+    r#type: String,
+    function: ToolFunction,
+}
+
+#[derive(Debug, Serialize)]
+struct ToolFunction {
+    name: String,
+    description: String,
+    parameters: Value,
+}
+
+impl From<&ToolDefinition> for Tool {
+    fn from(definition: &ToolDefinition) -> Self {
+        Self {
+            r#type: "function".to_string(),
+            function: ToolFunction {
+                name: definition.name.clone(),
+                description: definition.description.clone(),
+                parameters: definition.parameters.clone(),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatResponse {
+    message: Message,
+    #[serde(default)]
+    prompt_eval_count: u64,
+    #[serde(default)]
+    eval_count: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct TagsResponse {
+    #[serde(default)]
+    models: Vec<OllamaModel>,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{Attachment, test_utils::serve_json_once};
+
+    #[test]
+    fn test_build_request() {
+        let ollama = Ollama::new_with_options(
+            OLLAMA_API_URL,
+            Options {
+                prompt_model: "qwen2.5:7b".to_string(),
+                keep_alive: Some("10m".to_string()),
+                model_options: ModelOptions {
+                    num_ctx: Some(32768),
+                    temperature: Some(0.5),
+                    ..Default::default()
+                },
+            },
+        );
+
+        let request = ChatCompletionRequest {
+            model: None,
+            messages: vec![
+                ChatMessage::System("You are helpful".to_string()),
+                ChatMessage::User("Hi".to_string()),
+                ChatMessage::Attachment(Attachment::new("image/png", vec![1, 2, 3])),
+            ],
+            tool_definitions: vec![ToolDefinition {
+                r#type: "function".to_string(),
+                name: "weather-get_weather".to_string(),
+                description: "Get weather".to_string(),
+                parameters: json!({ "type": "object", "properties": {} }),
+                metadata: Default::default(),
+            }],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            serde_json::to_value(ollama.build_request(&request)).unwrap(),
+            json!({
+                "model": "qwen2.5:7b",
+                "messages": [
+                    { "role": "system", "content": "You are helpful" },
+                    { "role": "user", "content": "Hi" },
+                    { "role": "user", "content": "", "images": ["AQID"] }
+                ],
+                "tools": [{
+                    "type": "function",
+                    "function": {
+                        "name": "weather-get_weather",
+                        "description": "Get weather",
+                        "parameters": { "type": "object", "properties": {} }
+                    }
+                }],
+                "stream": false,
+                "keep_alive": "10m",
+                "options": { "num_ctx": 32768, "temperature": 0.5 }
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_response_with_tool_calls() {
+        let response: ChatResponse = serde_json::from_value(json!({
+            "model": "qwen2.5:7b",
+            "created_at": "2025-04-01T00:00:00Z",
+            "message": {
+                "role": "assistant",
+                "content": "",
+                "tool_calls": [{
+                    "function": {
+                        "name": "weather-get_weather",
+                        "arguments": { "location": "Hanoi" }
+                    }
+                }]
+            },
+            "done": true,
+            "prompt_eval_count": 30,
+            "eval_count": 12
+        }))
+        .unwrap();
+
+        let response = Ollama::parse_response(response);
+        assert!(response.messages.is_empty());
+        assert_eq!(response.tool_calls.len(), 1);
+        assert_eq!(response.tool_calls[0].args, r#"{"location":"Hanoi"}"#);
+        assert_eq!(response.usage.unwrap().total_tokens, 42);
+    }
+
+    #[tokio::test]
+    async fn test_list_models() {
+        let (url, server) = serve_json_once(
+            json!({
+                "models": [{
+                    "name": "llama3.1:latest",
+                    "model": "llama3.1:latest",
+                    "modified_at": "2025-03-28T10:00:00Z",
+                    "size": 4920753328u64,
+                    "digest": "46e0c10c039e",
+                    "details": {
+                        "format": "gguf",
+                        "family": "llama",
+                        "parameter_size": "8.0B",
+                        "quantization_level": "Q4_K_M"
+                    }
+                }]
+            })
+            .to_string(),
+        );
+
+        let models = Ollama::new(&url).list_models().await.unwrap();
+
+        assert!(server.join().unwrap().starts_with("GET /api/tags"));
+        assert_eq!(models.len(), 1);
+        assert_eq!(models[0].name, "llama3.1:latest");
+        assert_eq!(models[0].details.parameter_size, "8.0B");
+    }
+}
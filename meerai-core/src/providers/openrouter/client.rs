@@ -1,16 +1,21 @@
 use anyhow::{Context, Result};
-use async_openai::types::{ChatCompletionRequestMessage, CreateChatCompletionRequestArgs, Role};
+use async_openai::types::{
+    ChatCompletionRequestMessage, CreateChatCompletionRequestArgs, CreateChatCompletionResponse,
+    Role,
+};
 use reqwest::header::HeaderMap;
 use secrecy::{ExposeSecret, SecretString};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
 use std::sync::Arc;
 
 use crate::{
     ToolCall, async_trait,
     chat_completion::{
-        ChatCompletion, ChatCompletionError, ChatCompletionRequest, ChatCompletionResponse,
-        ChatMessage, message_to_openai,
+        CacheBreakpoint, ChatCompletion, ChatCompletionError, ChatCompletionRequest,
+        ChatCompletionResponse, ChatMessage, Usage, message_to_openai,
     },
+    model_catalog::{ModelCatalog, ModelCatalogError},
 };
 
 const OPENROUTER_API_URL: &str = "https://openrouter.ai/api/v1";
@@ -81,16 +86,88 @@ impl async_openai::config::Config for OpenRouterConfig {
 #[derive(Debug, Clone)]
 pub struct Options {
     pub prompt_model: String,
+
+    /// Models to try, in order, when `prompt_model` is unavailable or refuses the request
+    pub models: Vec<String>,
+
+    /// Preferences used by OpenRouter to pick a provider for the model
+    pub provider: Option<ProviderPreferences>,
+
+    /// Prompt transforms applied by OpenRouter, e.g. `middle-out`
+    pub transforms: Vec<String>,
+
+    /// Whether to request usage accounting (token counts and cost) in the response
+    pub usage: bool,
 }
 
 impl Default for Options {
     fn default() -> Self {
         Self {
             prompt_model: "meta-llama/llama-4-maverick".to_string(),
+            models: vec![],
+            provider: None,
+            transforms: vec![],
+            usage: false,
+        }
+    }
+}
+
+impl Options {
+    /// Builds the OpenRouter-only fields that are merged into the request body.
+    ///
+    /// [OpenRouter API Reference](https://openrouter.ai/docs/api-reference/overview#request)
+    fn extensions(&self) -> Map<String, Value> {
+        let mut extensions = Map::new();
+
+        if !self.models.is_empty() {
+            extensions.insert("models".to_string(), json!(self.models));
         }
+
+        if let Some(provider) = &self.provider {
+            extensions.insert("provider".to_string(), json!(provider));
+        }
+
+        if !self.transforms.is_empty() {
+            extensions.insert("transforms".to_string(), json!(self.transforms));
+        }
+
+        if self.usage {
+            extensions.insert("usage".to_string(), json!({ "include": true }));
+        }
+
+        extensions
     }
 }
 
+/// Provider routing preferences.
+///
+/// [OpenRouter Provider Routing](https://openrouter.ai/docs/features/provider-routing)
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ProviderPreferences {
+    /// Provider slugs to try in order, e.g. `["anthropic", "openai"]`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub order: Vec<String>,
+
+    /// Whether to fall back to other providers when the preferred ones are unavailable
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow_fallbacks: Option<bool>,
+
+    /// Whether providers that may store or train on the data can be used
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_collection: Option<DataCollection>,
+
+    /// Quantization levels to restrict to, e.g. `["fp8", "bf16"]`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub quantizations: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DataCollection {
+    Allow,
+    Deny,
+}
+
 #[derive(Debug, Clone)]
 pub struct OpenRouter {
     pub client: Arc<async_openai::Client<OpenRouterConfig>>,
@@ -108,6 +185,17 @@ impl Default for OpenRouter {
     }
 }
 
+impl OpenRouter {
+    pub fn set_default_options(&mut self, options: Options) {
+        self.default_options = options;
+    }
+
+    /// Fetches metadata for every model available through OpenRouter.
+    pub async fn fetch_model_catalog(&self) -> Result<ModelCatalog, ModelCatalogError> {
+        ModelCatalog::fetch(self.client.config()).await
+    }
+}
+
 #[async_trait]
 impl ChatCompletion for OpenRouter {
     async fn send(
@@ -143,7 +231,17 @@ impl ChatCompletion for OpenRouter {
         };
 
         let req = openai_request.tool_choice("auto").tools(tools).build()?;
-        let res = self.client.chat().create(req).await?;
+
+        let mut body = serde_json::to_value(req).context("Failed to serialize request")?;
+        if let Value::Object(body) = &mut body {
+            body.extend(self.default_options.extensions());
+            apply_cache_breakpoints(body, &request.cache_breakpoints);
+        }
+
+        let OpenRouterResponse {
+            completion: res,
+            usage,
+        } = self.client.chat().create_byot(body).await?;
 
         let chat_completion_response: ChatCompletionResponse = ChatCompletionResponse {
             messages: res
@@ -169,13 +267,263 @@ impl ChatCompletion for OpenRouter {
                 .unwrap_or_default()
                 .iter()
                 .map(|tool_call| ToolCall {
-                    // id: tool_call.id.clone(),
+                    id: tool_call.id.clone(),
                     name: tool_call.function.name.clone(),
                     args: tool_call.function.arguments.clone(),
                 })
                 .collect::<Vec<ToolCall>>(),
+            usage: usage.map(Usage::from),
         };
 
         Ok(chat_completion_response)
     }
 }
+
+/// Adds Anthropic style `cache_control` markers to the request body.
+///
+/// Providers with automatic prompt caching ignore the markers.
+/// [OpenRouter Prompt Caching](https://openrouter.ai/docs/features/prompt-caching)
+fn apply_cache_breakpoints(body: &mut Map<String, Value>, breakpoints: &[CacheBreakpoint]) {
+    let cache_control = json!({ "type": "ephemeral" });
+
+    for breakpoint in breakpoints {
+        match breakpoint {
+            CacheBreakpoint::Tools => {
+                if let Some(tool) = body
+                    .get_mut("tools")
+                    .and_then(Value::as_array_mut)
+                    .and_then(|tools| tools.last_mut())
+                    .and_then(Value::as_object_mut)
+                {
+                    tool.insert("cache_control".to_string(), cache_control.clone());
+                }
+            }
+            CacheBreakpoint::Message(index) => {
+                let Some(message) = body
+                    .get_mut("messages")
+                    .and_then(|messages| messages.get_mut(*index))
+                    .and_then(Value::as_object_mut)
+                else {
+                    continue;
+                };
+
+                // cache_control is only accepted on content parts, not on plain string content
+                let part = match message.get_mut("content") {
+                    Some(Value::String(text)) => {
+                        let part = json!({ "type": "text", "text": text });
+                        message.insert("content".to_string(), json!([part]));
+                        message["content"][0].as_object_mut()
+                    }
+                    Some(Value::Array(parts)) => parts.last_mut().and_then(Value::as_object_mut),
+                    _ => None,
+                };
+
+                if let Some(part) = part {
+                    part.insert("cache_control".to_string(), cache_control.clone());
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenRouterResponse {
+    #[serde(flatten)]
+    completion: CreateChatCompletionResponse,
+
+    usage: Option<OpenRouterUsage>,
+}
+
+// Usage accounting adds the cost of the request in credits (USD) to the usual token counts
+#[derive(Debug, Deserialize)]
+struct OpenRouterUsage {
+    prompt_tokens: u64,
+    completion_tokens: u64,
+    total_tokens: u64,
+    cost: Option<f64>,
+    prompt_tokens_details: Option<OpenRouterPromptTokensDetails>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenRouterPromptTokensDetails {
+    cached_tokens: Option<u64>,
+    cache_write_tokens: Option<u64>,
+}
+
+impl From<OpenRouterUsage> for Usage {
+    fn from(usage: OpenRouterUsage) -> Self {
+        Self {
+            prompt_tokens: usage.prompt_tokens,
+            completion_tokens: usage.completion_tokens,
+            total_tokens: usage.total_tokens,
+            cache_read_tokens: usage
+                .prompt_tokens_details
+                .as_ref()
+                .and_then(|details| details.cached_tokens)
+                .unwrap_or_default(),
+            cache_write_tokens: usage
+                .prompt_tokens_details
+                .and_then(|details| details.cache_write_tokens)
+                .unwrap_or_default(),
+            cost: usage.cost,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Attachment;
+
+    #[test]
+    fn test_default_options_have_no_extensions() {
+        assert!(Options::default().extensions().is_empty());
+    }
+
+    #[test]
+    fn test_options_extensions() {
+        let options = Options {
+            models: vec![
+                "anthropic/claude-3.5-sonnet".to_string(),
+                "openai/gpt-4o".to_string(),
+            ],
+            provider: Some(ProviderPreferences {
+                order: vec!["together".to_string()],
+                allow_fallbacks: Some(false),
+                data_collection: Some(DataCollection::Deny),
+                quantizations: vec![],
+            }),
+            transforms: vec!["middle-out".to_string()],
+            usage: true,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            Value::Object(options.extensions()),
+            json!({
+                "models": ["anthropic/claude-3.5-sonnet", "openai/gpt-4o"],
+                "provider": {
+                    "order": ["together"],
+                    "allow_fallbacks": false,
+                    "data_collection": "deny"
+                },
+                "transforms": ["middle-out"],
+                "usage": { "include": true }
+            })
+        );
+    }
+
+    #[test]
+    fn test_apply_cache_breakpoints() {
+        let mut body = json!({
+            "messages": [
+                { "role": "system", "content": "You are helpful" },
+                { "role": "user", "content": "Hi" }
+            ],
+            "tools": [
+                { "type": "function", "function": { "name": "a" } },
+                { "type": "function", "function": { "name": "b" } }
+            ]
+        });
+
+        apply_cache_breakpoints(
+            body.as_object_mut().unwrap(),
+            &[CacheBreakpoint::Tools, CacheBreakpoint::Message(0)],
+        );
+
+        assert_eq!(
+            body,
+            json!({
+                "messages": [
+                    {
+                        "role": "system",
+                        "content": [{
+                            "type": "text",
+                            "text": "You are helpful",
+                            "cache_control": { "type": "ephemeral" }
+                        }]
+                    },
+                    { "role": "user", "content": "Hi" }
+                ],
+                "tools": [
+                    { "type": "function", "function": { "name": "a" } },
+                    {
+                        "type": "function",
+                        "function": { "name": "b" },
+                        "cache_control": { "type": "ephemeral" }
+                    }
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn test_attachment_messages() {
+        let message = |mime_type: &str| {
+            let attachment = Attachment::new(mime_type, vec![1, 2, 3]);
+            serde_json::to_value(message_to_openai(&ChatMessage::Attachment(attachment)).unwrap())
+                .unwrap()
+        };
+
+        assert_eq!(
+            message("image/png"),
+            json!({
+                "role": "user",
+                "content": [{
+                    "type": "image_url",
+                    "image_url": { "url": "data:image/png;base64,AQID", "detail": "auto" }
+                }]
+            })
+        );
+        assert_eq!(
+            message("audio/wav"),
+            json!({
+                "role": "user",
+                "content": [{
+                    "type": "input_audio",
+                    "input_audio": { "data": "AQID", "format": "wav" }
+                }]
+            })
+        );
+        assert_eq!(
+            message("video/mp4"),
+            json!({ "role": "user", "content": "<video/mp4 attachment, 3 bytes>" })
+        );
+    }
+
+    #[test]
+    fn test_response_with_usage_accounting() {
+        let response: OpenRouterResponse = serde_json::from_value(json!({
+            "id": "gen-123",
+            "object": "chat.completion",
+            "created": 1743600000,
+            "model": "meta-llama/llama-4-maverick",
+            "choices": [{
+                "index": 0,
+                "message": { "role": "assistant", "content": "Hello" },
+                "finish_reason": "stop"
+            }],
+            "usage": {
+                "prompt_tokens": 10,
+                "completion_tokens": 2,
+                "total_tokens": 12,
+                "cost": 0.0000029,
+                "prompt_tokens_details": { "cached_tokens": 8, "cache_write_tokens": 4 }
+            }
+        }))
+        .unwrap();
+
+        assert_eq!(response.completion.choices.len(), 1);
+        assert_eq!(
+            response.usage.map(Usage::from),
+            Some(Usage {
+                prompt_tokens: 10,
+                completion_tokens: 2,
+                total_tokens: 12,
+                cache_read_tokens: 8,
+                cache_write_tokens: 4,
+                cost: Some(0.0000029),
+            })
+        );
+    }
+}
@@ -1,3 +1,3 @@
 mod client;
 
-pub use client::{OpenRouter, OpenRouterConfig, Options};
+pub use client::{DataCollection, OpenRouter, OpenRouterConfig, Options, ProviderPreferences};
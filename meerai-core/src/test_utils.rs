@@ -0,0 +1,135 @@
+use std::{
+    io::{Read, Write},
+    net::{TcpListener, TcpStream},
+    thread::JoinHandle,
+};
+
+/// Serves a single HTTP request with a JSON `body` on a random local port.
+///
+/// Returns the base URL of the stub and a handle resolving to the raw request it received.
+pub fn serve_json_once(body: String) -> (String, JoinHandle<String>) {
+    let (url, handle) = serve_json(vec![body]);
+    (
+        url,
+        std::thread::spawn(move || handle.join().unwrap().remove(0)),
+    )
+}
+
+/// Serves one HTTP request per JSON body, in order, on a random local port.
+///
+/// Returns the base URL of the stub and a handle resolving to the raw requests it received.
+pub fn serve_json(bodies: Vec<String>) -> (String, JoinHandle<Vec<String>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+
+    let handle = std::thread::spawn(move || {
+        bodies
+            .into_iter()
+            .map(|body| {
+                let (mut stream, _) = listener.accept().unwrap();
+                let raw_request = read_http_request(&mut stream);
+                write_http_response(&mut stream, &StubResponse::json(200, body));
+                raw_request
+            })
+            .collect()
+    });
+
+    (url, handle)
+}
+
+/// A response of a stub server.
+#[derive(Clone, Debug)]
+pub struct StubResponse {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: String,
+
+    /// Headers sent besides the content type and length
+    pub headers: Vec<(&'static str, String)>,
+}
+
+impl StubResponse {
+    pub fn new(status: u16, content_type: &'static str, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            content_type,
+            body: body.into(),
+            headers: vec![],
+        }
+    }
+
+    pub fn json(status: u16, body: impl Into<String>) -> Self {
+        Self::new(status, "application/json", body)
+    }
+
+    /// Answers with a redirect to `location`.
+    pub fn redirect(status: u16, location: impl Into<String>) -> Self {
+        Self {
+            headers: vec![("location", location.into())],
+            ..Self::new(status, "text/plain", "")
+        }
+    }
+}
+
+/// Answers every request with the response `handler` returns for the raw request, on a random
+/// local port and until the test process exits.
+///
+/// Returns the base URL of the stub.
+pub fn serve_with(handler: impl Fn(&str) -> StubResponse + Send + 'static) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let raw_request = read_http_request(&mut stream);
+            write_http_response(&mut stream, &handler(&raw_request));
+        }
+    });
+
+    url
+}
+
+fn write_http_response(stream: &mut TcpStream, response: &StubResponse) {
+    let reason = reqwest::StatusCode::from_u16(response.status)
+        .ok()
+        .and_then(|status| status.canonical_reason())
+        .unwrap_or_default();
+    let headers: String = response
+        .headers
+        .iter()
+        .map(|(name, value)| format!("{}: {}\r\n", name, value))
+        .collect();
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\ncontent-type: {}\r\ncontent-length: {}\r\n{}connection: close\r\n\r\n{}",
+        response.status,
+        reason,
+        response.content_type,
+        response.body.len(),
+        headers,
+        response.body
+    )
+    .unwrap();
+}
+
+fn read_http_request(stream: &mut TcpStream) -> String {
+    let mut raw = Vec::new();
+    let mut buf = [0; 4096];
+    loop {
+        let len = stream.read(&mut buf).unwrap();
+        raw.extend_from_slice(&buf[..len]);
+
+        let text = String::from_utf8_lossy(&raw).to_string();
+        if let Some((head, body)) = text.split_once("\r\n\r\n") {
+            let content_length = head
+                .lines()
+                .find_map(|line| line.strip_prefix("content-length: "))
+                .and_then(|len| len.parse::<usize>().ok())
+                .unwrap_or(0);
+            if len == 0 || body.len() >= content_length {
+                return text;
+            }
+        }
+    }
+}
@@ -0,0 +1,318 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use serde_json::Value;
+
+use crate::{ToolDefinition, ToolError, ToolOutput, Toolset, async_trait};
+
+/// Configuration for the CachedToolset.
+#[derive(Debug, Clone)]
+pub struct ToolCacheConfig {
+    /// How long a result is reused
+    pub ttl: Duration,
+
+    /// TTL of single functions, overriding `ttl`
+    pub function_ttls: HashMap<String, Duration>,
+
+    /// Maximum number of results kept, the oldest are dropped first
+    pub max_entries: usize,
+
+    /// Also caches functions marked as side-effecting, whose repeated calls are then skipped
+    pub cache_side_effecting: bool,
+}
+
+impl Default for ToolCacheConfig {
+    fn default() -> Self {
+        Self {
+            ttl: Duration::from_secs(5 * 60),
+            function_ttls: HashMap::new(),
+            max_entries: 1000,
+            cache_side_effecting: false,
+        }
+    }
+}
+
+struct CacheEntry {
+    output: ToolOutput,
+    expires_at: Instant,
+    inserted_at: Instant,
+}
+
+/// Memoizes the successful results of a toolset by function name and arguments.
+///
+/// Arguments are compared as canonical JSON, so formatting and the order of object keys do not
+/// matter. Errors and failed calls are never cached.
+pub struct CachedToolset<T> {
+    inner: T,
+    config: ToolCacheConfig,
+
+    /// Functions that are passed through on every call
+    uncached: HashSet<String>,
+
+    entries: Mutex<HashMap<(String, String), CacheEntry>>,
+}
+
+impl<T: Toolset> CachedToolset<T> {
+    pub fn new(inner: T) -> Self {
+        Self::new_with_config(inner, ToolCacheConfig::default())
+    }
+
+    pub fn new_with_config(inner: T, config: ToolCacheConfig) -> Self {
+        let uncached = inner
+            .definition()
+            .into_iter()
+            .filter(|definition| definition.metadata.side_effecting && !config.cache_side_effecting)
+            .map(|definition| definition.name)
+            .collect();
+
+        Self {
+            inner,
+            config,
+            uncached,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Drops every cached result.
+    pub fn clear(&self) {
+        self.entries.lock().expect("cache lock").clear();
+    }
+
+    fn get(&self, key: &(String, String)) -> Option<ToolOutput> {
+        let mut entries = self.entries.lock().expect("cache lock");
+        match entries.get(key) {
+            Some(entry) if entry.expires_at > Instant::now() => Some(entry.output.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn insert(&self, key: (String, String), output: ToolOutput) {
+        if self.config.max_entries == 0 {
+            return;
+        }
+
+        let now = Instant::now();
+        let ttl = self
+            .config
+            .function_ttls
+            .get(&key.0)
+            .copied()
+            .unwrap_or(self.config.ttl);
+
+        let mut entries = self.entries.lock().expect("cache lock");
+        if entries.len() >= self.config.max_entries && !entries.contains_key(&key) {
+            entries.retain(|_, entry| entry.expires_at > now);
+        }
+        while entries.len() >= self.config.max_entries && !entries.contains_key(&key) {
+            let Some(oldest) = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.inserted_at)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+            entries.remove(&oldest);
+        }
+
+        entries.insert(
+            key,
+            CacheEntry {
+                output,
+                expires_at: now + ttl,
+                inserted_at: now,
+            },
+        );
+    }
+}
+
+#[async_trait]
+impl<T: Toolset> Toolset for CachedToolset<T> {
+    fn name(&self) -> String {
+        self.inner.name()
+    }
+
+    fn definition(&self) -> Vec<ToolDefinition> {
+        self.inner.definition()
+    }
+
+    fn contain(&self, fn_name: &str) -> bool {
+        self.inner.contain(fn_name)
+    }
+
+    async fn invoke(&self, fn_name: &str, args: &str) -> Result<ToolOutput, ToolError> {
+        if self.uncached.contains(fn_name) {
+            return self.inner.invoke(fn_name, args).await;
+        }
+        // Arguments that are not JSON fail in the toolset, there is nothing worth caching
+        let Some(canonical_args) = canonical_json(args) else {
+            return self.inner.invoke(fn_name, args).await;
+        };
+
+        let key = (fn_name.to_string(), canonical_args);
+        if let Some(output) = self.get(&key) {
+            return Ok(output);
+        }
+
+        let output = self.inner.invoke(fn_name, args).await?;
+        if !matches!(output, ToolOutput::Fail(_) | ToolOutput::Stop(_)) {
+            self.insert(key, output.clone());
+        }
+        Ok(output)
+    }
+}
+
+/// Serializes JSON with sorted object keys and no whitespace, an empty string counts as `{}`.
+fn canonical_json(args: &str) -> Option<String> {
+    fn sort_keys(value: Value) -> Value {
+        match value {
+            Value::Object(object) => {
+                let mut entries = object.into_iter().collect::<Vec<_>>();
+                entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+                Value::Object(
+                    entries
+                        .into_iter()
+                        .map(|(key, value)| (key, sort_keys(value)))
+                        .collect(),
+                )
+            }
+            Value::Array(values) => Value::Array(values.into_iter().map(sort_keys).collect()),
+            value => value,
+        }
+    }
+
+    if args.trim().is_empty() {
+        return Some("{}".to_string());
+    }
+    let value = serde_json::from_str(args).ok()?;
+    Some(sort_keys(value).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::ToolMetadata;
+
+    /// Counts its calls and fails when asked to.
+    #[derive(Default)]
+    struct CountingToolset {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Toolset for CountingToolset {
+        fn name(&self) -> String {
+            "x".to_string()
+        }
+
+        fn definition(&self) -> Vec<ToolDefinition> {
+            [("x-read_tweet", false), ("x-post_tweet", true)]
+                .into_iter()
+                .map(|(name, side_effecting)| ToolDefinition {
+                    r#type: "function".to_string(),
+                    name: name.to_string(),
+                    description: String::new(),
+                    parameters: serde_json::json!({ "type": "object", "properties": {} }),
+                    metadata: ToolMetadata {
+                        side_effecting,
+                        ..Default::default()
+                    },
+                })
+                .collect()
+        }
+
+        fn contain(&self, fn_name: &str) -> bool {
+            matches!(fn_name, "x-read_tweet" | "x-post_tweet")
+        }
+
+        async fn invoke(&self, _fn_name: &str, args: &str) -> Result<ToolOutput, ToolError> {
+            let calls = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            if args.contains("fail") {
+                return Ok(ToolOutput::Fail("failed".to_string()));
+            }
+            Ok(ToolOutput::Text(format!("call {}", calls)))
+        }
+    }
+
+    fn calls(toolset: &CachedToolset<CountingToolset>) -> usize {
+        toolset.inner.calls.load(Ordering::SeqCst)
+    }
+
+    #[tokio::test]
+    async fn test_cache_hits() {
+        let toolset = CachedToolset::new(CountingToolset::default());
+
+        let first = toolset
+            .invoke("x-read_tweet", r#"{"url": "a", "limit": 1}"#)
+            .await
+            .unwrap();
+        let second = toolset
+            .invoke("x-read_tweet", r#"{ "limit": 1,  "url": "a" }"#)
+            .await
+            .unwrap();
+        assert_eq!(first, ToolOutput::Text("call 1".to_string()));
+        assert_eq!(second, first);
+        assert_eq!(calls(&toolset), 1);
+
+        toolset
+            .invoke("x-read_tweet", r#"{"url": "b", "limit": 1}"#)
+            .await
+            .unwrap();
+        assert_eq!(calls(&toolset), 2);
+
+        // Side-effecting tools and failures always run
+        for args in [r#"{"text": "hi"}"#, r#"{"text": "hi"}"#] {
+            toolset.invoke("x-post_tweet", args).await.unwrap();
+        }
+        for args in [r#"{"url": "fail"}"#, r#"{"url": "fail"}"#] {
+            toolset.invoke("x-read_tweet", args).await.unwrap();
+        }
+        assert_eq!(calls(&toolset), 6);
+    }
+
+    #[tokio::test]
+    async fn test_cache_bounds() {
+        let toolset = CachedToolset::new_with_config(
+            CountingToolset::default(),
+            ToolCacheConfig {
+                ttl: Duration::from_millis(20),
+                max_entries: 2,
+                ..Default::default()
+            },
+        );
+
+        for url in ["a", "b", "c", "a"] {
+            toolset
+                .invoke("x-read_tweet", &format!(r#"{{"url": "{}"}}"#, url))
+                .await
+                .unwrap();
+        }
+        // `a` was the oldest entry when `c` was added
+        assert_eq!(calls(&toolset), 4);
+
+        std::thread::sleep(Duration::from_millis(30));
+        toolset
+            .invoke("x-read_tweet", r#"{"url": "a"}"#)
+            .await
+            .unwrap();
+        assert_eq!(calls(&toolset), 5);
+    }
+
+    #[test]
+    fn test_canonical_json() {
+        assert_eq!(
+            canonical_json(r#"{"b": [{"d": 1, "c": 2}], "a": null}"#).unwrap(),
+            r#"{"a":null,"b":[{"c":2,"d":1}]}"#
+        );
+        assert_eq!(canonical_json(" ").unwrap(), "{}");
+        assert_eq!(canonical_json("{"), None);
+    }
+}
@@ -0,0 +1,193 @@
+use std::{collections::HashMap, pin::Pin};
+
+use thiserror::Error;
+
+use crate::{ToolDefinition, Toolset};
+
+#[derive(Debug, Error)]
+pub enum ToolRegistryError {
+    #[error("tool '{name}' of toolset '{duplicate}' is already defined by toolset '{existing}'")]
+    DuplicateName {
+        name: String,
+        existing: String,
+        duplicate: String,
+    },
+}
+
+/// Owns a set of toolsets and dispatches function names to them.
+///
+/// The function names of a toolset are taken from its definitions when it is registered, so
+/// every name resolves to exactly one toolset.
+#[derive(Debug, Default)]
+pub struct ToolRegistry {
+    toolsets: Vec<Pin<Box<dyn Toolset>>>,
+    definitions: Vec<ToolDefinition>,
+
+    /// Function name to the index of its toolset and of its definition
+    index: HashMap<String, (usize, usize)>,
+}
+
+impl ToolRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a toolset, failing without changes if one of its names is already taken.
+    pub fn register(&mut self, toolset: Pin<Box<dyn Toolset>>) -> Result<(), ToolRegistryError> {
+        let definitions = toolset.definition();
+
+        for (position, definition) in definitions.iter().enumerate() {
+            let existing = match self.index.get(&definition.name) {
+                Some((toolset_index, _)) => Some(self.toolsets[*toolset_index].name()),
+                None => definitions[..position]
+                    .iter()
+                    .any(|other| other.name == definition.name)
+                    .then(|| toolset.name()),
+            };
+
+            if let Some(existing) = existing {
+                return Err(ToolRegistryError::DuplicateName {
+                    name: definition.name.clone(),
+                    existing,
+                    duplicate: toolset.name(),
+                });
+            }
+        }
+
+        let toolset_index = self.toolsets.len();
+        for definition in definitions {
+            self.index.insert(
+                definition.name.clone(),
+                (toolset_index, self.definitions.len()),
+            );
+            self.definitions.push(definition);
+        }
+        self.toolsets.push(toolset);
+
+        Ok(())
+    }
+
+    /// Returns the toolset providing the function `name`.
+    pub fn get(&self, name: &str) -> Option<&Pin<Box<dyn Toolset>>> {
+        self.index
+            .get(name)
+            .map(|(toolset_index, _)| &self.toolsets[*toolset_index])
+    }
+
+    /// Returns the definition of the function `name`.
+    pub fn definition(&self, name: &str) -> Option<&ToolDefinition> {
+        self.index
+            .get(name)
+            .map(|(_, definition_index)| &self.definitions[*definition_index])
+    }
+
+    /// Returns the definitions of every registered function.
+    pub fn definitions(&self) -> &[ToolDefinition] {
+        &self.definitions
+    }
+
+    pub fn toolsets(&self) -> &[Pin<Box<dyn Toolset>>] {
+        &self.toolsets
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.toolsets.is_empty()
+    }
+}
+
+impl TryFrom<Vec<Pin<Box<dyn Toolset>>>> for ToolRegistry {
+    type Error = ToolRegistryError;
+
+    fn try_from(toolsets: Vec<Pin<Box<dyn Toolset>>>) -> Result<Self, Self::Error> {
+        let mut registry = Self::new();
+        for toolset in toolsets {
+            registry.register(toolset)?;
+        }
+        Ok(registry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ToolError, ToolOutput, async_trait};
+
+    struct NamedToolset {
+        name: &'static str,
+        functions: Vec<&'static str>,
+    }
+
+    #[async_trait]
+    impl Toolset for NamedToolset {
+        fn name(&self) -> String {
+            self.name.to_string()
+        }
+
+        fn definition(&self) -> Vec<ToolDefinition> {
+            self.functions
+                .iter()
+                .map(|function| ToolDefinition {
+                    r#type: "function".to_string(),
+                    name: function.to_string(),
+                    description: String::new(),
+                    parameters: serde_json::json!({ "type": "object", "properties": {} }),
+                    metadata: Default::default(),
+                })
+                .collect()
+        }
+
+        fn contain(&self, fn_name: &str) -> bool {
+            self.functions.contains(&fn_name)
+        }
+
+        async fn invoke(&self, _fn_name: &str, _args: &str) -> Result<ToolOutput, ToolError> {
+            Ok(ToolOutput::Text(self.name.to_string()))
+        }
+    }
+
+    #[test]
+    fn test_register_and_dispatch() {
+        let registry = ToolRegistry::try_from(vec![
+            Box::pin(NamedToolset {
+                name: "fs",
+                functions: vec!["fs-read", "fs-write"],
+            }) as Pin<Box<dyn Toolset>>,
+            Box::pin(NamedToolset {
+                name: "web",
+                functions: vec!["web-fetch"],
+            }),
+        ])
+        .unwrap();
+
+        assert_eq!(registry.definitions().len(), 3);
+        assert_eq!(registry.get("web-fetch").unwrap().name(), "web");
+        assert_eq!(registry.definition("fs-write").unwrap().name, "fs-write");
+        assert!(registry.get("fs-delete").is_none());
+    }
+
+    #[test]
+    fn test_register_duplicate_name() {
+        let mut registry = ToolRegistry::new();
+        registry
+            .register(Box::pin(NamedToolset {
+                name: "fs",
+                functions: vec!["fs-read"],
+            }))
+            .unwrap();
+
+        let err = registry
+            .register(Box::pin(NamedToolset {
+                name: "fs2",
+                functions: vec!["fs2-read", "fs-read"],
+            }))
+            .unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "tool 'fs-read' of toolset 'fs2' is already defined by toolset 'fs'"
+        );
+        // A rejected toolset is not partially registered
+        assert!(registry.get("fs2-read").is_none());
+        assert_eq!(registry.toolsets().len(), 1);
+    }
+}
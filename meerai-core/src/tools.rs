@@ -1,10 +1,11 @@
-use std::fmt::Debug;
+use std::{fmt::Debug, time::Duration};
 
 use anyhow::Result;
+use base64::Engine;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use super::async_trait;
+use super::{async_trait, validation::ValidationError};
 
 #[derive(Clone, Debug)]
 pub struct CommandOutput {
@@ -34,17 +35,165 @@ pub enum ToolError {
     #[error("arguments for tool failed to parse: {0:#}")]
     WrongArguments(#[from] serde_json::Error),
 
+    #[error("arguments for tool do not match its schema: {}", join_errors(.0))]
+    InvalidArguments(Vec<ValidationError>),
+
     #[error("tool execution failed: {0:#}")]
     ExecutionFailed(#[from] CommandError),
 
+    /// A failure that may go away when the call is repeated, e.g. a network error
+    #[error("tool execution failed temporarily: {0:#}")]
+    Transient(anyhow::Error),
+
+    #[error("tool execution timed out after {0:?}")]
+    Timeout(Duration),
+
     #[error(transparent)]
     Unknown(#[from] anyhow::Error),
 }
 
+/// How a caller should react to a [`ToolError`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ToolErrorKind {
+    /// Invoking the tool again with the same arguments may succeed
+    Retryable,
+
+    /// The model made a mistake in its call and should be told so it can correct it
+    ModelCorrectable,
+
+    /// Neither retrying nor the model can fix the error
+    Fatal,
+}
+
+impl ToolError {
+    /// Creates an [`ToolError::InvalidArguments`] with a single error at `path`.
+    pub fn invalid_argument(path: impl Into<String>, message: impl Into<String>) -> Self {
+        ToolError::InvalidArguments(vec![ValidationError {
+            path: path.into(),
+            message: message.into(),
+        }])
+    }
+
+    pub fn kind(&self) -> ToolErrorKind {
+        match self {
+            ToolError::InvalidFunctionName(_)
+            | ToolError::WrongArguments(_)
+            | ToolError::InvalidArguments(_) => ToolErrorKind::ModelCorrectable,
+            // The command ran and failed, its output tells the model what went wrong
+            ToolError::ExecutionFailed(CommandError::NonZeroExit(_)) => {
+                ToolErrorKind::ModelCorrectable
+            }
+            ToolError::Transient(_) | ToolError::Timeout(_) => ToolErrorKind::Retryable,
+            ToolError::ExecutionFailed(CommandError::ExecutorError(err))
+            | ToolError::Unknown(err) => {
+                if is_transient(err) {
+                    ToolErrorKind::Retryable
+                } else {
+                    ToolErrorKind::Fatal
+                }
+            }
+        }
+    }
+}
+
+fn is_transient(err: &anyhow::Error) -> bool {
+    use std::io::ErrorKind;
+
+    err.chain().any(|cause| {
+        // Missing files, denied permissions and the like fail again on every attempt
+        cause.downcast_ref::<std::io::Error>().is_some_and(|err| {
+            matches!(
+                err.kind(),
+                ErrorKind::TimedOut
+                    | ErrorKind::Interrupted
+                    | ErrorKind::WouldBlock
+                    | ErrorKind::ConnectionRefused
+                    | ErrorKind::ConnectionReset
+                    | ErrorKind::ConnectionAborted
+                    | ErrorKind::NotConnected
+                    | ErrorKind::BrokenPipe
+            )
+        }) || cause
+            .downcast_ref::<reqwest::Error>()
+            .is_some_and(|err| err.is_timeout() || err.is_connect() || err.is_request())
+    })
+}
+
+fn join_errors(errors: &[ValidationError]) -> String {
+    errors
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Binary content produced by a tool, e.g. an image.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Attachment {
+    pub mime_type: String,
+    pub data: Vec<u8>,
+}
+
+impl Attachment {
+    pub fn new(mime_type: impl Into<String>, data: impl Into<Vec<u8>>) -> Self {
+        Self {
+            mime_type: mime_type.into(),
+            data: data.into(),
+        }
+    }
+
+    /// Returns the content as text if the MIME type is textual and the data is valid UTF-8.
+    pub fn as_text(&self) -> Option<&str> {
+        let textual = self.mime_type.starts_with("text/")
+            || self.mime_type == "application/json"
+            || self.mime_type.ends_with("+json")
+            || self.mime_type.ends_with("+xml");
+        textual
+            .then(|| std::str::from_utf8(&self.data).ok())
+            .flatten()
+    }
+
+    /// Returns the data encoded as standard base64, as most providers expect inline media.
+    pub fn to_base64(&self) -> String {
+        base64::engine::general_purpose::STANDARD.encode(&self.data)
+    }
+}
+
+impl std::fmt::Display for Attachment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.as_text() {
+            Some(text) => write!(f, "{}", text),
+            None => write!(
+                f,
+                "<{} attachment, {} bytes>",
+                self.mime_type,
+                self.data.len()
+            ),
+        }
+    }
+}
+
+/// A named result of a tool that the caller can retrieve after the run.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Artifact {
+    pub name: String,
+    pub attachment: Attachment,
+}
+
 #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 pub enum ToolOutput {
     Text(String),
 
+    /// Structured data, sent to the model as compact JSON
+    Json(serde_json::Value),
+
+    /// Binary content such as an image. Textual content is sent to the model as the result,
+    /// anything else as a short description followed by the content for providers reading it
+    Attachment(Attachment),
+
+    /// Content kept for the caller, the model is only told that it was saved
+    Artifact(Artifact),
+
     Fail(String),
 
     Stop(String),
@@ -54,18 +203,65 @@ impl std::fmt::Display for ToolOutput {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             ToolOutput::Text(text) => write!(f, "Success: {}", text),
+            ToolOutput::Json(value) => write!(f, "Success: {}", value),
+            ToolOutput::Attachment(attachment) => write!(f, "Success: {}", attachment),
+            ToolOutput::Artifact(Artifact { name, attachment }) => write!(
+                f,
+                "Success: saved artifact '{}' ({}, {} bytes)",
+                name,
+                attachment.mime_type,
+                attachment.data.len()
+            ),
             ToolOutput::Fail(text) => write!(f, "Failure: {}", text),
             ToolOutput::Stop(text) => write!(f, "Stop: {}", text),
         }
     }
 }
 
+/// Execution properties of a tool that are not sent to the model.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ToolMetadata {
+    /// The tool changes state outside the agent, e.g. publishes a post, and needs approval
+    pub side_effecting: bool,
+
+    /// Maximum duration of a single invocation
+    pub timeout: Option<Duration>,
+
+    /// Repeating a call has the same effect as making it once, so failed calls may be retried.
+    /// Unset means idempotent unless the tool is side-effecting, see [`Self::is_idempotent`]
+    pub idempotent: Option<bool>,
+
+    /// Maximum number of invocations running at the same time
+    pub max_concurrency: Option<usize>,
+
+    pub rate_limit: Option<RateLimit>,
+}
+
+impl ToolMetadata {
+    /// Whether failed calls may be retried, side-effecting tools are only idempotent when
+    /// stated explicitly.
+    pub fn is_idempotent(&self) -> bool {
+        self.idempotent.unwrap_or(!self.side_effecting)
+    }
+}
+
+/// Allows at most `max_calls` invocations in any window of length `per`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct RateLimit {
+    pub max_calls: u32,
+    pub per: Duration,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ToolDefinition {
     pub r#type: String,
     pub name: String,
     pub description: String,
     pub parameters: serde_json::Value,
+
+    #[serde(skip)]
+    pub metadata: ToolMetadata,
 }
 
 impl ToolDefinition {
@@ -78,6 +274,23 @@ impl ToolDefinition {
             .build()?;
         Ok(chat_completion_tool)
     }
+
+    /// Checks that `args` parse as JSON and match the `parameters` schema.
+    pub fn validate_args(&self, args: &str) -> Result<(), ToolError> {
+        // Models commonly send an empty string for tools without parameters
+        let args = if args.trim().is_empty() {
+            serde_json::Value::Object(Default::default())
+        } else {
+            serde_json::from_str(args)?
+        };
+
+        let errors = crate::validation::validate(&self.parameters, &args);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ToolError::InvalidArguments(errors))
+        }
+    }
 }
 
 #[async_trait]
@@ -99,7 +312,7 @@ impl Debug for dyn Toolset {
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ToolCall {
-    // pub id: String,
+    pub id: String,
     pub name: String,
     pub args: String,
 }
@@ -133,6 +346,7 @@ mod tests {
                             }
                         }
                     }),
+                    metadata: Default::default(),
                 }]
             }
 
@@ -145,6 +359,77 @@ mod tests {
             }
         }
 
-        assert!(true);
+        let toolset = MyToolset;
+        assert_eq!(toolset.definition().len(), 1);
+        assert!(toolset.contain("my_tool"));
+    }
+
+    #[test]
+    fn test_tool_output_display() {
+        let json = ToolOutput::Json(serde_json::json!({ "likes": 3 }));
+        assert_eq!(json.to_string(), r#"Success: {"likes":3}"#);
+
+        let image = ToolOutput::Attachment(Attachment::new("image/png", vec![0u8; 16]));
+        assert_eq!(
+            image.to_string(),
+            "Success: <image/png attachment, 16 bytes>"
+        );
+
+        let text = ToolOutput::Attachment(Attachment::new("text/csv", "a,b"));
+        assert_eq!(text.to_string(), "Success: a,b");
+
+        let artifact = ToolOutput::Artifact(Artifact {
+            name: "report.md".to_string(),
+            attachment: Attachment::new("text/markdown", "# Report"),
+        });
+        assert_eq!(
+            artifact.to_string(),
+            "Success: saved artifact 'report.md' (text/markdown, 8 bytes)"
+        );
+    }
+
+    #[test]
+    fn test_tool_error_kind() {
+        assert_eq!(
+            ToolError::InvalidFunctionName("my_tool".to_string()).kind(),
+            ToolErrorKind::ModelCorrectable
+        );
+        assert_eq!(
+            ToolError::from(serde_json::from_str::<u8>("x").unwrap_err()).kind(),
+            ToolErrorKind::ModelCorrectable
+        );
+        assert_eq!(
+            ToolError::Transient(anyhow::anyhow!("rate limited")).kind(),
+            ToolErrorKind::Retryable
+        );
+
+        let io_error = std::io::Error::new(std::io::ErrorKind::ConnectionReset, "reset");
+        assert_eq!(
+            ToolError::from(anyhow::Error::new(io_error).context("failed to fetch")).kind(),
+            ToolErrorKind::Retryable
+        );
+        let io_error = std::io::Error::new(std::io::ErrorKind::PermissionDenied, "denied");
+        assert_eq!(
+            ToolError::from(anyhow::Error::new(io_error)).kind(),
+            ToolErrorKind::Fatal
+        );
+        assert_eq!(
+            ToolError::from(anyhow::anyhow!("invalid credentials")).kind(),
+            ToolErrorKind::Fatal
+        );
+    }
+
+    #[test]
+    fn test_tool_metadata_idempotent() {
+        assert!(ToolMetadata::default().is_idempotent());
+
+        let mut metadata = ToolMetadata {
+            side_effecting: true,
+            ..Default::default()
+        };
+        assert!(!metadata.is_idempotent());
+
+        metadata.idempotent = Some(true);
+        assert!(metadata.is_idempotent());
     }
 }
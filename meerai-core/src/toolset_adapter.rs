@@ -0,0 +1,332 @@
+use std::{
+    collections::{HashMap, HashSet},
+    pin::Pin,
+};
+
+use crate::{
+    CachedToolset, ToolCacheConfig, ToolDefinition, ToolError, ToolOutput, ToolRegistry,
+    ToolRegistryError, Toolset, async_trait,
+};
+
+/// Changes the name and the functions a toolset exposes without touching the toolset itself.
+///
+/// Renames, the allowlist and descriptions are given with the original function names. The
+/// prefix is applied last, so `renamed("x-read_tweet", "read")` with prefix `research` exposes
+/// `research-read`.
+pub struct ToolsetAdapter<T> {
+    inner: T,
+    name: Option<String>,
+    prefix: Option<String>,
+
+    /// Original function name to its new name
+    renames: HashMap<String, String>,
+
+    /// Original function names that are exposed, all of them if not set
+    allowlist: Option<HashSet<String>>,
+
+    /// Original function name to the description replacing its own
+    descriptions: HashMap<String, String>,
+}
+
+impl<T: Toolset> ToolsetAdapter<T> {
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            name: None,
+            prefix: None,
+            renames: HashMap::new(),
+            allowlist: None,
+            descriptions: HashMap::new(),
+        }
+    }
+
+    /// Replaces the name of the toolset, which is used in registry errors.
+    pub fn named(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Exposes every function as `{prefix}-{function}`.
+    pub fn prefixed(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = Some(prefix.into());
+        self
+    }
+
+    pub fn renamed(mut self, function: impl Into<String>, name: impl Into<String>) -> Self {
+        self.renames.insert(function.into(), name.into());
+        self
+    }
+
+    /// Exposes only the given functions, calling `only` again narrows the set further.
+    pub fn only<S: Into<String>>(mut self, functions: impl IntoIterator<Item = S>) -> Self {
+        let functions = functions
+            .into_iter()
+            .map(Into::into)
+            .collect::<HashSet<_>>();
+        self.allowlist = Some(match self.allowlist {
+            Some(allowlist) => allowlist.intersection(&functions).cloned().collect(),
+            None => functions,
+        });
+        self
+    }
+
+    pub fn described(
+        mut self,
+        function: impl Into<String>,
+        description: impl Into<String>,
+    ) -> Self {
+        self.descriptions
+            .insert(function.into(), description.into());
+        self
+    }
+
+    fn is_allowed(&self, function: &str) -> bool {
+        self.allowlist
+            .as_ref()
+            .is_none_or(|allowlist| allowlist.contains(function))
+    }
+
+    fn exposed_name(&self, function: &str) -> String {
+        let name = self.renames.get(function).map_or(function, String::as_str);
+        match &self.prefix {
+            Some(prefix) => format!("{}-{}", prefix, name),
+            None => name.to_string(),
+        }
+    }
+
+    /// Maps an exposed name back to the function of the inner toolset.
+    fn original_name(&self, name: &str) -> Option<String> {
+        let name = match &self.prefix {
+            Some(prefix) => name.strip_prefix(prefix.as_str())?.strip_prefix('-')?,
+            None => name,
+        };
+
+        let function = match self.renames.iter().find(|(_, new_name)| *new_name == name) {
+            Some((function, _)) => function.clone(),
+            // A renamed function is no longer reachable by its old name
+            None if self.renames.contains_key(name) => return None,
+            None => name.to_string(),
+        };
+
+        (self.is_allowed(&function) && self.inner.contain(&function)).then_some(function)
+    }
+}
+
+#[async_trait]
+impl<T: Toolset> Toolset for ToolsetAdapter<T> {
+    fn name(&self) -> String {
+        self.name.clone().unwrap_or_else(|| self.inner.name())
+    }
+
+    fn definition(&self) -> Vec<ToolDefinition> {
+        self.inner
+            .definition()
+            .into_iter()
+            .filter(|definition| self.is_allowed(&definition.name))
+            .map(|mut definition| {
+                if let Some(description) = self.descriptions.get(&definition.name) {
+                    definition.description = description.clone();
+                }
+                definition.name = self.exposed_name(&definition.name);
+                definition
+            })
+            .collect()
+    }
+
+    fn contain(&self, fn_name: &str) -> bool {
+        self.original_name(fn_name).is_some()
+    }
+
+    async fn invoke(&self, fn_name: &str, args: &str) -> Result<ToolOutput, ToolError> {
+        let function = self
+            .original_name(fn_name)
+            .ok_or_else(|| ToolError::InvalidFunctionName(fn_name.to_string()))?;
+        self.inner.invoke(&function, args).await
+    }
+}
+
+/// Adapter methods available on every toolset.
+pub trait ToolsetExt: Toolset + Sized {
+    fn named(self, name: impl Into<String>) -> ToolsetAdapter<Self> {
+        ToolsetAdapter::new(self).named(name)
+    }
+
+    fn prefixed(self, prefix: impl Into<String>) -> ToolsetAdapter<Self> {
+        ToolsetAdapter::new(self).prefixed(prefix)
+    }
+
+    fn renamed(self, function: impl Into<String>, name: impl Into<String>) -> ToolsetAdapter<Self> {
+        ToolsetAdapter::new(self).renamed(function, name)
+    }
+
+    fn only<S: Into<String>>(self, functions: impl IntoIterator<Item = S>) -> ToolsetAdapter<Self> {
+        ToolsetAdapter::new(self).only(functions)
+    }
+
+    fn described(
+        self,
+        function: impl Into<String>,
+        description: impl Into<String>,
+    ) -> ToolsetAdapter<Self> {
+        ToolsetAdapter::new(self).described(function, description)
+    }
+
+    fn cached(self, config: ToolCacheConfig) -> CachedToolset<Self> {
+        CachedToolset::new_with_config(self, config)
+    }
+}
+
+impl<T: Toolset> ToolsetExt for T {}
+
+/// Several toolsets exposed as one.
+///
+/// Function names must be unique across the merged toolsets, just like in a registry.
+#[derive(Debug)]
+pub struct MergedToolset {
+    name: String,
+    tools: ToolRegistry,
+}
+
+impl MergedToolset {
+    pub fn new(
+        name: impl Into<String>,
+        toolsets: Vec<Pin<Box<dyn Toolset>>>,
+    ) -> Result<Self, ToolRegistryError> {
+        Ok(Self {
+            name: name.into(),
+            tools: ToolRegistry::try_from(toolsets)?,
+        })
+    }
+}
+
+#[async_trait]
+impl Toolset for MergedToolset {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn definition(&self) -> Vec<ToolDefinition> {
+        self.tools.definitions().to_vec()
+    }
+
+    fn contain(&self, fn_name: &str) -> bool {
+        self.tools.get(fn_name).is_some()
+    }
+
+    async fn invoke(&self, fn_name: &str, args: &str) -> Result<ToolOutput, ToolError> {
+        let toolset = self
+            .tools
+            .get(fn_name)
+            .ok_or_else(|| ToolError::InvalidFunctionName(fn_name.to_string()))?;
+        toolset.invoke(fn_name, args).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Answers every call with the name of the function that was called.
+    struct XToolset;
+
+    #[async_trait]
+    impl Toolset for XToolset {
+        fn name(&self) -> String {
+            "x".to_string()
+        }
+
+        fn definition(&self) -> Vec<ToolDefinition> {
+            ["x-read_tweet", "x-post_tweet"]
+                .into_iter()
+                .map(|name| ToolDefinition {
+                    r#type: "function".to_string(),
+                    name: name.to_string(),
+                    description: format!("{} description", name),
+                    parameters: serde_json::json!({ "type": "object", "properties": {} }),
+                    metadata: Default::default(),
+                })
+                .collect()
+        }
+
+        fn contain(&self, fn_name: &str) -> bool {
+            matches!(fn_name, "x-read_tweet" | "x-post_tweet")
+        }
+
+        async fn invoke(&self, fn_name: &str, _args: &str) -> Result<ToolOutput, ToolError> {
+            Ok(ToolOutput::Text(fn_name.to_string()))
+        }
+    }
+
+    fn names(toolset: &impl Toolset) -> Vec<String> {
+        toolset
+            .definition()
+            .into_iter()
+            .map(|definition| definition.name)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_adapter() {
+        let toolset = XToolset
+            .only(["x-read_tweet"])
+            .renamed("x-read_tweet", "read")
+            .prefixed("research")
+            .described("x-read_tweet", "Read a tweet for research")
+            .named("research-x");
+
+        assert_eq!(toolset.name(), "research-x");
+        assert_eq!(names(&toolset), vec!["research-read"]);
+        assert_eq!(
+            toolset.definition()[0].description,
+            "Read a tweet for research"
+        );
+
+        assert!(toolset.contain("research-read"));
+        for hidden in [
+            "x-read_tweet",
+            "research-x-read_tweet",
+            "research-x-post_tweet",
+        ] {
+            assert!(!toolset.contain(hidden), "{} is exposed", hidden);
+        }
+
+        let output = toolset.invoke("research-read", "{}").await.unwrap();
+        assert_eq!(output, ToolOutput::Text("x-read_tweet".to_string()));
+
+        let err = toolset.invoke("x-post_tweet", "{}").await.unwrap_err();
+        assert!(matches!(err, ToolError::InvalidFunctionName(_)));
+    }
+
+    #[tokio::test]
+    async fn test_merged() {
+        let merged = MergedToolset::new(
+            "social",
+            vec![
+                Box::pin(XToolset.prefixed("a")) as Pin<Box<dyn Toolset>>,
+                Box::pin(XToolset.only(["x-post_tweet"])),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(merged.name(), "social");
+        assert_eq!(
+            names(&merged),
+            vec!["a-x-read_tweet", "a-x-post_tweet", "x-post_tweet"]
+        );
+        let output = merged.invoke("a-x-post_tweet", "{}").await.unwrap();
+        assert_eq!(output, ToolOutput::Text("x-post_tweet".to_string()));
+
+        let err = MergedToolset::new(
+            "social",
+            vec![
+                Box::pin(XToolset) as Pin<Box<dyn Toolset>>,
+                Box::pin(XToolset.only(["x-read_tweet"]).named("x2")),
+            ],
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "tool 'x-read_tweet' of toolset 'x2' is already defined by toolset 'x'"
+        );
+    }
+}
@@ -0,0 +1,208 @@
+use serde_json::Value;
+use thiserror::Error;
+
+/// A single mismatch between tool arguments and the tool's parameter schema.
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+#[error("{path}: {message}")]
+pub struct ValidationError {
+    /// JSON pointer style location of the offending value, `$` being the arguments root
+    pub path: String,
+
+    pub message: String,
+}
+
+/// Validates `args` against a JSON schema and returns every mismatch found.
+///
+/// Supports the subset of JSON schema emitted for tool parameters: `type`, `properties`,
+/// `required`, `additionalProperties`, `items`, `enum`, `const`, `anyOf` and `oneOf`.
+/// References (`$ref`) and other keywords are not checked.
+pub fn validate(schema: &Value, args: &Value) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+    validate_value(schema, args, "$", &mut errors);
+    errors
+}
+
+fn validate_value(schema: &Value, value: &Value, path: &str, errors: &mut Vec<ValidationError>) {
+    let Value::Object(schema) = schema else {
+        // `true` accepts everything and `false` nothing
+        if schema == &Value::Bool(false) {
+            push(errors, path, "no value is allowed here");
+        }
+        return;
+    };
+
+    if let Some(expected) = schema.get("type") {
+        let types = match expected {
+            Value::String(ty) => vec![ty.as_str()],
+            Value::Array(types) => types.iter().filter_map(Value::as_str).collect(),
+            _ => vec![],
+        };
+
+        if !types.is_empty() && !types.iter().any(|ty| is_type(value, ty)) {
+            push(
+                errors,
+                path,
+                format!(
+                    "expected {}, found {}",
+                    types.join(" or "),
+                    type_name(value)
+                ),
+            );
+            return;
+        }
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array)
+        && !allowed.contains(value)
+    {
+        push(
+            errors,
+            path,
+            format!(
+                "expected one of {}, found {}",
+                Value::from(allowed.clone()),
+                value
+            ),
+        );
+    }
+
+    if let Some(expected) = schema.get("const")
+        && expected != value
+    {
+        push(
+            errors,
+            path,
+            format!("expected {}, found {}", expected, value),
+        );
+    }
+
+    for keyword in ["anyOf", "oneOf"] {
+        if let Some(variants) = schema.get(keyword).and_then(Value::as_array) {
+            let matches = variants
+                .iter()
+                .any(|variant| validate(variant, value).is_empty());
+            if !matches {
+                push(
+                    errors,
+                    path,
+                    "value does not match any of the allowed schemas",
+                );
+            }
+        }
+    }
+
+    match value {
+        Value::Object(object) => {
+            let properties = schema.get("properties").and_then(Value::as_object);
+
+            if let Some(required) = schema.get("required").and_then(Value::as_array) {
+                for field in required.iter().filter_map(Value::as_str) {
+                    if !object.contains_key(field) {
+                        push(errors, path, format!("missing required field `{}`", field));
+                    }
+                }
+            }
+
+            for (key, field) in object {
+                let field_path = format!("{}.{}", path, key);
+                match properties.and_then(|properties| properties.get(key)) {
+                    Some(field_schema) => validate_value(field_schema, field, &field_path, errors),
+                    None => match schema.get("additionalProperties") {
+                        Some(Value::Bool(false)) => push(errors, &field_path, "unknown field"),
+                        Some(additional) => validate_value(additional, field, &field_path, errors),
+                        None => {}
+                    },
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items") {
+                for (index, item) in items.iter().enumerate() {
+                    validate_value(item_schema, item, &format!("{}[{}]", path, index), errors);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn is_type(value: &Value, ty: &str) -> bool {
+    match ty {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => {
+            value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|n| n.fract() == 0.0)
+        }
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn push(errors: &mut Vec<ValidationError>, path: &str, message: impl Into<String>) {
+    errors.push(ValidationError {
+        path: path.to_string(),
+        message: message.into(),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "text": { "type": "string" },
+                "limit": { "type": ["integer", "null"] },
+                "tags": { "type": "array", "items": { "type": "string" } },
+                "mode": { "type": "string", "enum": ["fast", "slow"] }
+            },
+            "required": ["text"],
+            "additionalProperties": false
+        })
+    }
+
+    #[test]
+    fn test_validate_valid_args() {
+        let args = json!({ "text": "hello", "limit": null, "tags": ["a"], "mode": "fast" });
+        assert!(validate(&schema(), &args).is_empty());
+    }
+
+    #[test]
+    fn test_validate_invalid_args() {
+        let args = json!({ "limit": 1.5, "tags": ["a", 2], "mode": "medium", "extra": true });
+
+        let errors = validate(&schema(), &args)
+            .into_iter()
+            .map(|err| err.to_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            errors,
+            vec![
+                "$: missing required field `text`",
+                "$.extra: unknown field",
+                "$.limit: expected integer or null, found number",
+                "$.mode: expected one of [\"fast\",\"slow\"], found \"medium\"",
+                "$.tags[1]: expected string, found number",
+            ]
+        );
+    }
+}
@@ -23,7 +23,11 @@ pub fn tool_attribute_impl(
         _ => None,
     };
 
-    let ToolMeta { name, description } = ToolMeta::from_list(&attr_args)?;
+    let ToolMeta {
+        name,
+        description,
+        execution,
+    } = ToolMeta::from_list(&attr_args)?;
     let ItemFn { sig, block, .. } = item;
 
     let struct_ident = syn::Ident::new(
@@ -40,6 +44,8 @@ pub fn tool_attribute_impl(
     let args_ident = params.clone().into_iter();
     let params_ident = params.clone().into_iter();
 
+    let execution_args = execution.to_attribute_args();
+
     let statements = block.stmts.clone();
     let function_identifier = sig.ident.clone();
 
@@ -47,7 +53,7 @@ pub fn tool_attribute_impl(
         #[derive(meerai_macros::Toolset)]
         #[toolset(
             name = #name,
-            tool(name = #tool_name, description = #description #(, params = #args_ident)*)
+            tool(name = #tool_name, description = #description #(, params = #args_ident)* #(, #execution_args)*)
         )]
         pub struct #struct_ident;
 
@@ -1,5 +1,6 @@
 use convert_case::{Case, Casing};
 use darling::{FromDeriveInput, FromMeta};
+use quote::quote;
 
 #[derive(Default, Debug, FromMeta)]
 #[darling(default)]
@@ -7,6 +8,9 @@ pub struct ToolMeta {
     pub name: String,
 
     pub description: String,
+
+    #[darling(flatten)]
+    pub execution: ExecutionArgs,
 }
 
 #[derive(Debug, FromDeriveInput)]
@@ -42,6 +46,125 @@ pub struct ToolArgs {
     pub description: String,
 
     pub params: Option<syn::Path>,
+
+    #[darling(flatten)]
+    pub execution: ExecutionArgs,
+}
+
+/// Execution metadata of a tool, see `meerai_core::ToolMetadata`.
+#[derive(Default, Debug, FromMeta)]
+#[darling(default)]
+pub struct ExecutionArgs {
+    /// The tool changes state outside the agent and requires approval before running
+    pub side_effecting: bool,
+
+    pub timeout_secs: Option<u64>,
+
+    /// Defaults to true unless the tool is side-effecting, failed calls are only retried for
+    /// idempotent tools
+    pub idempotent: Option<bool>,
+
+    pub max_concurrency: Option<usize>,
+
+    /// Written as `"<calls>/<s|min|h>"`, e.g. `"10/min"`
+    pub rate_limit: Option<RateLimitArg>,
+}
+
+#[derive(Debug)]
+pub struct RateLimitArg {
+    pub raw: String,
+    pub max_calls: u32,
+    pub per_secs: u64,
+}
+
+impl FromMeta for RateLimitArg {
+    fn from_string(value: &str) -> darling::Result<Self> {
+        let invalid = || {
+            darling::Error::custom(format!(
+                "invalid rate limit `{}`, expected e.g. \"10/min\"",
+                value
+            ))
+        };
+
+        let (max_calls, unit) = value.split_once('/').ok_or_else(invalid)?;
+        let max_calls = max_calls.trim().parse().map_err(|_| invalid())?;
+        let per_secs = match unit.trim() {
+            "s" | "sec" => 1,
+            "min" => 60,
+            "h" | "hour" => 3600,
+            _ => return Err(invalid()),
+        };
+
+        Ok(Self {
+            raw: value.to_string(),
+            max_calls,
+            per_secs,
+        })
+    }
+}
+
+impl ExecutionArgs {
+    /// Builds the `meerai_core::ToolMetadata` expression.
+    pub fn to_metadata(&self) -> proc_macro2::TokenStream {
+        let side_effecting = self.side_effecting;
+        let idempotent = match self.idempotent {
+            Some(idempotent) => quote!(Some(#idempotent)),
+            None => quote!(None),
+        };
+        let timeout = match self.timeout_secs {
+            Some(secs) => quote!(Some(std::time::Duration::from_secs(#secs))),
+            None => quote!(None),
+        };
+        let max_concurrency = match self.max_concurrency {
+            Some(max) => quote!(Some(#max)),
+            None => quote!(None),
+        };
+        let rate_limit = match &self.rate_limit {
+            Some(RateLimitArg {
+                max_calls,
+                per_secs,
+                ..
+            }) => quote! {
+                Some(meerai_core::RateLimit {
+                    max_calls: #max_calls,
+                    per: std::time::Duration::from_secs(#per_secs),
+                })
+            },
+            None => quote!(None),
+        };
+
+        quote! {
+            meerai_core::ToolMetadata {
+                side_effecting: #side_effecting,
+                timeout: #timeout,
+                idempotent: #idempotent,
+                max_concurrency: #max_concurrency,
+                rate_limit: #rate_limit,
+            }
+        }
+    }
+
+    /// Re-emits the arguments for a nested `tool(...)` attribute.
+    pub fn to_attribute_args(&self) -> Vec<proc_macro2::TokenStream> {
+        let mut args = vec![];
+        if self.side_effecting {
+            args.push(quote!(side_effecting));
+        }
+        if let Some(secs) = self.timeout_secs {
+            args.push(quote!(timeout_secs = #secs));
+        }
+        if let Some(idempotent) = self.idempotent {
+            args.push(quote!(idempotent = #idempotent));
+        }
+        if let Some(max) = self.max_concurrency {
+            args.push(quote!(max_concurrency = #max));
+        }
+        if let Some(rate_limit) = &self.rate_limit {
+            let raw = &rate_limit.raw;
+            args.push(quote!(rate_limit = #raw));
+        }
+        args
+    }
 }
 
 impl ToolArgs {
@@ -1,79 +1,418 @@
+use proc_macro2::TokenStream;
 use quote::quote;
-use syn::{Data, DeriveInput, Field, Fields};
+use syn::{
+    Attribute, Data, DeriveInput, Fields, FieldsNamed, LitStr, Token, Variant,
+    meta::ParseNestedMeta, parse_quote, spanned::Spanned,
+};
 
-pub fn schema_derive_impl(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+pub fn schema_derive_impl(input: &DeriveInput) -> syn::Result<TokenStream> {
     let name = &input.ident;
-    let fields = match &input.data {
+    let serde = SerdeAttrs::parse(&input.attrs)?;
+
+    let schema = match &input.data {
         Data::Struct(data) => match &data.fields {
-            Fields::Named(fields) => generate_field_schemas(&fields.named),
-            Fields::Unnamed(_) => panic!("Tuple structs are not supported"),
-            Fields::Unit => vec![],
+            Fields::Named(fields) => object_schema(fields, serde.rename_all, &[])?,
+            Fields::Unnamed(_) | Fields::Unit => content_schema(&data.fields, None)?
+                .unwrap_or_else(|| quote! { serde_json::json!({ "type": "null" }) }),
         },
-        Data::Enum(_) => {
-            // Handle enums separately
-            return Ok(handle_enum_derive(input));
+        Data::Enum(data) => enum_schema(&serde, data.variants.iter())?,
+        Data::Union(_) => {
+            return Err(syn::Error::new(
+                input.span(),
+                "unions are not supported by the Schema derive",
+            ));
         }
-        Data::Union(_) => panic!("Unions are not supported"),
     };
 
-    let expanded = quote! {
-        impl meerai_core::JsonSchema for #name {
-            fn json_schema() -> serde_json::Value {
-                use serde_json::{Map, Value};
+    let mut generics = input.generics.clone();
+    for param in generics.type_params_mut() {
+        param.bounds.push(parse_quote!(schemars::JsonSchema));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
-                let mut schema = Map::new();
-                schema.insert("type".to_string(), Value::String("object".to_string()));
+    Ok(quote! {
+        impl #impl_generics schemars::JsonSchema for #name #ty_generics #where_clause {
+            fn schema_name() -> std::borrow::Cow<'static, str> {
+                std::borrow::Cow::Borrowed(stringify!(#name))
+            }
 
-                let mut properties = Map::new();
-                #(#fields)*
+            fn schema_id() -> std::borrow::Cow<'static, str> {
+                std::borrow::Cow::Borrowed(concat!(module_path!(), "::", stringify!(#name)))
+            }
 
-                schema.insert("properties".to_string(), Value::Object(properties));
-                Value::Object(schema)
+            #[allow(unused_variables)]
+            fn json_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+                schemars::Schema::try_from(#schema).expect("derived schemas are objects")
             }
         }
-    };
+    })
+}
 
-    Ok(expanded)
+/// How serde represents the variants of an enum.
+#[derive(Clone, Copy)]
+enum Tagging<'a> {
+    External,
+    Internal { tag: &'a str },
+    Adjacent { tag: &'a str, content: &'a str },
+    Untagged,
 }
 
-fn handle_enum_derive(input: &syn::DeriveInput) -> proc_macro2::TokenStream {
-    let name = &input.ident;
+/// Follows serde's enum representations, so the schema describes what deserializes.
+fn enum_schema<'a>(
+    serde: &SerdeAttrs,
+    variants: impl Iterator<Item = &'a Variant>,
+) -> syn::Result<TokenStream> {
+    let tagging = match (&serde.tag, &serde.content, serde.untagged) {
+        (_, _, true) => Tagging::Untagged,
+        (None, None, _) => Tagging::External,
+        (Some(tag), None, _) => Tagging::Internal { tag },
+        (Some(tag), Some(content), _) => Tagging::Adjacent { tag, content },
+        (None, Some(_), _) => {
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "serde `content` requires `tag`",
+            ));
+        }
+    };
 
-    // This would extract enum variants and generate a schema
-    // For demonstration, we'll just create a basic implementation
-    let expanded = quote! {
-        impl meerai_core::JsonSchema for #name {
-            fn json_schema() -> serde_json::Value {
-                use serde_json::{Map, Value};
+    let tag_property = |tag: &str, name: &str| {
+        (
+            tag.to_string(),
+            quote! { serde_json::json!({ "type": "string", "const": #name }) },
+        )
+    };
 
-                let mut schema = Map::new();
-                schema.insert("type".to_string(), Value::String("string".to_string()));
-                // In a real implementation, we would extract variant names here
-                schema.insert("description".to_string(), Value::String(format!("Enum type {}", stringify!(#name))));
+    let mut unit_names = vec![];
+    let mut schemas = vec![];
+    for variant in variants {
+        let attrs = SerdeAttrs::parse(&variant.attrs)?;
+        if attrs.skip {
+            continue;
+        }
+        let name = attrs.rename.unwrap_or_else(|| {
+            serde.rename_all.map_or(variant.ident.to_string(), |rule| {
+                rule.apply_to_variant(&variant.ident.to_string())
+            })
+        });
+        let rename_all = attrs.rename_all.or(serde.rename_all_fields);
 
-                Value::Object(schema)
+        let schema = match tagging {
+            Tagging::External => match content_schema(&variant.fields, rename_all)? {
+                // Unit variants are plain strings and share a single `enum` schema
+                None => {
+                    unit_names.push(name);
+                    continue;
+                }
+                Some(content) => quote! {
+                    serde_json::json!({
+                        "type": "object",
+                        "properties": { #name: (#content) },
+                        "required": [#name],
+                        "additionalProperties": false,
+                    })
+                },
+            },
+            Tagging::Internal { tag } => match &variant.fields {
+                Fields::Named(fields) => {
+                    object_schema(fields, rename_all, &[tag_property(tag, &name)])?
+                }
+                Fields::Unit => unit_object_schema(&[tag_property(tag, &name)]),
+                Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                    let tagged = unit_object_schema(&[tag_property(tag, &name)]);
+                    let ty = &fields.unnamed[0].ty;
+                    quote! {
+                        serde_json::json!({
+                            "allOf": [(#tagged), (generator.subschema_for::<#ty>())]
+                        })
+                    }
+                }
+                Fields::Unnamed(_) => {
+                    return Err(syn::Error::new(
+                        variant.span(),
+                        "internally tagged enums cannot have tuple variants",
+                    ));
+                }
+            },
+            Tagging::Adjacent { tag, content } => {
+                let mut properties = vec![tag_property(tag, &name)];
+                if let Some(schema) = content_schema(&variant.fields, rename_all)? {
+                    properties.push((content.to_string(), schema));
+                }
+                unit_object_schema(&properties)
             }
-        }
-    };
+            Tagging::Untagged => content_schema(&variant.fields, rename_all)?
+                .unwrap_or_else(|| quote! { serde_json::json!({ "type": "null" }) }),
+        };
+        schemas.push(schema);
+    }
 
-    expanded
+    let unit_schema =
+        quote! { serde_json::json!({ "type": "string", "enum": [#(#unit_names),*] }) };
+    Ok(match (unit_names.is_empty(), schemas.is_empty()) {
+        (false, true) => unit_schema,
+        (false, false) => {
+            quote! { serde_json::json!({ "oneOf": [(#unit_schema), #((#schemas)),*] }) }
+        }
+        (true, _) => quote! { serde_json::json!({ "oneOf": [#((#schemas)),*] }) },
+    })
 }
 
-fn generate_field_schemas(
-    fields: &syn::punctuated::Punctuated<Field, syn::token::Comma>,
-) -> Vec<proc_macro2::TokenStream> {
-    fields.iter().map(generate_field_schema).collect()
+/// Returns the schema of the fields of a struct or variant, `None` for unit fields.
+fn content_schema(
+    fields: &Fields,
+    rename_all: Option<RenameRule>,
+) -> syn::Result<Option<TokenStream>> {
+    Ok(match fields {
+        Fields::Named(fields) => Some(object_schema(fields, rename_all, &[])?),
+        Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+            let ty = &fields.unnamed[0].ty;
+            Some(quote! { generator.subschema_for::<#ty>() })
+        }
+        Fields::Unnamed(fields) => {
+            let types = fields.unnamed.iter().map(|field| &field.ty);
+            let len = fields.unnamed.len();
+            Some(quote! {
+                serde_json::json!({
+                    "type": "array",
+                    "prefixItems": [#((generator.subschema_for::<#types>())),*],
+                    "minItems": #len,
+                    "maxItems": #len,
+                })
+            })
+        }
+        Fields::Unit => None,
+    })
 }
 
-fn generate_field_schema(field: &Field) -> proc_macro2::TokenStream {
-    let field_name = &field.ident;
-    let field_type = &field.ty;
+/// Builds an object schema of named fields, after the given leading properties such as a tag.
+///
+/// Fields are required unless they are an `Option` or have a serde default.
+fn object_schema(
+    fields: &FieldsNamed,
+    rename_all: Option<RenameRule>,
+    leading: &[(String, TokenStream)],
+) -> syn::Result<TokenStream> {
+    let mut properties = leading.to_vec();
+    let mut required: Vec<String> = leading.iter().map(|(name, _)| name.clone()).collect();
+
+    for field in &fields.named {
+        let attrs = SerdeAttrs::parse(&field.attrs)?;
+        if attrs.skip {
+            continue;
+        }
+        if attrs.flatten {
+            return Err(syn::Error::new(
+                field.span(),
+                "flattened fields are not supported by the Schema derive",
+            ));
+        }
 
+        let ident = field
+            .ident
+            .as_ref()
+            .expect("named fields have an identifier")
+            .to_string();
+        let ident = ident.strip_prefix("r#").unwrap_or(&ident);
+        let name = attrs.rename.unwrap_or_else(|| {
+            rename_all.map_or(ident.to_string(), |rule| rule.apply_to_field(ident))
+        });
+        if !attrs.default && !is_option(&field.ty) {
+            required.push(name.clone());
+        }
+
+        let ty = &field.ty;
+        properties.push((name, quote! { generator.subschema_for::<#ty>() }));
+    }
+
+    let names = properties.iter().map(|(name, _)| name);
+    let schemas = properties.iter().map(|(_, schema)| schema);
+    Ok(quote! {
+        serde_json::json!({
+            "type": "object",
+            "properties": { #(#names: (#schemas)),* },
+            "required": [#(#required),*],
+        })
+    })
+}
+
+/// Builds an object schema with every given property required.
+fn unit_object_schema(properties: &[(String, TokenStream)]) -> TokenStream {
+    let names = properties.iter().map(|(name, _)| name);
+    let required = names.clone();
+    let schemas = properties.iter().map(|(_, schema)| schema);
     quote! {
-        properties.insert(
-            stringify!(#field_name).to_string(),
-            <#field_type as meerai_core::JsonSchema>::json_schema()
-        );
+        serde_json::json!({
+            "type": "object",
+            "properties": { #(#names: (#schemas)),* },
+            "required": [#(#required),*],
+        })
+    }
+}
+
+fn is_option(ty: &syn::Type) -> bool {
+    matches!(
+        ty,
+        syn::Type::Path(path) if path.path.segments.last().is_some_and(|s| s.ident == "Option")
+    )
+}
+
+/// The serde attributes that change the JSON shape of a type, others are ignored.
+#[derive(Default)]
+struct SerdeAttrs {
+    rename: Option<String>,
+    rename_all: Option<RenameRule>,
+    rename_all_fields: Option<RenameRule>,
+    tag: Option<String>,
+    content: Option<String>,
+    untagged: bool,
+    default: bool,
+    skip: bool,
+    flatten: bool,
+}
+
+impl SerdeAttrs {
+    fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut serde = Self::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+            attr.parse_nested_meta(|meta| {
+                let path = &meta.path;
+                if path.is_ident("rename") {
+                    serde.rename = Some(deserialize_name(&meta)?.value());
+                } else if path.is_ident("rename_all") {
+                    serde.rename_all = Some(RenameRule::parse(&deserialize_name(&meta)?)?);
+                } else if path.is_ident("rename_all_fields") {
+                    serde.rename_all_fields = Some(RenameRule::parse(&deserialize_name(&meta)?)?);
+                } else if path.is_ident("tag") {
+                    serde.tag = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if path.is_ident("content") {
+                    serde.content = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if path.is_ident("untagged") {
+                    serde.untagged = true;
+                } else if path.is_ident("default") {
+                    serde.default = true;
+                    skip_value(&meta)?;
+                } else if path.is_ident("skip") || path.is_ident("skip_deserializing") {
+                    serde.skip = true;
+                } else if path.is_ident("flatten") {
+                    serde.flatten = true;
+                } else {
+                    skip_value(&meta)?;
+                }
+                Ok(())
+            })?;
+        }
+        Ok(serde)
+    }
+}
+
+/// Reads `name = "..."`, or the deserialize name of `name(serialize = "...", deserialize = "...")`.
+fn deserialize_name(meta: &ParseNestedMeta) -> syn::Result<LitStr> {
+    if meta.input.peek(Token![=]) {
+        return meta.value()?.parse();
+    }
+
+    let mut name = None;
+    meta.parse_nested_meta(|nested| {
+        let value: LitStr = nested.value()?.parse()?;
+        if nested.path.is_ident("deserialize") {
+            name = Some(value);
+        }
+        Ok(())
+    })?;
+    name.ok_or_else(|| meta.error("expected a deserialize name"))
+}
+
+fn skip_value(meta: &ParseNestedMeta) -> syn::Result<()> {
+    if meta.input.peek(Token![=]) {
+        meta.value()?.parse::<syn::Expr>()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        let content;
+        syn::parenthesized!(content in meta.input);
+        content.parse::<TokenStream>()?;
+    }
+    Ok(())
+}
+
+/// A serde `rename_all` rule, applied the way serde applies it.
+#[derive(Clone, Copy)]
+enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl RenameRule {
+    fn parse(rule: &LitStr) -> syn::Result<Self> {
+        Ok(match rule.value().as_str() {
+            "lowercase" => Self::Lower,
+            "UPPERCASE" => Self::Upper,
+            "PascalCase" => Self::Pascal,
+            "camelCase" => Self::Camel,
+            "snake_case" => Self::Snake,
+            "SCREAMING_SNAKE_CASE" => Self::ScreamingSnake,
+            "kebab-case" => Self::Kebab,
+            "SCREAMING-KEBAB-CASE" => Self::ScreamingKebab,
+            _ => return Err(syn::Error::new(rule.span(), "unknown rename rule")),
+        })
+    }
+
+    /// Variants are written in PascalCase.
+    fn apply_to_variant(self, variant: &str) -> String {
+        match self {
+            Self::Pascal => variant.to_string(),
+            Self::Lower => variant.to_ascii_lowercase(),
+            Self::Upper => variant.to_ascii_uppercase(),
+            Self::Camel => variant[..1].to_ascii_lowercase() + &variant[1..],
+            Self::Snake => {
+                let mut snake = String::new();
+                for (i, c) in variant.char_indices() {
+                    if i > 0 && c.is_uppercase() {
+                        snake.push('_');
+                    }
+                    snake.push(c.to_ascii_lowercase());
+                }
+                snake
+            }
+            Self::ScreamingSnake => Self::Snake.apply_to_variant(variant).to_ascii_uppercase(),
+            Self::Kebab => Self::Snake.apply_to_variant(variant).replace('_', "-"),
+            Self::ScreamingKebab => Self::ScreamingSnake
+                .apply_to_variant(variant)
+                .replace('_', "-"),
+        }
+    }
+
+    /// Fields are written in snake_case.
+    fn apply_to_field(self, field: &str) -> String {
+        match self {
+            Self::Lower | Self::Snake => field.to_string(),
+            Self::Upper | Self::ScreamingSnake => field.to_ascii_uppercase(),
+            Self::Pascal => {
+                let mut pascal = String::new();
+                let mut capitalize = true;
+                for c in field.chars() {
+                    if c == '_' {
+                        capitalize = true;
+                    } else if capitalize {
+                        pascal.push(c.to_ascii_uppercase());
+                        capitalize = false;
+                    } else {
+                        pascal.push(c);
+                    }
+                }
+                pascal
+            }
+            Self::Camel => {
+                let pascal = Self::Pascal.apply_to_field(field);
+                pascal[..1].to_ascii_lowercase() + &pascal[1..]
+            }
+            Self::Kebab => field.replace('_', "-"),
+            Self::ScreamingKebab => field.to_ascii_uppercase().replace('_', "-"),
+        }
     }
 }
 
@@ -103,4 +442,47 @@ mod test {
 
         insta::assert_snapshot!(crate::test_utils::pretty_macro_output(&output));
     }
+
+    #[test]
+    fn test_schema_derive_tagged_enum() {
+        let input: DeriveInput = parse_quote! {
+            #[derive(Debug, serde::Serialize, serde::Deserialize)]
+            #[serde(tag = "kind", rename_all = "snake_case")]
+            enum Shape {
+                Point,
+                Circle { radius: f64 },
+                Polygon(Polygon),
+            }
+        };
+
+        let output = schema_derive_impl(&input).unwrap();
+
+        insta::assert_snapshot!(crate::test_utils::pretty_macro_output(&output));
+    }
+
+    #[test]
+    fn test_internally_tagged_tuple_variant() {
+        let input: DeriveInput = parse_quote! {
+            #[serde(tag = "kind")]
+            enum Shape {
+                Line(f64, f64),
+            }
+        };
+
+        assert!(schema_derive_impl(&input).is_err());
+    }
+
+    #[test]
+    fn test_rename_rules() {
+        assert_eq!(
+            RenameRule::Snake.apply_to_variant("VeryTasty"),
+            "very_tasty"
+        );
+        assert_eq!(
+            RenameRule::ScreamingKebab.apply_to_variant("VeryTasty"),
+            "VERY-TASTY"
+        );
+        assert_eq!(RenameRule::Camel.apply_to_field("very_tasty"), "veryTasty");
+        assert_eq!(RenameRule::Pascal.apply_to_field("very_tasty"), "VeryTasty");
+    }
 }
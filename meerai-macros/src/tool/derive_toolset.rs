@@ -80,6 +80,7 @@ fn build_definition_fn(derived: &ToolsetDerive) -> proc_macro2::TokenStream {
         .map(|tool| {
             let fn_name = build_fn_name(&derived.toolset.name, &tool.get_fn_name());
             let description = &tool.description;
+            let metadata = tool.execution.to_metadata();
 
             if let Some(params) = &tool.params {
                 let args_struct_ident = params.get_ident().unwrap();
@@ -90,6 +91,7 @@ fn build_definition_fn(derived: &ToolsetDerive) -> proc_macro2::TokenStream {
                         name: #fn_name.to_string(),
                         description: #description.to_string(),
                         parameters: #args_struct_ident::json_schema(generator).into(),
+                        metadata: #metadata,
                     }
                 }
             } else {
@@ -102,6 +104,7 @@ fn build_definition_fn(derived: &ToolsetDerive) -> proc_macro2::TokenStream {
                             "type": "object",
                             "properties": {}
                         }),
+                        metadata: #metadata,
                     }
                 }
             }
@@ -113,6 +116,8 @@ fn build_definition_fn(derived: &ToolsetDerive) -> proc_macro2::TokenStream {
             let generator = &mut schemars::SchemaGenerator::new(
                 schemars::generate::SchemaSettings::default().with(|s| {
                     s.meta_schema = None;
+                    // Definitions are not part of the parameters, so nested types must be inlined
+                    s.inline_subschemas = true;
                 }),
             );
 
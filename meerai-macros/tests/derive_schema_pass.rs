@@ -1,4 +1,5 @@
 use meerai_core::JsonSchema;
+use serde_json::json;
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone, JsonSchema)]
 pub struct ChildrenArgs {
@@ -29,3 +30,172 @@ async fn test_derive_schema_pass() {
     let value: serde_json::Value = SampleArgs::json_schema(generator).into();
     println!("Args Schema: {}", value);
 }
+
+#[derive(serde::Deserialize, meerai_macros::Schema)]
+#[serde(rename_all = "snake_case")]
+pub enum TemperatureUnit {
+    Celsius,
+    Fahrenheit,
+}
+
+#[derive(serde::Deserialize, meerai_macros::Schema)]
+pub struct Location {
+    pub city: String,
+    #[serde(default)]
+    pub country: String,
+    pub unit: Option<TemperatureUnit>,
+}
+
+#[derive(serde::Deserialize, meerai_macros::Schema)]
+pub enum ExternallyTagged {
+    Unknown,
+    Named(String),
+    At { location: Location },
+}
+
+#[derive(serde::Deserialize, meerai_macros::Schema)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum InternallyTagged {
+    Unknown,
+    At { location: Location },
+    Near(Location),
+}
+
+#[derive(serde::Deserialize, meerai_macros::Schema)]
+#[serde(tag = "kind", content = "value", rename_all_fields = "camelCase")]
+pub enum AdjacentlyTagged {
+    Unknown,
+    Named(String),
+    At { home_location: Location },
+}
+
+#[derive(serde::Deserialize, meerai_macros::Schema)]
+#[serde(untagged)]
+pub enum Untagged {
+    Named(String),
+    Coordinates(f64, f64),
+}
+
+fn schema_of<T: schemars::JsonSchema>() -> serde_json::Value {
+    let generator = &mut schemars::SchemaGenerator::new(
+        schemars::generate::SchemaSettings::default().with(|s| {
+            s.meta_schema = None;
+            s.inline_subschemas = true;
+        }),
+    );
+    T::json_schema(generator).into()
+}
+
+#[test]
+fn test_derive_schema_enums() {
+    let unit = json!({ "type": "string", "enum": ["celsius", "fahrenheit"] });
+    assert_eq!(schema_of::<TemperatureUnit>(), unit);
+
+    let location = schema_of::<Location>();
+    assert_eq!(location["required"], json!(["city"]));
+    assert_eq!(
+        location["properties"]["country"],
+        json!({ "type": "string" })
+    );
+
+    assert_eq!(
+        schema_of::<ExternallyTagged>(),
+        json!({
+            "oneOf": [
+                { "type": "string", "enum": ["Unknown"] },
+                {
+                    "type": "object",
+                    "properties": { "Named": { "type": "string" } },
+                    "required": ["Named"],
+                    "additionalProperties": false
+                },
+                {
+                    "type": "object",
+                    "properties": {
+                        "At": {
+                            "type": "object",
+                            "properties": { "location": location },
+                            "required": ["location"]
+                        }
+                    },
+                    "required": ["At"],
+                    "additionalProperties": false
+                }
+            ]
+        })
+    );
+
+    let kind = |name: &str| json!({ "type": "string", "const": name });
+    assert_eq!(
+        schema_of::<InternallyTagged>(),
+        json!({
+            "oneOf": [
+                {
+                    "type": "object",
+                    "properties": { "kind": kind("unknown") },
+                    "required": ["kind"]
+                },
+                {
+                    "type": "object",
+                    "properties": { "kind": kind("at"), "location": location },
+                    "required": ["kind", "location"]
+                },
+                {
+                    "allOf": [
+                        {
+                            "type": "object",
+                            "properties": { "kind": kind("near") },
+                            "required": ["kind"]
+                        },
+                        location
+                    ]
+                }
+            ]
+        })
+    );
+
+    assert_eq!(
+        schema_of::<AdjacentlyTagged>(),
+        json!({
+            "oneOf": [
+                {
+                    "type": "object",
+                    "properties": { "kind": kind("Unknown") },
+                    "required": ["kind"]
+                },
+                {
+                    "type": "object",
+                    "properties": { "kind": kind("Named"), "value": { "type": "string" } },
+                    "required": ["kind", "value"]
+                },
+                {
+                    "type": "object",
+                    "properties": {
+                        "kind": kind("At"),
+                        "value": {
+                            "type": "object",
+                            "properties": { "homeLocation": location },
+                            "required": ["homeLocation"]
+                        }
+                    },
+                    "required": ["kind", "value"]
+                }
+            ]
+        })
+    );
+
+    assert_eq!(
+        schema_of::<Untagged>(),
+        json!({
+            "oneOf": [
+                { "type": "string" },
+                {
+                    "type": "array",
+                    "prefixItems": [{ "type": "number", "format": "double" }, { "type": "number", "format": "double" }],
+                    "minItems": 2,
+                    "maxItems": 2
+                }
+            ]
+        })
+    );
+}
@@ -7,10 +7,17 @@ pub struct GetWeatherArgs {
     pub size: f64,
 }
 
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TemperatureUnit {
+    Celsius,
+    Fahrenheit,
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone, JsonSchema)]
 pub struct SetWeatherArgs {
     pub location: String,
-    pub unit: Option<String>,
+    pub unit: TemperatureUnit,
     pub size: f64,
 }
 
@@ -25,6 +32,11 @@ pub struct SetWeatherArgs {
         name = "Set Weather",
         description = "Set weather information",
         params = SetWeatherArgs,
+        side_effecting,
+        idempotent = false,
+        timeout_secs = 10,
+        max_concurrency = 1,
+        rate_limit = "5/min",
     )
 )]
 pub struct MyToolset;
@@ -66,5 +78,26 @@ async fn test_derive_toolset_pass() {
 
     let definition = tool.definition();
     println!("{}", serde_json::to_string_pretty(&definition).unwrap());
+    assert!(!definition[0].metadata.side_effecting);
+    assert!(definition[0].metadata.is_idempotent());
+    // The enum is inlined instead of referenced, the definition has no `$defs` to point at
+    assert_eq!(
+        definition[1].parameters["properties"]["unit"]["enum"],
+        serde_json::json!(["celsius", "fahrenheit"])
+    );
+    assert!(definition[1].metadata.side_effecting);
+    assert!(!definition[1].metadata.is_idempotent());
+    assert_eq!(
+        definition[1].metadata.timeout,
+        Some(std::time::Duration::from_secs(10))
+    );
+    assert_eq!(definition[1].metadata.max_concurrency, Some(1));
+    assert_eq!(
+        definition[1].metadata.rate_limit,
+        Some(meerai_core::RateLimit {
+            max_calls: 5,
+            per: std::time::Duration::from_secs(60),
+        })
+    );
     assert_eq!(direct_call, invoke_call);
 }
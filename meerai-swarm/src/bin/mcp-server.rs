@@ -0,0 +1,44 @@
+use std::pin::Pin;
+
+use agent_twitter_client::scraper::Scraper;
+use bsky_sdk::BskyAgent;
+use dotenv::dotenv;
+use meerai_common::mcp::McpServer;
+use meerai_core::{ToolRegistry, Toolset};
+use meerai_swarm::{config::load_config, log::init_logging, tools};
+
+/// Serves the Bluesky and X toolsets over stdio so editors and other agent runtimes can use them.
+#[tokio::main]
+async fn main() {
+    // Logs go to stderr, stdout is reserved for the protocol
+    init_logging();
+
+    dotenv().ok();
+    let config = load_config().expect("Failed to load config");
+
+    let mut scraper = Scraper::new().await.unwrap();
+    scraper
+        .set_from_cookie_string(&config.x.cookie)
+        .await
+        .unwrap();
+
+    let bsky_agent = BskyAgent::builder()
+        .build()
+        .await
+        .expect("Failed to create BskyAgent");
+    bsky_agent
+        .login(&config.bluesky.identifier, &config.bluesky.password)
+        .await
+        .expect("Failed to log in to Bluesky");
+
+    let registry = ToolRegistry::try_from(vec![
+        Box::pin(tools::BskyToolset::new(bsky_agent)) as Pin<Box<dyn Toolset>>,
+        Box::pin(tools::XToolset::new(scraper)),
+    ])
+    .expect("Failed to register toolsets");
+
+    McpServer::new("meerai-swarm", registry)
+        .serve_stdio()
+        .await
+        .expect("MCP server failed");
+}
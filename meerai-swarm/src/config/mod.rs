@@ -1,8 +1,10 @@
 mod bluesky;
 mod x;
 
+use std::collections::HashMap;
+
 pub use bluesky::BlueskyConfig;
-use meerai_common::config;
+use meerai_common::{config, mcp::McpServerConfig, memory::MemoryStoreConfig};
 use serde::Deserialize;
 pub use x::XConfig;
 
@@ -11,6 +13,14 @@ pub use x::XConfig;
 pub struct Config {
     pub x: XConfig,
     pub bluesky: BlueskyConfig,
+
+    /// MCP servers whose tools are given to the agents, by toolset name
+    #[serde(default)]
+    pub mcp_servers: HashMap<String, McpServerConfig>,
+
+    /// Store of the memories agents keep between prompts
+    #[serde(default)]
+    pub memory: MemoryStoreConfig,
 }
 
 pub fn load_config() -> Result<Config, config::ConfigError> {
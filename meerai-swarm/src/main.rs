@@ -1,8 +1,37 @@
+use std::time::Duration;
+
 use agent_twitter_client::scraper::Scraper;
 use dotenv::dotenv;
-use meerai_core::OpenRouter;
+use meerai_agents::tools::{Approval, ApprovalRequest, ChannelApprover};
+use meerai_common::{
+    mcp::McpToolset,
+    tools::{MemoryToolset, WebConfig, WebToolset},
+};
+use meerai_core::{OpenRouter, ToolCacheConfig, ToolsetExt};
 use meerai_swarm::{config::load_config, log::init_logging, tools, workers::bluesky::BlueskyActor};
 use ractor::Actor;
+use tokio::{
+    io::{AsyncBufReadExt, BufReader},
+    sync::mpsc,
+};
+
+/// Asks on the terminal before the agent posts or runs other side-effecting tools.
+async fn review_tool_calls(mut requests: mpsc::Receiver<ApprovalRequest>) {
+    let mut answers = BufReader::new(tokio::io::stdin()).lines();
+    while let Some(request) = requests.recv().await {
+        println!(
+            "The agent wants to call `{}` with {}",
+            request.tool_call.name, request.tool_call.args
+        );
+        println!("Run it? [y/N]");
+
+        let approval = match answers.next_line().await {
+            Ok(Some(answer)) if answer.trim().eq_ignore_ascii_case("y") => Approval::Approve,
+            _ => Approval::Reject("the operator declined the call".to_string()),
+        };
+        request.respond(approval);
+    }
+}
 
 #[tokio::main]
 async fn main() {
@@ -21,19 +50,47 @@ async fn main() {
 
     // Clone config and create tools first to avoid partial moves
     let bluesky_config = config.bluesky.clone();
-    let x_toolset = tools::XToolset::new(scraper);
+    // Tweets rarely change, caching them spares the X rate limits
+    let x_toolset = tools::XToolset::new(scraper).cached(ToolCacheConfig {
+        ttl: Duration::from_secs(60 * 60),
+        ..Default::default()
+    });
+    let web_toolset = WebToolset::new(WebConfig::default()).expect("Failed to create WebToolset");
+    let memory_store = config
+        .memory
+        .open()
+        .await
+        .expect("Failed to open memory store");
 
     // Create BlueskyActor which internally manages its tools
-    let bluesky_actor = BlueskyActor::new(bluesky_config, llm_client.clone())
+    let mut bluesky_actor = BlueskyActor::new(bluesky_config, llm_client.clone())
         .await
         .expect("Failed to create BlueskyActor");
+    bluesky_actor
+        .add_tool(x_toolset)
+        .expect("Failed to add XToolset");
+    bluesky_actor
+        .add_tool(web_toolset)
+        .expect("Failed to add WebToolset");
+    bluesky_actor
+        .add_tool(MemoryToolset::new("bluesky-actor", memory_store))
+        .expect("Failed to add MemoryToolset");
+    let (approver, approval_requests) = ChannelApprover::new(1);
+    bluesky_actor.set_approver(approver);
+    tokio::spawn(review_tool_calls(approval_requests));
+    for (name, server_config) in &config.mcp_servers {
+        let mcp_toolset = McpToolset::connect(name, server_config)
+            .await
+            .expect("Failed to connect to MCP server");
+        bluesky_actor
+            .add_tool(mcp_toolset)
+            .expect("Failed to add McpToolset");
+    }
 
-    let prompt = "Read a tweet from X https://x.com/AltcoinDailyio/status/1906810513562239021, rephrase the content and then post the new version to Bluesky";
-
-    // Run with BlueskyActor
-    let (actor, actor_handle) = Actor::spawn(Some("bluesky-actor".to_string()), bluesky_actor, ())
+    // Run with BlueskyActor, prompts are sent to it by name
+    let (_actor, actor_handle) = Actor::spawn(Some("bluesky-actor".to_string()), bluesky_actor, ())
         .await
         .expect("Failed to spawn BlueskyActor");
 
-    actor_handle.await.expect("Failed to send prompt");
+    actor_handle.await.expect("BlueskyActor panicked");
 }
@@ -1,7 +1,8 @@
-use agent_twitter_client::scraper::Scraper;
+use agent_twitter_client::{error::TwitterError, models::Tweet, scraper::Scraper};
 use anyhow::{Error, Result};
 use meerai_core::{JsonSchema, ToolError, ToolOutput, async_trait};
 use meerai_macros::Toolset;
+use reqwest::StatusCode;
 
 #[derive(Debug, Clone, serde::Deserialize, serde::Serialize, JsonSchema)]
 pub struct ReadTweetArgs {
@@ -31,18 +32,79 @@ impl XToolset {
             .map(ToString::to_string)
             .ok_or(Error::msg("Invalid URL"))
     }
+
+    /// Keeps the parts of a tweet that are useful to the model.
+    fn tweet_to_json(tweet: &Tweet) -> serde_json::Value {
+        serde_json::json!({
+            "id": tweet.id,
+            "url": tweet.permanent_url,
+            "text": tweet.text,
+            "created_at": tweet.created_at,
+            "author": {
+                "id": tweet.user_id,
+                "username": tweet.username,
+                "name": tweet.name,
+            },
+            "media": {
+                "photos": tweet.photos.iter().map(|photo| serde_json::json!({
+                    "url": photo.url,
+                    "alt_text": photo.alt_text,
+                })).collect::<Vec<_>>(),
+                "videos": tweet.videos.iter().map(|video| serde_json::json!({
+                    "url": video.url,
+                    "preview": video.preview,
+                })).collect::<Vec<_>>(),
+            },
+            "metrics": {
+                "likes": tweet.likes,
+                "replies": tweet.replies,
+                "retweets": tweet.retweets,
+                "quotes": tweet.quote_count,
+                "bookmarks": tweet.bookmark_count,
+                "views": tweet.views,
+            },
+            "hashtags": tweet.hashtags,
+            "urls": tweet.urls,
+            "quoted_tweet": tweet.quoted_status.as_deref().map(Self::tweet_to_json),
+        })
+    }
+
+    /// Sorts a failed read by what can fix it: rate limits and server errors pass with time,
+    /// a missing or protected tweet is reported to the model, anything else stops the run.
+    fn read_error(err: TwitterError) -> Result<ToolOutput, ToolError> {
+        let message = format!("Failed to read tweet: {}", err);
+        match &err {
+            TwitterError::RateLimit => Err(ToolError::Transient(Error::msg(message))),
+            TwitterError::Api(api_message) => match Self::response_status(api_message) {
+                Some(status)
+                    if status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error() =>
+                {
+                    Err(ToolError::Transient(Error::msg(message)))
+                }
+                Some(StatusCode::UNAUTHORIZED) => Err(ToolError::Unknown(Error::msg(message))),
+                // Other client errors and empty results, e.g. "No tweets found"
+                _ => Ok(ToolOutput::Fail(message)),
+            },
+            // Left to the executor, which retries timeouts and connection failures
+            TwitterError::Network(_) => Err(ToolError::Unknown(Error::new(err).context(message))),
+            _ => Err(ToolError::Unknown(Error::msg(message))),
+        }
+    }
+
+    /// The scraper only reports the status of failed requests in the message.
+    fn response_status(api_message: &str) -> Option<StatusCode> {
+        let status = api_message.strip_prefix("Request failed with status: ")?;
+        StatusCode::from_bytes(status.split(' ').next()?.as_bytes()).ok()
+    }
 }
 
 #[async_trait]
 impl XToolsetInvoke for XToolset {
     async fn read_tweet(&self, args: &ReadTweetArgs) -> Result<ToolOutput, ToolError> {
         let id = Self::extract_tweet_id(&args.url)?;
-        self.scraper
-            .get_tweet(&id)
-            .await
-            .map_err(|err| {
-                ToolError::Unknown(anyhow::anyhow!("Failed to read tweet: {}", err.to_string()))
-            })
-            .map(|response| ToolOutput::Text(response.text.unwrap_or_default()))
+        match self.scraper.get_tweet(&id).await {
+            Ok(tweet) => Ok(ToolOutput::Json(Self::tweet_to_json(&tweet))),
+            Err(err) => Self::read_error(err),
+        }
     }
 }
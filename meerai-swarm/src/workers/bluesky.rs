@@ -1,12 +1,12 @@
-use std::{pin::Pin, time::Duration};
+use std::pin::Pin;
 
-use anyhow::{Result, anyhow};
 use bsky_sdk::BskyAgent;
+use meerai_agents::{agents::MultiTurnAgent, tools::Approver};
 use meerai_core::{
-    ToolCall, ToolOutput, Toolset,
-    chat_completion::{ChatCompletion, ChatCompletionRequest, ChatMessage},
+    Artifact, ToolRegistry, ToolRegistryError, Toolset, chat_completion::ChatCompletion,
 };
-use ractor::{Actor, ActorProcessingErr, ActorRef, async_trait};
+use ractor::{Actor, ActorProcessingErr, ActorRef, RpcReplyPort, async_trait};
+use tokio::sync::Mutex;
 
 use crate::{config::BlueskyConfig, tools};
 
@@ -23,131 +23,13 @@ Guidelines:
 - Be professional and thoughtful in all interactions
 - Tasks may require multiple steps - plan carefully
 - Always verify tool results before proceeding
+- If memory tools are available, check them before engaging and remember what you did, e.g. which users you already replied to
 - Use the stop tool when the task is complete
 ";
 
-/// Configuration for the BlueskyActor
-#[derive(Debug, Clone)]
-pub struct BlueskyAgentConfig {
-    /// Maximum number of cycles the agent will run in a single prompt
-    pub max_cycles: usize,
-
-    /// Maximum number of times to retry a failed tool invocation
-    pub max_retries: usize,
-}
-
-impl BlueskyActor {
-    async fn process_prompt(
-        &self,
-        prompt: &str,
-        chat_history: &mut Vec<ChatMessage>,
-    ) -> Result<(), anyhow::Error> {
-        let mut tool_definitions = Vec::new();
-        for tool in &self.tools {
-            tool_definitions.extend(tool.definition());
-        }
-
-        chat_history.clear();
-        chat_history.push(ChatMessage::System(DEFAULT_SYSTEM_PROMPT.to_string()));
-        chat_history.push(ChatMessage::User(prompt.to_string()));
-
-        let mut cycle_count = 0;
-
-        loop {
-            if cycle_count >= self.agent_config.max_cycles {
-                return Err(anyhow!(
-                    "Exceeded maximum number of cycles ({})",
-                    self.agent_config.max_cycles
-                ));
-            }
-
-            cycle_count += 1;
-            let request = ChatCompletionRequest {
-                model: None,
-                messages: chat_history.clone(),
-                tool_definitions: tool_definitions.clone(),
-            };
-
-            let response = self.chat_completion.send(&request).await?;
-            chat_history.extend(response.messages);
-
-            if response.tool_calls.is_empty() {
-                break;
-            }
-
-            for tool_call in response.tool_calls {
-                let ToolCall { name, args, .. } = tool_call;
-                let tool = self
-                    .tools
-                    .iter()
-                    .find(|t| t.contain(&name))
-                    .ok_or_else(|| anyhow!("Tool not found: {}", name))?;
-
-                let output = self.invoke_tool_with_retry(tool, &name, &args).await?;
-                chat_history.push(ChatMessage::Assistant(format!(
-                    "Tool {} result: {}",
-                    name, output
-                )));
-
-                if let ToolOutput::Stop(_) = output {
-                    chat_history.clear();
-                    break;
-                }
-            }
-        }
-
-        Ok(())
-    }
-
-    async fn invoke_tool_with_retry(
-        &self,
-        tool: &Pin<Box<dyn Toolset>>,
-        name: &str,
-        args: &str,
-    ) -> Result<ToolOutput> {
-        let mut retry_count = 0;
-
-        loop {
-            match tool.invoke(name, args).await {
-                Ok(output) => return Ok(output),
-                Err(err) => {
-                    retry_count += 1;
-                    if retry_count >= self.agent_config.max_retries {
-                        return Err(anyhow!(
-                            "Failed to invoke tool '{}' after {} retries: {}",
-                            name,
-                            self.agent_config.max_retries,
-                            err
-                        ));
-                    }
-                    tokio::time::sleep(Duration::from_millis(500)).await;
-                }
-            }
-        }
-    }
-}
-
-impl Default for BlueskyAgentConfig {
-    fn default() -> Self {
-        Self {
-            max_cycles: 10,
-            max_retries: 3,
-        }
-    }
-}
-
 pub struct BlueskyActor {
-    /// Configuration for the Bluesky tool
-    config: BlueskyConfig,
-
-    /// The underlying chat completion provider
-    chat_completion: Pin<Box<dyn ChatCompletion>>,
-
-    /// Available tools for the agent to use
-    tools: Vec<Pin<Box<dyn Toolset>>>,
-
-    /// Agent configuration
-    agent_config: BlueskyAgentConfig,
+    /// Runs the prompts, locked while a prompt is processed
+    agent: Mutex<MultiTurnAgent>,
 }
 
 impl BlueskyActor {
@@ -160,26 +42,43 @@ impl BlueskyActor {
             .login(&config.identifier, &config.password)
             .await?;
 
+        let agent = MultiTurnAgent::new(
+            chat_completion,
+            ToolRegistry::try_from(vec![
+                Box::pin(tools::BskyToolset::new(bsky_agent.clone())) as Pin<Box<dyn Toolset>>
+            ])?,
+            DEFAULT_SYSTEM_PROMPT.to_string(),
+        );
+
         Ok(Self {
-            config,
-            chat_completion: Box::pin(chat_completion),
-            tools: vec![Box::pin(tools::BskyToolset::new(bsky_agent.clone()))],
-            agent_config: BlueskyAgentConfig::default(),
+            agent: Mutex::new(agent),
         })
     }
 
-    pub fn add_tool(&mut self, tool: impl Toolset + 'static) {
-        self.tools.push(Box::pin(tool));
+    pub fn add_tool(&mut self, tool: impl Toolset + 'static) -> Result<(), ToolRegistryError> {
+        self.agent.get_mut().add_tool(tool)
+    }
+
+    pub fn add_tools(
+        &mut self,
+        tools: Vec<Pin<Box<dyn Toolset>>>,
+    ) -> Result<(), ToolRegistryError> {
+        self.agent.get_mut().add_tools(tools)
     }
 
-    pub fn add_tools(&mut self, tools: Vec<Pin<Box<dyn Toolset>>>) {
-        self.tools.extend(tools);
+    /// Requires approval from `approver` before posting or running other side-effecting tools.
+    pub fn set_approver(&mut self, approver: impl Approver + 'static) {
+        self.agent.get_mut().set_approver(approver);
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub enum BlueskyMessage {
     Prompt(String),
+
+    /// Replies with the artifacts produced by tools during the last prompt
+    Artifacts(RpcReplyPort<Vec<Artifact>>),
+
     Stop,
 }
 
@@ -193,9 +92,6 @@ enum Status {
 #[derive(Debug, Clone)]
 pub struct BlueskyState {
     status: Status,
-
-    /// History of the conversation
-    chat_history: Vec<ChatMessage>,
 }
 
 #[async_trait]
@@ -211,7 +107,6 @@ impl Actor for BlueskyActor {
     ) -> Result<Self::State, ActorProcessingErr> {
         Ok(Self::State {
             status: Status::Idle,
-            chat_history: vec![],
         })
     }
 
@@ -225,21 +120,23 @@ impl Actor for BlueskyActor {
             BlueskyMessage::Prompt(prompt) => match state.status {
                 Status::Idle => {
                     state.status = Status::Working;
-                    if let Err(e) = self.process_prompt(&prompt, &mut state.chat_history).await {
-                        eprintln!("Error processing prompt: {}", e);
-                        state.status = Status::Idle;
-                    } else {
-                        state.status = Status::Idle;
+                    match self.agent.lock().await.prompt(&prompt).await {
+                        Ok(answer) => tracing::info!(answer, "processed prompt"),
+                        Err(e) => tracing::error!(error = %e, "failed to process prompt"),
                     }
-                    state.chat_history.clear();
+                    state.status = Status::Idle;
                 }
                 Status::Working => {
-                    eprintln!("Actor is already working");
+                    tracing::warn!("actor is already working, dropping prompt");
                 }
                 Status::Stopped => {
-                    eprintln!("Actor is stopped");
+                    tracing::warn!("actor is stopped, dropping prompt");
                 }
             },
+            BlueskyMessage::Artifacts(reply) => {
+                // The caller may have stopped waiting, in which case there is nobody to tell
+                let _ = reply.send(self.agent.lock().await.artifacts().to_vec());
+            }
             BlueskyMessage::Stop => {
                 state.status = Status::Stopped;
                 myself.stop(Some("Stopped by user".to_string()));